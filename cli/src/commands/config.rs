@@ -0,0 +1,350 @@
+//! Configuration commands
+
+use crate::config::format::ConfigFormat;
+use crate::config::loader::parse_protocol;
+use crate::config::secrets::redact_config;
+use crate::config::writer::{parse_value, update_config_file};
+use crate::config::{CliConfigLoader, ConfigScope};
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use coro_core::{Protocol, ResolvedLlmConfig};
+use dialoguer::{Confirm, Input, Password, Select};
+use serde_json::Value;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// Config subcommands
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Create or update a config file with an interactive wizard
+    Init {
+        #[command(flatten)]
+        target: TargetArgs,
+    },
+
+    /// Check that every profile resolves and its API is reachable
+    Validate {
+        /// Skip the connectivity check
+        #[arg(long)]
+        offline: bool,
+    },
+
+    /// Set a value in a config file, e.g. `coro config set agent.max_steps 100`
+    Set {
+        /// Dotted key of the setting
+        key: String,
+        /// New value; numbers, booleans, arrays and objects are read as JSON
+        value: String,
+        #[command(flatten)]
+        target: TargetArgs,
+    },
+
+    /// Show the effective configuration merged from all config files and flags
+    ///
+    /// Literal API keys and header values are redacted.
+    Show {
+        /// Show which file or flag each value comes from
+        #[arg(long)]
+        origin: bool,
+    },
+
+    /// Print the JSON Schema of config files, for editor validation
+    Schema,
+}
+
+/// Config file written by `init` and `set`; `--config` takes precedence
+#[derive(Args)]
+pub struct TargetArgs {
+    /// Write to the user config instead of the project config
+    #[arg(long, conflicts_with = "local")]
+    user: bool,
+
+    /// Write to the untracked local project config, e.g. for secrets
+    #[arg(long)]
+    local: bool,
+
+    /// Format of the file if it does not exist yet
+    #[arg(long, value_enum, default_value_t = ConfigFormat::Json)]
+    format: ConfigFormat,
+
+    /// Rewrite a YAML file even though its comments are lost
+    #[arg(long)]
+    force: bool,
+}
+
+impl TargetArgs {
+    fn scope(&self) -> ConfigScope {
+        if self.user {
+            ConfigScope::User
+        } else if self.local {
+            ConfigScope::Local
+        } else {
+            ConfigScope::Project
+        }
+    }
+}
+
+/// Protocols offered by the init wizard: name, label, default model and API key variable
+const WIZARD_PROTOCOLS: [(&str, &str, &str, &str); 4] = [
+    (
+        "openai",
+        "OpenAI or an OpenAI-compatible API",
+        "gpt-4o",
+        "OPENAI_API_KEY",
+    ),
+    (
+        "anthropic",
+        "Anthropic",
+        "claude-sonnet-4-20250514",
+        "ANTHROPIC_API_KEY",
+    ),
+    ("google_ai", "Google AI", "gemini-1.5-pro", "GOOGLE_API_KEY"),
+    (
+        "azure_openai",
+        "Azure OpenAI",
+        "gpt-4o",
+        "AZURE_OPENAI_API_KEY",
+    ),
+];
+
+/// Time the connectivity check may take per profile
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run a config subcommand
+pub async fn config_command(command: ConfigCommand, config_loader: CliConfigLoader) -> Result<()> {
+    match command {
+        ConfigCommand::Init { target } => init_config(&config_loader, &target),
+        ConfigCommand::Validate { offline } => validate_config(&config_loader, offline).await,
+        ConfigCommand::Set { key, value, target } => {
+            let path = config_loader.config_file_for(target.scope(), target.format)?;
+            update_config_file(&path, &[(&key, Some(parse_value(&value)))], target.force)?;
+            println!("✅ Set {} in {}", key, path.display());
+            Ok(())
+        }
+        ConfigCommand::Show { origin } => show_config(&config_loader, origin).await,
+        ConfigCommand::Schema => {
+            let schema = crate::config::schema::config_schema();
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+            Ok(())
+        }
+    }
+}
+
+fn init_config(config_loader: &CliConfigLoader, target: &TargetArgs) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        bail!("coro config init needs a terminal; use coro config set in scripts");
+    }
+
+    let scope = target.scope();
+    let path = config_loader.config_file_for(scope, target.format)?;
+    println!("⚙️  Configuring the model in {}", path.display());
+
+    let labels: Vec<_> = WIZARD_PROTOCOLS.iter().map(|p| p.1).collect();
+    let Some(index) = Select::new()
+        .with_prompt("Protocol")
+        .items(&labels)
+        .default(0)
+        .interact_opt()?
+    else {
+        bail!("Aborted");
+    };
+    let (protocol, _, default_model, key_variable) = WIZARD_PROTOCOLS[index];
+
+    let model: String = Input::new()
+        .with_prompt("Model")
+        .default(default_model.to_string())
+        .interact_text()?;
+
+    // Only write the base URL when it differs from the protocol default
+    let default_base_url = parse_protocol(protocol).default_base_url();
+    let mut base_url_input = Input::<String>::new().with_prompt("Base URL");
+    if let Some(default_base_url) = default_base_url {
+        base_url_input = base_url_input.default(default_base_url.to_string());
+    }
+    let base_url = base_url_input.interact_text()?;
+    let base_url = (Some(base_url.as_str()) != default_base_url).then_some(base_url);
+
+    let key_sources = [
+        "Environment variable",
+        "Command, e.g. a password manager",
+        "File",
+        "Enter the key now",
+    ];
+    let Some(source) = Select::new()
+        .with_prompt("API key source")
+        .items(&key_sources)
+        .default(0)
+        .interact_opt()?
+    else {
+        bail!("Aborted");
+    };
+    let api_key = match source {
+        0 => {
+            let variable: String = Input::new()
+                .with_prompt("Environment variable")
+                .default(key_variable.to_string())
+                .interact_text()?;
+            format!("env:{}", variable)
+        }
+        1 => {
+            let command: String = Input::new()
+                .with_prompt("Command printing the key")
+                .interact_text()?;
+            format!("cmd:{}", command)
+        }
+        2 => {
+            let file: String = Input::new()
+                .with_prompt("File containing the key")
+                .interact_text()?;
+            format!("file:{}", file)
+        }
+        _ => {
+            if scope == ConfigScope::Project {
+                println!(
+                    "⚠️  The key is stored in plain text in a committed file; consider --local"
+                );
+            }
+            Password::new().with_prompt("API key").interact()?
+        }
+    };
+
+    if path.exists()
+        && !Confirm::new()
+            .with_prompt(format!("Update {}?", path.display()))
+            .default(true)
+            .interact()?
+    {
+        bail!("Aborted");
+    }
+
+    update_config_file(
+        &path,
+        &[
+            ("protocol", Some(Value::from(protocol))),
+            ("model", Some(Value::from(model))),
+            ("api_key", Some(Value::from(api_key))),
+            ("base_url", base_url.map(Value::from)),
+        ],
+        target.force,
+    )?;
+
+    println!("✅ Wrote {}", path.display());
+    println!("   Run `coro config validate` to check the connection");
+    Ok(())
+}
+
+async fn validate_config(config_loader: &CliConfigLoader, offline: bool) -> Result<()> {
+    let mut problems = 0;
+
+    match config_loader.load_settings().await {
+        Ok(_) => println!("✅ agent and tools settings"),
+        Err(e) => {
+            problems += 1;
+            println!("❌ agent and tools settings: {:#}", e);
+        }
+    }
+
+    for (name, resolved) in config_loader.resolve_profiles().await? {
+        let checked = match resolved {
+            Ok(config) if offline => Ok(config),
+            Ok(config) => check_connection(&config).await.map(|_| config),
+            Err(e) => Err(e),
+        };
+        match checked {
+            Ok(config) => println!(
+                "✅ profile {}: {} via {} at {}",
+                name,
+                config.model,
+                config.protocol.as_str(),
+                config.base_url
+            ),
+            Err(e) => {
+                problems += 1;
+                println!("❌ profile {}: {:#}", name, e);
+            }
+        }
+    }
+
+    if problems > 0 {
+        bail!("Found {} configuration problem(s)", problems);
+    }
+    Ok(())
+}
+
+/// Check that the API accepts the credentials by listing models, which costs no tokens
+pub async fn check_connection(config: &ResolvedLlmConfig) -> Result<()> {
+    let base_url = config.base_url.trim_end_matches('/');
+    let client = reqwest::Client::builder()
+        .timeout(CONNECTION_TIMEOUT)
+        .build()?;
+
+    let request = match config.protocol {
+        Protocol::Anthropic => client
+            .get(format!("{}/v1/models", base_url))
+            .header("x-api-key", &config.api_key)
+            .header("anthropic-version", "2023-06-01"),
+        Protocol::GoogleAI => client
+            .get(format!("{}/models", base_url))
+            .header("x-goog-api-key", &config.api_key),
+        Protocol::AzureOpenAI => client
+            .get(format!("{}/models", base_url))
+            .header("api-key", &config.api_key),
+        Protocol::OpenAICompat | Protocol::Custom(_) => client
+            .get(format!("{}/models", base_url))
+            .bearer_auth(&config.api_key),
+    };
+    let request = config
+        .headers
+        .iter()
+        .fold(request, |request, (name, value)| {
+            request.header(name, value)
+        });
+
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Cannot reach {}", base_url))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    if matches!(status.as_u16(), 401 | 403) {
+        bail!("{} rejected the API key ({})", base_url, status);
+    }
+    let body: String = response
+        .text()
+        .await
+        .unwrap_or_default()
+        .chars()
+        .take(200)
+        .collect();
+    bail!("{} answered {}: {}", base_url, status, body.trim());
+}
+
+async fn show_config(config_loader: &CliConfigLoader, origin: bool) -> Result<()> {
+    let mut merged = config_loader.load_merged().await?;
+    redact_config(&mut merged.value);
+
+    if !origin {
+        println!("{}", serde_json::to_string_pretty(&merged.value)?);
+        return Ok(());
+    }
+
+    let entries: Vec<_> = merged
+        .entries()
+        .into_iter()
+        .map(|(path, value, origin)| (format!("{} = {}", path, value), origin))
+        .collect();
+    let width = entries
+        .iter()
+        .map(|(line, _)| line.len())
+        .max()
+        .unwrap_or(0);
+
+    for (line, origin) in entries {
+        println!("{:width$}  # {}", line, origin, width = width);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,436 @@
+//! Environment diagnostics
+
+use super::config::check_connection;
+use crate::config::format::ConfigFormat;
+use crate::config::{CliConfigLoader, ConfigScope, Settings};
+use crate::interactive::input_history::InputHistory;
+use crate::session::SessionStore;
+use crate::tools::bash::ShellConfig;
+use crate::tools::ckg::{database_stats, DEFAULT_DB_PATH};
+use crate::tools::get_default_cli_tools;
+use anyhow::{anyhow, bail, Context, Result};
+use coro_core::tools::builtin::{McpServer, McpServerConfig};
+use serde::Serialize;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Time a probed program may take to answer
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Warning,
+    Error,
+    Skipped,
+}
+
+impl Status {
+    fn icon(self) -> &'static str {
+        match self {
+            Status::Ok => "✅",
+            Status::Warning => "⚠️ ",
+            Status::Error => "❌",
+            Status::Skipped => "⏭️ ",
+        }
+    }
+}
+
+/// A single diagnostic
+#[derive(Debug, Serialize)]
+struct Check {
+    name: String,
+    status: Status,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn from_result(name: impl Into<String>, result: Result<String>) -> Self {
+        match result {
+            Ok(detail) => Self::new(name, Status::Ok, detail),
+            Err(e) => Self::new(name, Status::Error, format!("{:#}", e)),
+        }
+    }
+}
+
+/// Report printed by `coro doctor --json`
+#[derive(Debug, Serialize)]
+struct Report {
+    version: &'static str,
+    checks: Vec<Check>,
+}
+
+/// Diagnose the configuration and environment
+pub async fn doctor_command(
+    config_loader: CliConfigLoader,
+    json: bool,
+    offline: bool,
+) -> Result<()> {
+    let mut checks = Vec::new();
+
+    checks.push(match config_loader.config_paths() {
+        Ok(paths) if paths.is_empty() => Check::new(
+            "config",
+            Status::Warning,
+            "no config file found, using environment variables",
+        ),
+        Ok(paths) => Check::new(
+            "config",
+            Status::Ok,
+            paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        Err(e) => Check::new("config", Status::Error, format!("{:#}", e)),
+    });
+
+    let settings = match config_loader.load_settings().await {
+        Ok(settings) => settings,
+        Err(e) => {
+            checks.push(Check::new("settings", Status::Error, format!("{:#}", e)));
+            Settings::default()
+        }
+    };
+
+    let llm_config = match config_loader.load().await {
+        Ok(config) => {
+            checks.push(Check::new(
+                "model",
+                Status::Ok,
+                format!(
+                    "{} via {} at {}",
+                    config.model,
+                    config.protocol.as_str(),
+                    config.base_url
+                ),
+            ));
+            Some(config)
+        }
+        Err(e) => {
+            checks.push(Check::new("model", Status::Error, format!("{:#}", e)));
+            None
+        }
+    };
+
+    checks.push(match llm_config {
+        _ if offline => Check::new("api", Status::Skipped, "skipped with --offline"),
+        None => Check::new("api", Status::Skipped, "no model configured"),
+        Some(config) => Check::from_result(
+            "api",
+            check_connection(&config)
+                .await
+                .map(|_| "reachable and authenticated".to_string()),
+        ),
+    });
+
+    checks.push(check_shell().await);
+    checks.push(check_git().await);
+    checks.push(check_ckg(&settings));
+    checks.extend(check_mcp_servers(settings.tools.mcp.server_configs()).await);
+    checks.push(check_terminal());
+    checks.extend(check_data_dirs(&config_loader));
+
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == Status::Error)
+        .count();
+    let report = Report {
+        version: env!("CARGO_PKG_VERSION"),
+        checks,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if failed > 0 {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    println!("🩺 coro {}", report.version);
+    let width = report
+        .checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or(0);
+    for check in &report.checks {
+        println!(
+            "{} {:width$}  {}",
+            check.status.icon(),
+            check.name,
+            check.detail,
+            width = width
+        );
+    }
+
+    if failed > 0 {
+        bail!("{} check(s) failed", failed);
+    }
+    Ok(())
+}
+
+/// Run a program and return the first line it prints
+async fn probe(program: &str, args: &[&str]) -> Result<String> {
+    let output = tokio::time::timeout(
+        PROBE_TIMEOUT,
+        Command::new(program).args(args).kill_on_drop(true).output(),
+    )
+    .await
+    .map_err(|_| anyhow!("{} did not answer within {:?}", program, PROBE_TIMEOUT))?
+    .with_context(|| format!("Cannot run {}", program))?;
+
+    if !output.status.success() {
+        bail!("{} exited with {}", program, output.status);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default()
+        .trim()
+        .to_string())
+}
+
+async fn check_shell() -> Check {
+    let shell = ShellConfig::new();
+    let args: &[&str] = if cfg!(target_os = "windows") {
+        &["/C", "ver"]
+    } else {
+        &["--version"]
+    };
+    let version = probe(shell.program(), args).await;
+    Check::from_result(
+        "shell",
+        version.map(|version| format!("{} ({})", shell.program(), version)),
+    )
+}
+
+async fn check_git() -> Check {
+    let version = match probe("git", &["--version"]).await {
+        Ok(version) => version,
+        Err(e) => {
+            return Check::new(
+                "git",
+                Status::Warning,
+                format!("{:#}; git features are unavailable", e),
+            )
+        }
+    };
+
+    let detail = match probe("git", &["rev-parse", "--show-toplevel"]).await {
+        Ok(root) => format!("{}, repository at {}", version, root),
+        Err(_) => format!("{}, not inside a repository", version),
+    };
+    Check::new("git", Status::Ok, detail)
+}
+
+fn check_ckg(settings: &Settings) -> Check {
+    if !settings
+        .tools
+        .tool_names(get_default_cli_tools())
+        .iter()
+        .any(|tool| tool == "ckg_tool")
+    {
+        return Check::new("ckg", Status::Skipped, "ckg_tool is disabled");
+    }
+
+    let db_path = settings
+        .tools
+        .ckg
+        .db_path
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_DB_PATH));
+    if !db_path.exists() {
+        return Check::new(
+            "ckg",
+            Status::Ok,
+            format!("{} not built yet, created on first use", db_path.display()),
+        );
+    }
+
+    Check::from_result(
+        "ckg",
+        database_stats(&db_path)
+            .map(|(symbols, files)| {
+                format!(
+                    "{}: {} symbols in {} files",
+                    db_path.display(),
+                    symbols,
+                    files
+                )
+            })
+            .map_err(|e| anyhow!("{}: {}", db_path.display(), e)),
+    )
+}
+
+async fn check_mcp_servers(servers: Vec<McpServerConfig>) -> Vec<Check> {
+    if servers.is_empty() {
+        return vec![Check::new("mcp", Status::Skipped, "no servers configured")];
+    }
+
+    let mut checks = Vec::new();
+    for config in servers {
+        let name = format!("mcp:{}", config.name);
+        let mut server = McpServer::new(config);
+        let tools = async {
+            server.start().await?;
+            server.list_tools().await
+        }
+        .await;
+        server.stop();
+
+        checks.push(Check::from_result(
+            name,
+            tools
+                .map(|tools| format!("started, {} tools", tools.len()))
+                .map_err(|e| anyhow!("{}", e)),
+        ));
+    }
+    checks
+}
+
+fn check_terminal() -> Check {
+    let tty = |is_terminal: bool| {
+        if is_terminal {
+            "terminal"
+        } else {
+            "not a terminal"
+        }
+    };
+    let stdout_is_terminal = std::io::stdout().is_terminal();
+
+    let mut details = vec![
+        format!("stdin {}", tty(std::io::stdin().is_terminal())),
+        format!("stdout {}", tty(stdout_is_terminal)),
+    ];
+    if let Ok((columns, rows)) = crossterm::terminal::size() {
+        details.push(format!("{}x{}", columns, rows));
+    }
+    for variable in ["TERM", "COLORTERM", "NO_COLOR"] {
+        if let Ok(value) = std::env::var(variable) {
+            details.push(format!("{}={}", variable, value));
+        }
+    }
+
+    let status = if stdout_is_terminal {
+        Status::Ok
+    } else {
+        Status::Warning
+    };
+    Check::new("terminal", status, details.join(", "))
+}
+
+fn check_data_dirs(config_loader: &CliConfigLoader) -> Vec<Check> {
+    let user_config = config_loader
+        .config_file_for(ConfigScope::User, ConfigFormat::Json)
+        .map(|path| path.parent().map(Path::to_path_buf).unwrap_or(path));
+    let history = InputHistory::default_history_path();
+    let dirs = [
+        ("data:sessions", SessionStore::default_dir()),
+        (
+            "data:history",
+            Ok(history.parent().map(Path::to_path_buf).unwrap_or(history)),
+        ),
+        ("data:config", user_config),
+    ];
+
+    dirs.into_iter()
+        .map(|(name, dir)| Check::from_result(name, dir.and_then(|dir| check_writable(&dir))))
+        .collect()
+}
+
+/// Check that a directory can be created and written to
+fn check_writable(dir: &Path) -> Result<String> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+    let probe = dir.join(".coro-doctor");
+    std::fs::write(&probe, b"").with_context(|| format!("Cannot write to {}", dir.display()))?;
+    std::fs::remove_file(&probe).ok();
+    Ok(format!("{} is writable", dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_writable() {
+        let dir = TempDir::new().unwrap();
+        let data_dir = dir.path().join("coro").join("sessions");
+        let detail = check_writable(&data_dir).unwrap();
+        assert_eq!(detail, format!("{} is writable", data_dir.display()));
+        assert_eq!(std::fs::read_dir(&data_dir).unwrap().count(), 0);
+
+        // A directory cannot be created below a file
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert!(check_writable(&file.join("sessions")).is_err());
+    }
+
+    #[test]
+    fn test_check_ckg() {
+        let mut settings = Settings::default();
+        settings.tools.disabled = vec!["ckg_tool".to_string()];
+        assert_eq!(check_ckg(&settings).status, Status::Skipped);
+
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("ckg.db");
+        let mut settings = Settings::default();
+        settings.tools.ckg.db_path = Some(db_path.clone());
+        let check = check_ckg(&settings);
+        assert_eq!(check.status, Status::Ok);
+        assert!(check.detail.contains("not built yet"));
+
+        std::fs::write(&db_path, "not a database").unwrap();
+        assert_eq!(check_ckg(&settings).status, Status::Error);
+    }
+
+    #[tokio::test]
+    async fn test_check_mcp_servers_without_servers() {
+        let checks = check_mcp_servers(Vec::new()).await;
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].name, "mcp");
+        assert_eq!(checks[0].status, Status::Skipped);
+    }
+
+    #[test]
+    fn test_report_json() {
+        let report = Report {
+            version: "1.2.3",
+            checks: vec![
+                Check::new("git", Status::Ok, "git version 2.43.0"),
+                Check::from_result("api", Err(anyhow!("401 Unauthorized"))),
+                Check::new("terminal", Status::Warning, "stdout not a terminal"),
+                Check::new("mcp", Status::Skipped, "no servers configured"),
+            ],
+        };
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({
+                "version": "1.2.3",
+                "checks": [
+                    { "name": "git", "status": "ok", "detail": "git version 2.43.0" },
+                    { "name": "api", "status": "error", "detail": "401 Unauthorized" },
+                    { "name": "terminal", "status": "warning", "detail": "stdout not a terminal" },
+                    { "name": "mcp", "status": "skipped", "detail": "no servers configured" }
+                ]
+            })
+        );
+    }
+}
@@ -1,15 +1,33 @@
 //! Interactive mode command
 
+use crate::commands::sessions::current_project_path;
 use crate::interactive::app::run_rich_interactive;
-use anyhow::Result;
-use std::path::PathBuf;
-use tracing::debug;
+use crate::session::{Session, SessionRecorder, SessionStore};
+use anyhow::{bail, Result};
+use coro_core::ResolvedLlmConfig;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Which session interactive mode should start with
+#[derive(Debug, Clone, Default)]
+pub enum SessionSelection {
+    /// Start a fresh session
+    #[default]
+    New,
+    /// Continue the most recent session in the current directory
+    Continue,
+    /// Resume a session by id, or pick one interactively when no id is given
+    Resume(Option<String>),
+}
 
 /// Start interactive mode
 pub async fn interactive_command(
     config_loader: crate::config::CliConfigLoader,
     trajectory_file: Option<PathBuf>,
     debug_output: bool,
+    session_selection: SessionSelection,
+    fork_session: bool,
 ) -> Result<()> {
     if debug_output {
         debug!("Debug output enabled");
@@ -21,19 +39,112 @@ pub async fn interactive_command(
 
     // Load LLM configuration
     let llm_config = config_loader.load().await?;
+    let profiles = config_loader.load_profiles().await?;
+    let settings = config_loader.load_settings().await?;
     if debug_output {
         debug!("Using protocol: {}", llm_config.protocol.as_str());
         debug!("Using model: {}", llm_config.model);
     }
 
     // Get current working directory
-    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-    let project_path = current_dir.canonicalize().unwrap_or(current_dir);
+    let project_path = current_project_path();
 
     if debug_output {
         debug!("Project path: {}", project_path.display());
     }
 
+    // Start a new session or load the one to resume
+    let store = SessionStore::open_default()?;
+    let mut session = select_session(&store, &project_path, &llm_config, session_selection)?;
+    if fork_session {
+        session = session.fork();
+    }
+
+    if session.project_path != project_path {
+        warn!(
+            "Session {} was started in {}, continuing in {}",
+            session.short_id(),
+            session.project_path.display(),
+            project_path.display()
+        );
+    }
+
+    if debug_output {
+        debug!("Session: {}", session.id);
+    }
+
+    let session_recorder = SessionRecorder::new(store, session, llm_config.clone());
+
     // Run the interactive mode (always use rich mode)
-    run_rich_interactive(llm_config, project_path, debug_output).await
+    run_rich_interactive(
+        llm_config,
+        profiles,
+        settings,
+        project_path,
+        debug_output,
+        session_recorder,
+    )
+    .await
+}
+
+/// Resolve the session selection into a session to run
+fn select_session(
+    store: &SessionStore,
+    project_path: &Path,
+    llm_config: &ResolvedLlmConfig,
+    selection: SessionSelection,
+) -> Result<Session> {
+    match selection {
+        SessionSelection::New => Ok(Session::new(project_path.to_path_buf(), llm_config)),
+        SessionSelection::Continue => match store.latest_for_project(project_path)? {
+            Some(session) => Ok(session),
+            None => bail!(
+                "No saved session found in {}; start a new one with `coro`",
+                project_path.display()
+            ),
+        },
+        SessionSelection::Resume(Some(id)) => store.load(&id),
+        SessionSelection::Resume(None) => pick_session(store, project_path),
+    }
+}
+
+/// Let the user pick one of the sessions saved for this directory
+fn pick_session(store: &SessionStore, project_path: &Path) -> Result<Session> {
+    let mut sessions = store.list_for_project(project_path)?;
+    if sessions.is_empty() {
+        bail!(
+            "No saved sessions found in {}; use `coro sessions list --all` to see all sessions",
+            project_path.display()
+        );
+    }
+
+    if !std::io::stdin().is_terminal() {
+        bail!("Cannot pick a session without a terminal; pass a session id to --resume");
+    }
+
+    let items: Vec<String> = sessions
+        .iter()
+        .map(|session| {
+            format!(
+                "{}  {}  {}",
+                session.short_id(),
+                session
+                    .updated_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M"),
+                session.title()
+            )
+        })
+        .collect();
+
+    let selected = dialoguer::Select::new()
+        .with_prompt("Resume session")
+        .items(&items)
+        .default(0)
+        .interact_opt()?;
+
+    match selected {
+        Some(index) => Ok(sessions.swap_remove(index)),
+        None => bail!("No session selected"),
+    }
 }
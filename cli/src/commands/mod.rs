@@ -1,11 +1,17 @@
 //! CLI command implementations
 
+pub mod config;
+pub mod doctor;
 pub mod interactive;
 pub mod run;
+pub mod sessions;
 pub mod test;
 pub mod tools;
 
+pub use config::config_command;
+pub use doctor::doctor_command;
 pub use interactive::interactive_command;
 pub use run::run_command;
+pub use sessions::sessions_command;
 pub use test::test_command;
 pub use tools::tools_command;
@@ -1,12 +1,111 @@
 //! Single task execution command
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use std::io::Read;
 use std::path::PathBuf;
 use tracing::{debug, info};
 
+/// Task text together with any extra context piped in on stdin
+pub struct TaskInput {
+    pub task: String,
+    pub piped_context: Option<String>,
+}
+
+/// Resolve the task from the positional argument, `-p/--prompt` or `--task-file`
+///
+/// `-p -` reads the task itself from stdin. Otherwise, when stdin is piped
+/// (e.g. `cargo test 2>&1 | coro "fix these failures"`), its content is
+/// returned as extra context for the task.
+pub fn read_task_input(
+    task: Option<String>,
+    prompt: Option<String>,
+    task_file: Option<PathBuf>,
+) -> Result<Option<TaskInput>> {
+    resolve_task_input(
+        task,
+        prompt,
+        task_file,
+        &mut std::io::stdin(),
+        stdin_is_piped(),
+    )
+}
+
+fn resolve_task_input(
+    task: Option<String>,
+    prompt: Option<String>,
+    task_file: Option<PathBuf>,
+    stdin: &mut dyn Read,
+    stdin_piped: bool,
+) -> Result<Option<TaskInput>> {
+    let (task, stdin_consumed) = match (task, prompt, task_file) {
+        (_, Some(prompt), _) if prompt == "-" => (read_all(stdin)?, true),
+        (Some(task), _, _) | (_, Some(task), _) => (task, false),
+        (_, _, Some(path)) => {
+            let task = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read task file: {}", path.display()))?;
+            (task, false)
+        }
+        _ => return Ok(None),
+    };
+
+    let task = task.trim().to_string();
+    if task.is_empty() {
+        bail!("Task is empty");
+    }
+
+    let piped_context = if !stdin_consumed && stdin_piped {
+        Some(read_all(stdin)?).filter(|content| !content.trim().is_empty())
+    } else {
+        None
+    };
+
+    Ok(Some(TaskInput {
+        task,
+        piped_context,
+    }))
+}
+
+/// Whether stdin is a pipe or a redirected file
+///
+/// A closed or inherited stdin (e.g. `/dev/null` under cron, or a socket in
+/// CI) is not treated as input, so reading it can never block the task.
+#[cfg(unix)]
+fn stdin_is_piped() -> bool {
+    use std::os::fd::AsFd;
+    use std::os::unix::fs::FileTypeExt;
+
+    let Ok(fd) = std::io::stdin().as_fd().try_clone_to_owned() else {
+        return false;
+    };
+    std::fs::File::from(fd)
+        .metadata()
+        .map(|metadata| {
+            let file_type = metadata.file_type();
+            file_type.is_fifo() || file_type.is_file()
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn stdin_is_piped() -> bool {
+    use std::io::IsTerminal;
+
+    !std::io::stdin().is_terminal()
+}
+
+/// Read all of `reader` as (lossy) UTF-8 text
+fn read_all(reader: &mut dyn Read) -> Result<String> {
+    let mut buffer = Vec::new();
+    reader
+        .read_to_end(&mut buffer)
+        .context("Failed to read from stdin")?;
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
 /// Configuration for running a single task
 pub struct RunConfig {
     pub task: String,
+    pub piped_context: Option<String>,
     pub config_loader: crate::config::CliConfigLoader,
     pub max_steps: Option<usize>,
     pub trajectory_file: Option<PathBuf>,
@@ -14,25 +113,58 @@ pub struct RunConfig {
     pub patch_path: PathBuf,
     pub working_dir: Option<PathBuf>,
     pub debug_output: bool,
+    pub output_format: crate::output::json_handler::OutputFormat,
 }
 
-/// Execute a single task
-pub async fn run_command(config: RunConfig) -> Result<()> {
+/// Execute a single task, returning the process exit code
+///
+/// With a JSON output format every failure, including a missing model or a
+/// broken config file, is reported as a result record and turned into an
+/// exit code. The agent and its background jobs are dropped before this
+/// returns, so the caller can exit the process right away.
+pub async fn run_command(config: RunConfig) -> Result<i32> {
+    use crate::output::json_handler::JsonOutputHandler;
+    use coro_core::agent::AgentExecution;
+
     info!("Executing task: {}", config.task);
 
+    let json_output = config
+        .output_format
+        .is_json()
+        .then(|| JsonOutputHandler::stdout(config.output_format));
+    let result = execute_task(config, json_output.clone()).await;
+
+    match json_output {
+        // Headless consumers always get a result record, even for hard errors
+        Some(json_output) => {
+            let execution =
+                result.unwrap_or_else(|e| AgentExecution::failure(format!("{:#}", e), 0, 0));
+            Ok(json_output.finish(&execution)?)
+        }
+        None => {
+            result?;
+            info!("✅ Task completed successfully");
+            Ok(0)
+        }
+    }
+}
+
+async fn execute_task(
+    config: RunConfig,
+    json_output: Option<crate::output::json_handler::JsonOutputHandler>,
+) -> Result<coro_core::agent::AgentExecution> {
     use crate::output::cli_handler::{CliOutputConfig, CliOutputHandler};
-    use coro_core::{trajectory::TrajectoryRecorder, AgentBuilder, AgentConfig, OutputMode};
+    use coro_core::output::AgentOutput;
+    use coro_core::{trajectory::TrajectoryRecorder, AgentBuilder, OutputMode};
 
     // Load LLM configuration
     let llm_config = config.config_loader.load().await?;
     info!("🤖 Using protocol: {}", llm_config.protocol.as_str());
     info!("🤖 Using model: {}", llm_config.model);
 
-    // Create agent configuration with CLI tools
-    let mut agent_config = AgentConfig {
-        tools: crate::tools::get_default_cli_tools(),
-        ..Default::default()
-    };
+    // Create agent configuration with CLI tools and the config file settings
+    let settings = config.config_loader.load_settings().await?;
+    let mut agent_config = settings.agent_config(crate::tools::get_default_cli_tools());
     if let Some(steps) = config.max_steps {
         agent_config.max_steps = steps;
     }
@@ -40,24 +172,30 @@ pub async fn run_command(config: RunConfig) -> Result<()> {
         agent_config.output_mode = OutputMode::Debug;
     }
 
-    // Create CLI output handler
-    let cli_config = CliOutputConfig {
-        realtime_updates: true, // Always enable realtime updates for better UX
+    // Create output handler for the requested format
+    let output: Box<dyn AgentOutput> = match json_output {
+        Some(json_output) => Box::new(json_output),
+        None => {
+            let cli_config = CliOutputConfig {
+                realtime_updates: true, // Always enable realtime updates for better UX
+            };
+            Box::new(CliOutputHandler::new(cli_config))
+        }
     };
-    let cli_output = Box::new(CliOutputHandler::new(cli_config));
 
     // Build agent with new configuration system and CLI tools
-    let cli_tool_registry = crate::tools::create_cli_tool_registry();
+    let cli_tool_registry = crate::tools::create_configured_tool_registry(&settings.tools);
+    let max_steps = agent_config.max_steps;
     let agent = AgentBuilder::new(llm_config)
         .with_agent_config(agent_config)
-        .build_with_output_and_registry(cli_output, cli_tool_registry)
+        .build_with_output_and_registry(output, cli_tool_registry)
         .await?;
 
     // Initialize trajectory recorder
     let trajectory = TrajectoryRecorder::new();
     let task_entry = coro_core::trajectory::TrajectoryEntry::task_start(
         config.task.clone(),
-        serde_json::json!({"max_steps": config.max_steps.unwrap_or(200)}),
+        serde_json::json!({ "max_steps": max_steps }),
     );
     trajectory.record(task_entry).await?;
 
@@ -77,9 +215,15 @@ pub async fn run_command(config: RunConfig) -> Result<()> {
 
     // Execute the task using the agent
     let mut agent = agent; // Make mutable for execution
-    let _execution_result = agent
+    if let Some(piped_context) = &config.piped_context {
+        debug!("📥 Attaching {} bytes of piped input", piped_context.len());
+    }
+    agent.set_initial_context(config.piped_context.clone());
+    let execution = agent
         .execute_task_with_context(&config.task, &project_path)
         .await?;
+    // Background jobs of the agent's tools end with it
+    drop(agent);
 
     if config.must_patch {
         info!("📄 Creating patch file: {}", config.patch_path.display());
@@ -94,7 +238,64 @@ pub async fn run_command(config: RunConfig) -> Result<()> {
         info!("📊 Trajectory saved to: {}", trajectory_file.display());
     }
 
-    info!("✅ Task completed successfully");
+    Ok(execution)
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stdin that must never be read
+    struct UnreadableStdin;
+
+    impl Read for UnreadableStdin {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            panic!("stdin should not be read");
+        }
+    }
+
+    #[test]
+    fn test_task_input_without_piped_stdin() {
+        let input = resolve_task_input(
+            Some("  fix the build ".to_string()),
+            None,
+            None,
+            &mut UnreadableStdin,
+            false,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(input.task, "fix the build");
+        assert!(input.piped_context.is_none());
+
+        let input = resolve_task_input(None, None, None, &mut UnreadableStdin, false).unwrap();
+        assert!(input.is_none());
+    }
+
+    #[test]
+    fn test_task_input_with_piped_stdin() {
+        let mut stdin: &[u8] = b"error[E0308]: mismatched types\n";
+        let input = resolve_task_input(
+            None,
+            Some("fix these failures".to_string()),
+            None,
+            &mut stdin,
+            true,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(input.task, "fix these failures");
+        assert_eq!(
+            input.piped_context.as_deref(),
+            Some("error[E0308]: mismatched types\n")
+        );
+
+        // `-p -` takes the task itself from stdin
+        let mut stdin: &[u8] = b"explain this repo\n";
+        let input = resolve_task_input(None, Some("-".to_string()), None, &mut stdin, true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(input.task, "explain this repo");
+        assert!(input.piped_context.is_none());
+    }
 }
@@ -0,0 +1,99 @@
+//! Saved session management commands
+
+use crate::session::{Session, SessionStore};
+use anyhow::Result;
+use clap::Subcommand;
+use std::path::PathBuf;
+use tracing::info;
+
+/// Session subcommands
+#[derive(Subcommand)]
+pub enum SessionsCommand {
+    /// List saved sessions for the current directory
+    List {
+        /// Show sessions from all directories
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Fork a saved session into a new one
+    Fork {
+        /// Session id (or a unique prefix of it)
+        id: String,
+    },
+}
+
+/// Run a session subcommand
+pub async fn sessions_command(command: SessionsCommand) -> Result<()> {
+    let store = SessionStore::open_default()?;
+
+    match command {
+        SessionsCommand::List { all } => list_sessions(&store, all),
+        SessionsCommand::Fork { id } => fork_session(&store, &id),
+    }
+}
+
+fn list_sessions(store: &SessionStore, all: bool) -> Result<()> {
+    info!("Listing saved sessions");
+
+    let sessions = if all {
+        println!("📚 All saved sessions\n");
+        store.list()?
+    } else {
+        let project_path = current_project_path();
+        println!("📚 Saved sessions in {}\n", project_path.display());
+        store.list_for_project(&project_path)?
+    };
+
+    if sessions.is_empty() {
+        println!("No saved sessions found.");
+        return Ok(());
+    }
+
+    for session in &sessions {
+        print_session(session, all);
+    }
+
+    println!("💡 Resume with `coro --resume <id>` or `coro --continue` for the latest one");
+
+    Ok(())
+}
+
+fn fork_session(store: &SessionStore, id: &str) -> Result<()> {
+    let forked = store.fork(id)?;
+
+    println!(
+        "🍴 Forked session {} into {}",
+        forked.parent_id.as_deref().unwrap_or(id),
+        forked.id
+    );
+    println!("💡 Resume it with `coro --resume {}`", forked.short_id());
+
+    Ok(())
+}
+
+fn print_session(session: &Session, show_project: bool) {
+    let updated = session
+        .updated_at
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M");
+
+    println!(
+        "📝 {}  {}  {} messages  {} tokens",
+        session.short_id(),
+        updated,
+        session.message_count(),
+        session.token_usage.total_tokens
+    );
+    println!("   {}", session.title());
+    if show_project {
+        println!("   {}", session.project_path.display());
+    }
+    println!();
+}
+
+/// Canonical path of the current directory, as recorded in sessions
+pub fn current_project_path() -> PathBuf {
+    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    current_dir.canonicalize().unwrap_or(current_dir)
+}
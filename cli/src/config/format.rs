@@ -0,0 +1,148 @@
+//! Config file formats
+//!
+//! Config files can be written in JSON, TOML or YAML with the same schema.
+//! Every format is parsed into a JSON document before the layers are merged.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Supported config file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+/// Config file extensions, in the order they are looked up
+pub const CONFIG_EXTENSIONS: [&str; 4] = ["json", "toml", "yaml", "yml"];
+
+impl ConfigFormat {
+    /// Detect the format from the file extension, defaulting to JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::Toml,
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Json,
+        }
+    }
+
+    /// File extension of the format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+        }
+    }
+
+    /// Parse a config document
+    pub fn parse(self, content: &str) -> Result<Value> {
+        Ok(match self {
+            Self::Json => serde_json::from_str(content)?,
+            Self::Toml => toml::from_str(content)?,
+            // An empty YAML file is a null document
+            Self::Yaml if content.trim().is_empty() => Value::Object(Default::default()),
+            Self::Yaml => serde_yaml_ng::from_str(content)?,
+        })
+    }
+}
+
+/// Find the config file `<stem>.<ext>`, trying the extensions in order
+///
+/// When files exist in several formats the first one wins and the others are
+/// reported as ignored.
+pub fn find_config_file(stem: &Path) -> Option<PathBuf> {
+    let mut found = CONFIG_EXTENSIONS
+        .iter()
+        .map(|ext| with_extension(stem, ext));
+
+    let path = found.find(|path| path.is_file())?;
+    for ignored in found.filter(|path| path.is_file()) {
+        tracing::warn!(
+            "Ignoring {} because {} takes precedence",
+            ignored.display(),
+            path.display()
+        );
+    }
+    Some(path)
+}
+
+/// Append an extension to a stem, keeping dots already in it (`config.local`)
+pub fn with_extension(stem: &Path, extension: &str) -> PathBuf {
+    let mut path = OsString::from(stem.as_os_str());
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_formats_parse_to_the_same_document() {
+        let expected = json!({
+            "default_profile": "fast",
+            "profiles": {
+                "fast": { "protocol": "openai", "model": "gpt-4o-mini", "params": { "max_tokens": 4096 } }
+            }
+        });
+
+        let toml = r#"
+            # Team default
+            default_profile = "fast"
+
+            [profiles.fast]
+            protocol = "openai"
+            model = "gpt-4o-mini"
+            params = { max_tokens = 4096 }
+        "#;
+        let yaml = "
+# Team default
+default_profile: fast
+profiles:
+  fast:
+    protocol: openai
+    model: gpt-4o-mini
+    params:
+      max_tokens: 4096
+";
+
+        assert_eq!(ConfigFormat::Toml.parse(toml).unwrap(), expected);
+        assert_eq!(ConfigFormat::Yaml.parse(yaml).unwrap(), expected);
+        assert_eq!(
+            ConfigFormat::Json.parse(&expected.to_string()).unwrap(),
+            expected
+        );
+        assert_eq!(ConfigFormat::Yaml.parse("").unwrap(), json!({}));
+    }
+
+    #[test]
+    fn test_find_config_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let stem = temp_dir.path().join("config.local");
+        assert_eq!(find_config_file(&stem), None);
+
+        std::fs::write(temp_dir.path().join("config.local.yaml"), "model: m").unwrap();
+        assert_eq!(
+            find_config_file(&stem),
+            Some(temp_dir.path().join("config.local.yaml"))
+        );
+
+        // JSON takes precedence over the other formats
+        std::fs::write(temp_dir.path().join("config.local.json"), "{}").unwrap();
+        assert_eq!(
+            find_config_file(&stem),
+            Some(temp_dir.path().join("config.local.json"))
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("coro.yml")),
+            ConfigFormat::Yaml
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Layered configuration merging
+//!
+//! Config files are merged field by field: objects are merged recursively and
+//! any other value replaces the one from a lower layer. The origin of every
+//! effective value is recorded so `coro config show --origin` can explain it.
+
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Configuration document merged from several layers
+#[derive(Debug, Clone)]
+pub struct MergedConfig {
+    /// Effective configuration document
+    pub value: Value,
+    /// Origin of every leaf value, keyed by its dotted path
+    pub origins: BTreeMap<String, String>,
+}
+
+impl MergedConfig {
+    /// Create an empty document
+    pub fn new() -> Self {
+        Self {
+            value: Value::Object(Map::new()),
+            origins: BTreeMap::new(),
+        }
+    }
+
+    /// Merge a layer on top of the current document
+    pub fn merge(&mut self, layer: Value, origin: &str) {
+        merge_value(&mut self.value, layer, "", origin, &mut self.origins);
+    }
+
+    /// Set a single value, e.g. from a command-line flag
+    pub fn set(&mut self, path: &[&str], value: Value, origin: &str) {
+        let layer = path.iter().rev().fold(value, |value, key| {
+            Value::Object(Map::from_iter([(key.to_string(), value)]))
+        });
+        self.merge(layer, origin);
+    }
+
+    /// Effective leaf values with their origin, sorted by path
+    pub fn entries(&self) -> Vec<(String, &Value, &str)> {
+        let mut leaves = Vec::new();
+        collect_leaves(&self.value, String::new(), &mut leaves);
+        leaves.sort_by(|a, b| a.0.cmp(&b.0));
+
+        leaves
+            .into_iter()
+            .map(|(path, value)| {
+                let origin = self.origins.get(&path).map_or("", String::as_str);
+                (path, value, origin)
+            })
+            .collect()
+    }
+}
+
+impl Default for MergedConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn merge_value(
+    target: &mut Value,
+    mut source: Value,
+    path: &str,
+    origin: &str,
+    origins: &mut BTreeMap<String, String>,
+) {
+    if let (Some(target), Value::Object(source)) = (target.as_object_mut(), &mut source) {
+        for (key, value) in std::mem::take(source) {
+            let child = join_path(path, &key);
+            match target.get_mut(&key) {
+                Some(existing) => merge_value(existing, value, &child, origin, origins),
+                None => {
+                    record_origins(&value, &child, origin, origins);
+                    target.insert(key, value);
+                }
+            }
+        }
+        return;
+    }
+
+    // Replace the whole subtree, forgetting where its old values came from
+    let prefix = format!("{}.", path);
+    origins.retain(|key, _| !path.is_empty() && key != path && !key.starts_with(&prefix));
+    record_origins(&source, path, origin, origins);
+    *target = source;
+}
+
+fn record_origins(value: &Value, path: &str, origin: &str, origins: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                record_origins(value, &join_path(path, key), origin, origins);
+            }
+        }
+        _ => {
+            origins.insert(path.to_string(), origin.to_string());
+        }
+    }
+}
+
+fn collect_leaves<'a>(value: &'a Value, path: String, leaves: &mut Vec<(String, &'a Value)>) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                collect_leaves(value, join_path(&path, key), leaves);
+            }
+        }
+        _ if path.is_empty() => {}
+        _ => leaves.push((path, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_field_by_field() {
+        let mut merged = MergedConfig::new();
+        merged.merge(
+            json!({
+                "protocol": "openai",
+                "model": "gpt-4o",
+                "params": { "temperature": 0.2, "max_tokens": 4096 }
+            }),
+            "user",
+        );
+        merged.merge(
+            json!({ "model": "gpt-4o-mini", "params": { "max_tokens": 8192 } }),
+            "project",
+        );
+        merged.merge(json!({ "api_key": "secret" }), "local");
+        merged.set(&["model"], json!("o3"), "--model");
+
+        assert_eq!(
+            merged.value,
+            json!({
+                "protocol": "openai",
+                "model": "o3",
+                "api_key": "secret",
+                "params": { "temperature": 0.2, "max_tokens": 8192 }
+            })
+        );
+
+        let origins: Vec<_> = merged
+            .entries()
+            .into_iter()
+            .map(|(path, _, origin)| format!("{}={}", path, origin))
+            .collect();
+        assert_eq!(
+            origins,
+            [
+                "api_key=local",
+                "model=--model",
+                "params.max_tokens=project",
+                "params.temperature=user",
+                "protocol=user"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replacing_subtree_drops_stale_origins() {
+        let mut merged = MergedConfig::new();
+        merged.merge(json!({ "headers": { "x-a": "1", "x-b": "2" } }), "user");
+        merged.merge(json!({ "headers": "none" }), "project");
+
+        assert_eq!(merged.origins.len(), 1);
+        assert_eq!(merged.origins["headers"], "project");
+
+        merged.merge(json!({ "headers": { "x-c": "3" } }), "local");
+        assert_eq!(merged.value, json!({ "headers": { "x-c": "3" } }));
+        assert_eq!(merged.origins.keys().collect::<Vec<_>>(), ["headers.x-c"]);
+    }
+}
@@ -1,16 +1,33 @@
 //! Simple CLI configuration loader for coro-code
 //!
-//! Implements single-source priority loading with flag overrides:
-//! 1. --config file/dir (highest priority)
-//! 2. Current working directory: ./coro.json or ./.coro/config.json
-//! 3. Git repository root: <repo_root>/.coro/config.json
-//! 4. XDG config: $XDG_CONFIG_HOME/coro/config.json or ~/.config/coro/config.json
-//! 5. Environment variables only (no files)
-
-use anyhow::{anyhow, Context, Result};
-use coro_core::{ModelParams, Protocol, ResolvedLlmConfig};
+//! Merges every config file found, field by field, from lowest to highest
+//! priority:
+//! 1. XDG config: $XDG_CONFIG_HOME/coro/config.json or ~/.config/coro/config.json
+//! 2. Project config: <repo_root>/.coro/config.json, then ./.coro/config.json
+//! 3. Working directory: ./coro.json
+//! 4. Local, untracked config: <repo_root>/.coro/config.local.json, then
+//!    ./.coro/config.local.json
+//! 5. --config file/dir
+//! 6. Command-line flags
+//!
+//! Each file may also be written as `.toml`, `.yaml` or `.yml` with the same
+//! schema; see [`super::format`].
+//!
+//! When no file configures a model, it is detected from environment variables.
+//!
+//! A config file describes either a single model at the top level or a set of
+//! named `profiles`; the profile to use is picked with `--profile`, falling
+//! back to `default_profile`.
+
+use super::format::{find_config_file, with_extension, ConfigFormat};
+use super::layers::MergedConfig;
+use super::secrets::{register_credential, resolve_secret};
+use super::settings::Settings;
+use anyhow::{anyhow, bail, Context, Result};
+use coro_core::{ModelParams, PromptMode, Protocol, ResolvedLlmConfig};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 /// Raw configuration file format (simple single-file schema)
@@ -18,7 +35,7 @@ use std::path::{Path, PathBuf};
 pub struct RawConfig {
     /// Protocol to use
     pub protocol: String,
-    /// API key (can be "env:VAR_NAME" for environment variable)
+    /// API key, or a secret reference (`env:`, `file:` or `cmd:`)
     pub api_key: String,
     /// Base URL (optional, uses protocol default if not specified)
     pub base_url: Option<String>,
@@ -27,20 +44,173 @@ pub struct RawConfig {
     /// Model parameters (optional)
     #[serde(default)]
     pub params: ModelParams,
-    /// Additional headers (optional)
+    /// Additional headers (optional), values may be secret references
     #[serde(default)]
     pub headers: HashMap<String, String>,
 }
 
+/// Profile name of a model configured at the top level of a config file
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Keys that mark a top-level single-model configuration
+const SINGLE_MODEL_KEYS: [&str; 3] = ["protocol", "api_key", "model"];
+
+/// Project config file without extension, relative to a project directory
+const PROJECT_CONFIG_STEM: &str = ".coro/config";
+
+/// Untracked project config file for secrets, without extension
+const LOCAL_CONFIG_STEM: &str = ".coro/config.local";
+
+/// Origin label of the model detected from environment variables
+const ENVIRONMENT_ORIGIN: &str = "environment";
+
+/// Parsed configuration file: a set of named model profiles
+///
+/// The single-model format is loaded as one profile named [`DEFAULT_PROFILE`].
+/// It can be combined with a `profiles` table, e.g.
+///
+/// ```json
+/// {
+///   "default_profile": "fast",
+///   "profiles": {
+///     "fast": { "protocol": "openai", "api_key": "env:OPENAI_API_KEY", "model": "gpt-4o-mini" },
+///     "strong": { "protocol": "anthropic", "api_key": "env:ANTHROPIC_API_KEY", "model": "claude-sonnet-4-20250514" }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    /// Model profiles by name
+    pub profiles: BTreeMap<String, RawConfig>,
+    /// Profile used when none is selected explicitly
+    pub default_profile: Option<String>,
+}
+
+impl ConfigFile {
+    /// Parse a config document in either format
+    pub fn from_value(mut value: Value) -> Result<Self> {
+        let object = value
+            .as_object_mut()
+            .ok_or_else(|| anyhow!("expected a JSON object"))?;
+
+        let Some(profiles) = object.remove("profiles") else {
+            if object.contains_key("default_profile") {
+                bail!("default_profile is set but no profiles are defined");
+            }
+            let config = serde_json::from_value(value)?;
+            return Ok(Self {
+                profiles: BTreeMap::from([(DEFAULT_PROFILE.to_string(), config)]),
+                default_profile: None,
+            });
+        };
+
+        let default_profile = object
+            .remove("default_profile")
+            .map(serde_json::from_value)
+            .transpose()
+            .context("invalid default_profile")?;
+        let mut profiles: BTreeMap<String, RawConfig> =
+            serde_json::from_value(profiles).context("invalid profiles")?;
+
+        // A top-level model next to the profiles becomes the default profile
+        if SINGLE_MODEL_KEYS
+            .iter()
+            .any(|key| object.contains_key(*key))
+        {
+            if profiles.contains_key(DEFAULT_PROFILE) {
+                bail!(
+                    "the top-level model conflicts with the profile named '{}'",
+                    DEFAULT_PROFILE
+                );
+            }
+            profiles.insert(DEFAULT_PROFILE.to_string(), serde_json::from_value(value)?);
+        }
+
+        Ok(Self {
+            profiles,
+            default_profile,
+        })
+    }
+
+    /// Whether a config document configures a model in either format
+    pub fn defines_model(value: &Value) -> bool {
+        value.get("profiles").is_some()
+            || SINGLE_MODEL_KEYS.iter().any(|key| value.get(key).is_some())
+    }
+
+    /// Pick a profile by name, falling back to the default one
+    pub fn select(&self, name: Option<&str>) -> Result<&RawConfig> {
+        let name = self.profile_name(name)?;
+        Ok(&self.profiles[name])
+    }
+
+    /// Name of the profile to use
+    ///
+    /// Without a name this uses `default_profile`, then the top-level model,
+    /// then the only profile if there is exactly one.
+    pub fn profile_name<'a>(&'a self, name: Option<&'a str>) -> Result<&'a str> {
+        let name = name.or(self.default_profile.as_deref()).or_else(|| {
+            if self.profiles.contains_key(DEFAULT_PROFILE) {
+                Some(DEFAULT_PROFILE)
+            } else if self.profiles.len() == 1 {
+                self.profiles.keys().next().map(String::as_str)
+            } else {
+                None
+            }
+        });
+
+        let Some(name) = name else {
+            if self.profiles.is_empty() {
+                bail!("No model profiles defined in config");
+            }
+            bail!(
+                "Multiple profiles configured ({}). Set default_profile or select one with --profile",
+                self.profile_names()
+            );
+        };
+
+        if !self.profiles.contains_key(name) {
+            bail!(
+                "Profile '{}' not found. Available profiles: {}",
+                name,
+                self.profile_names()
+            );
+        }
+        Ok(name)
+    }
+
+    fn profile_names(&self) -> String {
+        self.profiles
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Config file written by `coro config init` and `coro config set`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigScope {
+    /// User config in the XDG config directory
+    User,
+    /// Project config, committed to the repository
+    Project,
+    /// Untracked project config, e.g. for secrets
+    Local,
+}
+
 /// CLI configuration loader
 pub struct CliConfigLoader {
     /// Override config file/directory path
     config_override: Option<PathBuf>,
+    /// Profile selected with --profile
+    profile: Option<String>,
     /// Flag overrides
     protocol_override: Option<String>,
     api_key_override: Option<String>,
     base_url_override: Option<String>,
     model_override: Option<String>,
+    mode_override: Option<PromptMode>,
 }
 
 impl CliConfigLoader {
@@ -48,10 +218,12 @@ impl CliConfigLoader {
     pub fn new() -> Self {
         Self {
             config_override: None,
+            profile: None,
             protocol_override: None,
             api_key_override: None,
             base_url_override: None,
             model_override: None,
+            mode_override: None,
         }
     }
 
@@ -61,6 +233,12 @@ impl CliConfigLoader {
         self
     }
 
+    /// Select a named profile from the config file
+    pub fn with_profile(mut self, profile: String) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
     /// Set protocol override
     pub fn with_protocol_override(mut self, protocol: String) -> Self {
         self.protocol_override = Some(protocol);
@@ -85,100 +263,195 @@ impl CliConfigLoader {
         self
     }
 
+    /// Set prompt mode override
+    pub fn with_mode_override(mut self, mode: PromptMode) -> Self {
+        self.mode_override = Some(mode);
+        self
+    }
+
     /// Load and resolve configuration
     pub async fn load(&self) -> Result<ResolvedLlmConfig> {
-        // Step 1: Find and load base configuration
-        let mut config = if let Some(override_path) = &self.config_override {
-            // Use explicit config override
-            self.load_from_path(override_path).await.with_context(|| {
-                format!(
-                    "Failed to load config from override path: {}",
-                    override_path.display()
-                )
-            })?
-        } else {
-            // Search in priority order
-            self.search_and_load().await?
-        };
+        self.resolve_selected(self.load_merged().await?).await
+    }
 
-        // Step 2: Apply flag overrides
-        if let Some(protocol) = &self.protocol_override {
-            config.protocol = protocol.clone();
-        }
-        if let Some(api_key) = &self.api_key_override {
-            config.api_key = api_key.clone();
-        }
-        if let Some(base_url) = &self.base_url_override {
-            config.base_url = Some(base_url.clone());
-        }
-        if let Some(model) = &self.model_override {
-            config.model = model.clone();
+    /// Load and resolve every profile, sorted by name
+    ///
+    /// Profiles that fail to resolve, e.g. because their API key variable is
+    /// unset, are skipped with a warning.
+    pub async fn load_profiles(&self) -> Result<Vec<(String, ResolvedLlmConfig)>> {
+        let mut profiles = Vec::new();
+        for (name, resolved) in self.resolve_profiles().await? {
+            match resolved {
+                Ok(resolved) => profiles.push((name, resolved)),
+                Err(e) => tracing::warn!("Skipping profile '{}': {:#}", name, e),
+            }
         }
+        Ok(profiles)
+    }
 
-        // Step 3: Resolve to final LLM config
+    /// Resolve every profile, keeping the error of those that fail
+    pub async fn resolve_profiles(&self) -> Result<Vec<(String, Result<ResolvedLlmConfig>)>> {
+        self.resolve_all(self.load_merged().await?).await
+    }
+
+    /// Resolve the selected profile of a merged configuration
+    async fn resolve_selected(&self, merged: MergedConfig) -> Result<ResolvedLlmConfig> {
+        let file = self.model_config(merged).await?;
+        let config = file.select(self.profile.as_deref())?.clone();
         self.resolve_config(config).await
     }
 
-    /// Search for config in priority order
-    async fn search_and_load(&self) -> Result<RawConfig> {
-        // 1. Current working directory
-        if let Some(config) = self.try_load_cwd().await? {
-            return Ok(config);
+    /// Resolve every profile of a merged configuration
+    async fn resolve_all(
+        &self,
+        merged: MergedConfig,
+    ) -> Result<Vec<(String, Result<ResolvedLlmConfig>)>> {
+        let file = self.model_config(merged).await?;
+
+        let mut profiles = Vec::new();
+        for (name, config) in file.profiles {
+            let resolved = self.resolve_config(config).await;
+            profiles.push((name, resolved));
         }
+        Ok(profiles)
+    }
 
-        // 2. Git repository root
-        if let Some(config) = self.try_load_git_root().await? {
-            return Ok(config);
+    /// Parse a merged configuration that has to define a model
+    async fn model_config(&self, merged: MergedConfig) -> Result<ConfigFile> {
+        if !ConfigFile::defines_model(&merged.value) {
+            // Neither the files nor the environment define one, tell why
+            self.try_load_env_only().await?;
         }
+        ConfigFile::from_value(merged.value).context("Invalid configuration")
+    }
 
-        // 3. XDG config directory
-        if let Some(config) = self.try_load_xdg().await? {
-            return Ok(config);
-        }
+    /// Config file of a scope, to write settings to
+    ///
+    /// This is the existing file in any format, or a new `<stem>.<format>`.
+    /// A `--config` path takes precedence over the scope.
+    pub fn config_file_for(&self, scope: ConfigScope, format: ConfigFormat) -> Result<PathBuf> {
+        let stem = match &self.config_override {
+            Some(path) if path.is_dir() => path.join("config"),
+            Some(path) => return Ok(path.clone()),
+            None => {
+                let project_dir = match self.find_git_root()? {
+                    Some(root) => root,
+                    None => std::env::current_dir()?,
+                };
+                match scope {
+                    ConfigScope::User => self
+                        .get_xdg_config_dir()
+                        .ok_or_else(|| anyhow!("Cannot locate the user config directory"))?
+                        .join("coro")
+                        .join("config"),
+                    ConfigScope::Project => project_dir.join(PROJECT_CONFIG_STEM),
+                    ConfigScope::Local => project_dir.join(LOCAL_CONFIG_STEM),
+                }
+            }
+        };
 
-        // 4. Environment variables only
-        self.try_load_env_only().await
+        Ok(find_config_file(&stem).unwrap_or_else(|| with_extension(&stem, format.extension())))
     }
 
-    /// Try loading from current working directory
-    async fn try_load_cwd(&self) -> Result<Option<RawConfig>> {
-        let cwd = std::env::current_dir()?;
+    /// Load the `agent` and `tools` sections of the merged configuration
+    pub async fn load_settings(&self) -> Result<Settings> {
+        let merged = self.load_merged().await?;
+        serde_json::from_value(merged.value).context("Invalid agent or tools settings")
+    }
+
+    /// Merge all config layers and the flag overrides
+    pub async fn load_merged(&self) -> Result<MergedConfig> {
+        let paths = self.config_paths()?;
+        self.merge_files(&paths).await
+    }
 
-        // Try ./coro.json first
-        let coro_json = cwd.join("coro.json");
-        if coro_json.exists() {
-            return Ok(Some(self.load_file(&coro_json).await?));
+    /// Merge the given config files, from lowest to highest priority
+    async fn merge_files(&self, paths: &[PathBuf]) -> Result<MergedConfig> {
+        let mut layers = Vec::new();
+        for path in paths {
+            let value = self.load_file(path).await?;
+            layers.push((path.display().to_string(), value));
         }
 
-        // Try ./.coro/config.json
-        let coro_dir_config = cwd.join(".coro").join("config.json");
-        if coro_dir_config.exists() {
-            return Ok(Some(self.load_file(&coro_dir_config).await?));
+        // Fall back to environment variables when no file configures a model.
+        // Without one the settings can still be loaded, only resolving a model
+        // fails.
+        let mut merged = merge_layers(&layers);
+        if !ConfigFile::defines_model(&merged.value) {
+            if let Ok(env_config) = self.try_load_env_only().await {
+                layers.insert(
+                    0,
+                    (
+                        ENVIRONMENT_ORIGIN.to_string(),
+                        serde_json::to_value(env_config)?,
+                    ),
+                );
+                merged = merge_layers(&layers);
+            }
         }
 
-        Ok(None)
-    }
+        if let Some(mode) = self.mode_override {
+            merged.set(&["agent", "mode"], Value::from(mode.as_str()), "--mode");
+        }
+
+        // Model flags override the selected profile only
+        if !ConfigFile::defines_model(&merged.value) {
+            return Ok(merged);
+        }
+        let file = ConfigFile::from_value(merged.value.clone()).context("Invalid configuration")?;
+        let Ok(profile) = file.profile_name(self.profile.as_deref()) else {
+            return Ok(merged);
+        };
+        let mut path = Vec::new();
+        if merged
+            .value
+            .get("profiles")
+            .and_then(|p| p.get(profile))
+            .is_some()
+        {
+            path.extend(["profiles", profile]);
+        }
 
-    /// Try loading from git repository root
-    async fn try_load_git_root(&self) -> Result<Option<RawConfig>> {
-        if let Some(git_root) = self.find_git_root()? {
-            let config_path = git_root.join(".coro").join("config.json");
-            if config_path.exists() {
-                return Ok(Some(self.load_file(&config_path).await?));
+        let overrides = [
+            ("protocol", "--protocol", &self.protocol_override),
+            ("api_key", "--api-key", &self.api_key_override),
+            ("base_url", "--base-url", &self.base_url_override),
+            ("model", "--model", &self.model_override),
+        ];
+        for (key, flag, value) in overrides {
+            if let Some(value) = value {
+                let key_path = [path.as_slice(), &[key]].concat();
+                merged.set(&key_path, Value::String(value.clone()), flag);
             }
         }
-        Ok(None)
+
+        Ok(merged)
     }
 
-    /// Try loading from XDG config directory
-    async fn try_load_xdg(&self) -> Result<Option<RawConfig>> {
-        if let Some(config_dir) = self.get_xdg_config_dir() {
-            let config_path = config_dir.join("coro").join("config.json");
-            if config_path.exists() {
-                return Ok(Some(self.load_file(&config_path).await?));
-            }
+    /// Config files to merge, from lowest to highest priority
+    pub fn config_paths(&self) -> Result<Vec<PathBuf>> {
+        let cwd = std::env::current_dir()?;
+        let git_root = self.find_git_root()?;
+        let xdg_dir = self.get_xdg_config_dir();
+
+        let mut paths: Vec<PathBuf> =
+            config_candidates(xdg_dir.as_deref(), git_root.as_deref(), &cwd)
+                .iter()
+                .filter_map(|stem| find_config_file(stem))
+                .collect();
+
+        if let Some(override_path) = &self.config_override {
+            // Explicit config goes on top of the discovered files
+            let path = self.resolve_override_path(override_path).with_context(|| {
+                format!(
+                    "Failed to load config from override path: {}",
+                    override_path.display()
+                )
+            })?;
+            paths.push(path);
         }
-        Ok(None)
+
+        Ok(paths)
     }
 
     /// Try loading from environment variables only
@@ -287,34 +560,39 @@ impl CliConfigLoader {
         })
     }
 
-    /// Load configuration from a specific path (file or directory)
-    async fn load_from_path(&self, path: &Path) -> Result<RawConfig> {
+    /// Resolve the config file of a --config path (file or directory)
+    fn resolve_override_path(&self, path: &Path) -> Result<PathBuf> {
         if path.is_file() {
-            self.load_file(path).await
+            Ok(path.to_path_buf())
         } else if path.is_dir() {
-            // Try config.json in the directory
-            let config_file = path.join("config.json");
-            if config_file.exists() {
-                self.load_file(&config_file).await
-            } else {
-                Err(anyhow!(
-                    "No config.json found in directory: {}",
+            // Try config.json (or .toml/.yaml) in the directory
+            find_config_file(&path.join("config")).ok_or_else(|| {
+                anyhow!(
+                    "No config.json, config.toml or config.yaml found in directory: {}",
                     path.display()
-                ))
-            }
+                )
+            })
         } else {
             Err(anyhow!("Config path does not exist: {}", path.display()))
         }
     }
 
     /// Load a single config file
-    async fn load_file(&self, path: &Path) -> Result<RawConfig> {
+    async fn load_file(&self, path: &Path) -> Result<Value> {
         let content = tokio::fs::read_to_string(path)
             .await
             .with_context(|| format!("Failed to read config file: {}", path.display()))?;
 
-        serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {}", path.display()))
+        let value = ConfigFormat::from_path(path)
+            .parse(&content)
+            .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+        if !value.is_object() {
+            bail!(
+                "Config file must contain a table of settings: {}",
+                path.display()
+            );
+        }
+        Ok(value)
     }
 
     /// Find git repository root
@@ -349,23 +627,22 @@ impl CliConfigLoader {
 
     /// Resolve raw config to ResolvedLlmConfig
     async fn resolve_config(&self, config: RawConfig) -> Result<ResolvedLlmConfig> {
-        // Parse protocol
-        let protocol = match config.protocol.as_str() {
-            "openai" => Protocol::OpenAICompat,
-            "anthropic" => Protocol::Anthropic,
-            "google_ai" => Protocol::GoogleAI,
-            "azure_openai" => Protocol::AzureOpenAI,
-            custom => Protocol::Custom(custom.to_string()),
-        };
+        let protocol = parse_protocol(&config.protocol);
 
-        // Resolve API key (handle env: prefix)
-        let api_key = if config.api_key.starts_with("env:") {
-            let var_name = &config.api_key[4..];
-            std::env::var(var_name)
-                .with_context(|| format!("Environment variable not found: {}", var_name))?
-        } else {
-            config.api_key
-        };
+        // Resolve API key and header secret references
+        let api_key = resolve_secret(&config.api_key)
+            .await
+            .context("Failed to resolve api_key")?;
+        register_credential(&api_key);
+
+        let mut headers = HashMap::new();
+        for (name, value) in config.headers {
+            let value = resolve_secret(&value)
+                .await
+                .with_context(|| format!("Failed to resolve header {}", name))?;
+            register_credential(&value);
+            headers.insert(name, value);
+        }
 
         // Resolve base URL
         let base_url = config.base_url.unwrap_or_else(|| {
@@ -378,7 +655,7 @@ impl CliConfigLoader {
         // Create resolved config
         let resolved = ResolvedLlmConfig::new(protocol, base_url, api_key, config.model)
             .with_params(config.params)
-            .with_headers(config.headers);
+            .with_headers(headers);
 
         // Validate
         resolved
@@ -389,8 +666,258 @@ impl CliConfigLoader {
     }
 }
 
+/// Parse a protocol name as written in config files
+pub fn parse_protocol(name: &str) -> Protocol {
+    match name {
+        "openai" => Protocol::OpenAICompat,
+        "anthropic" => Protocol::Anthropic,
+        "google_ai" => Protocol::GoogleAI,
+        "azure_openai" => Protocol::AzureOpenAI,
+        custom => Protocol::Custom(custom.to_string()),
+    }
+}
+
+/// Merge config layers given as (origin, document) pairs
+fn merge_layers(layers: &[(String, Value)]) -> MergedConfig {
+    let mut merged = MergedConfig::new();
+    for (origin, value) in layers {
+        merged.merge(value.clone(), origin);
+    }
+    merged
+}
+
+/// Candidate config files without extension, from lowest to highest priority
+fn config_candidates(xdg_dir: Option<&Path>, git_root: Option<&Path>, cwd: &Path) -> Vec<PathBuf> {
+    let mut project_dirs = Vec::new();
+    project_dirs.extend(git_root);
+    if git_root != Some(cwd) {
+        project_dirs.push(cwd);
+    }
+
+    let mut paths = Vec::new();
+    if let Some(xdg_dir) = xdg_dir {
+        paths.push(xdg_dir.join("coro").join("config"));
+    }
+    paths.extend(project_dirs.iter().map(|dir| dir.join(PROJECT_CONFIG_STEM)));
+    paths.push(cwd.join("coro"));
+    paths.extend(project_dirs.iter().map(|dir| dir.join(LOCAL_CONFIG_STEM)));
+    paths
+}
+
 impl Default for CliConfigLoader {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn parse(content: &str) -> Result<ConfigFile> {
+        ConfigFile::from_value(serde_json::from_str(content)?)
+    }
+
+    const PROFILES: &str = r#"{
+        "default_profile": "fast",
+        "profiles": {
+            "fast": { "protocol": "openai", "api_key": "key-fast", "model": "gpt-4o-mini" },
+            "strong": {
+                "protocol": "anthropic",
+                "api_key": "key-strong",
+                "model": "claude-sonnet-4-20250514",
+                "params": { "max_tokens": 8192 }
+            },
+            "local": { "protocol": "openai", "api_key": "env:CORO_TEST_UNSET_KEY", "base_url": "http://localhost:11434/v1", "model": "qwen2.5-coder" }
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_single_model_format() {
+        let file =
+            parse(r#"{ "protocol": "openai", "api_key": "key", "model": "gpt-4o" }"#).unwrap();
+
+        assert_eq!(file.profiles.len(), 1);
+        assert_eq!(file.select(None).unwrap().model, "gpt-4o");
+        assert_eq!(file.select(Some(DEFAULT_PROFILE)).unwrap().model, "gpt-4o");
+        assert!(file.select(Some("fast")).is_err());
+
+        // Errors in the single-model format are still reported
+        let err = parse(r#"{ "protocol": "openai", "api_key": "key" }"#).unwrap_err();
+        assert!(err.to_string().contains("model"));
+    }
+
+    #[test]
+    fn test_select_profile() {
+        let file = parse(PROFILES).unwrap();
+
+        assert_eq!(file.select(None).unwrap().model, "gpt-4o-mini");
+        let strong = file.select(Some("strong")).unwrap();
+        assert_eq!(strong.protocol, "anthropic");
+        assert_eq!(strong.params.max_tokens, Some(8192));
+
+        let err = file.select(Some("missing")).unwrap_err().to_string();
+        assert!(err.contains("Available profiles: fast, local, strong"));
+    }
+
+    #[test]
+    fn test_select_without_default_profile() {
+        let only = parse(
+            r#"{ "profiles": { "fast": { "protocol": "openai", "api_key": "k", "model": "m" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(only.select(None).unwrap().model, "m");
+
+        // The top-level model is the default next to other profiles
+        let mixed = parse(
+            r#"{
+                "protocol": "openai", "api_key": "k", "model": "top",
+                "profiles": { "fast": { "protocol": "openai", "api_key": "k", "model": "m" } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(mixed.select(None).unwrap().model, "top");
+        assert_eq!(mixed.select(Some("fast")).unwrap().model, "m");
+
+        let ambiguous = parse(
+            PROFILES
+                .replace(r#""default_profile": "fast","#, "")
+                .as_str(),
+        )
+        .unwrap();
+        assert!(ambiguous
+            .select(None)
+            .unwrap_err()
+            .to_string()
+            .contains("--profile"));
+
+        let dangling = parse(PROFILES.replace(r#""fast","#, r#""slow","#).as_str()).unwrap();
+        assert!(dangling.select(None).is_err());
+    }
+
+    #[test]
+    fn test_config_candidates() {
+        let xdg = Path::new("/home/me/.config");
+        let root = Path::new("/repo");
+
+        let paths = config_candidates(Some(xdg), Some(root), &root.join("crates/app"));
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/home/me/.config/coro/config"),
+                PathBuf::from("/repo/.coro/config"),
+                PathBuf::from("/repo/crates/app/.coro/config"),
+                PathBuf::from("/repo/crates/app/coro"),
+                PathBuf::from("/repo/.coro/config.local"),
+                PathBuf::from("/repo/crates/app/.coro/config.local"),
+            ]
+        );
+
+        // The git root is not repeated when it is the working directory
+        assert_eq!(config_candidates(None, Some(root), root).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_merge_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let user = temp_dir.path().join("user.json");
+        let project = temp_dir.path().join("project.toml");
+        let local = temp_dir.path().join("local.yaml");
+        std::fs::write(&user, PROFILES).unwrap();
+        std::fs::write(
+            &project,
+            "default_profile = \"strong\"\n\n[profiles.strong.params]\ntemperature = 0.2\n",
+        )
+        .unwrap();
+        std::fs::write(&local, "profiles:\n  strong:\n    api_key: local-key\n").unwrap();
+
+        let loader =
+            CliConfigLoader::new().with_model_override("claude-opus-4-20250514".to_string());
+        let merged = loader
+            .merge_files(&[user.clone(), project, local.clone()])
+            .await
+            .unwrap();
+
+        let strong = ConfigFile::from_value(merged.value.clone())
+            .unwrap()
+            .select(None)
+            .unwrap()
+            .clone();
+        assert_eq!(strong.api_key, "local-key");
+        assert_eq!(strong.model, "claude-opus-4-20250514");
+        assert_eq!(strong.params.temperature, Some(0.2));
+        assert_eq!(strong.params.max_tokens, Some(8192));
+
+        assert_eq!(
+            merged.origins["profiles.strong.api_key"],
+            local.display().to_string()
+        );
+        assert_eq!(merged.origins["profiles.strong.model"], "--model");
+        assert_eq!(
+            merged.origins["profiles.strong.protocol"],
+            user.display().to_string()
+        );
+        assert_eq!(
+            merged.origins["profiles.fast.model"],
+            user.display().to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_profiles() {
+        // Only the given file is merged, never the user's own config files
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("coro.json");
+        std::fs::write(&path, PROFILES).unwrap();
+        let layers = [path];
+
+        let loader = CliConfigLoader::new();
+        let merged = loader.merge_files(&layers).await.unwrap();
+        assert_eq!(
+            loader.resolve_selected(merged).await.unwrap().model,
+            "gpt-4o-mini"
+        );
+
+        let loader = CliConfigLoader::new()
+            .with_profile("strong".to_string())
+            .with_model_override("claude-opus-4-20250514".to_string());
+        let merged = loader.merge_files(&layers).await.unwrap();
+        let config = loader.resolve_selected(merged.clone()).await.unwrap();
+        assert_eq!(config.protocol, Protocol::Anthropic);
+        assert_eq!(config.model, "claude-opus-4-20250514");
+
+        // Unresolvable profiles fail on their own, overrides apply to the selected one
+        let profiles: Vec<_> = loader
+            .resolve_all(merged)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(name, config)| (name, config.map(|config| config.model).ok()))
+            .collect();
+        assert_eq!(
+            profiles,
+            [
+                ("fast".to_string(), Some("gpt-4o-mini".to_string())),
+                ("local".to_string(), None),
+                (
+                    "strong".to_string(),
+                    Some("claude-opus-4-20250514".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_settings_load_without_a_model() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("coro.json");
+        std::fs::write(&path, r#"{ "agent": { "max_steps": 5 } }"#).unwrap();
+
+        // Whether or not the environment has an API key, the settings merge
+        let loader = CliConfigLoader::new().with_mode_override(PromptMode::Review);
+        let merged = loader.merge_files(&[path]).await.unwrap();
+        assert_eq!(merged.value["agent"]["max_steps"], 5);
+        assert_eq!(merged.value["agent"]["mode"], "review");
+    }
+}
@@ -1,5 +1,12 @@
 //! CLI configuration loading and management
 
+pub mod format;
+pub mod layers;
 pub mod loader;
+pub mod schema;
+pub mod secrets;
+pub mod settings;
+pub mod writer;
 
-pub use loader::CliConfigLoader;
+pub use loader::{CliConfigLoader, ConfigScope};
+pub use settings::{Settings, ToolSettings};
@@ -0,0 +1,77 @@
+//! JSON Schema of the config file, published by `coro config schema`
+//!
+//! Every field is optional because a single file may hold only part of the
+//! configuration; the layers are validated after merging.
+
+use super::settings::{AgentSettings, ToolSettings};
+use coro_core::ModelParams;
+use schemars::{schema_for, JsonSchema};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Model settings as they appear at the top level or in a profile
+#[allow(dead_code)] // Only used to derive the schema
+#[derive(JsonSchema)]
+struct ModelSchema {
+    /// Protocol to use: openai, anthropic, google_ai, azure_openai or a custom name
+    protocol: Option<String>,
+    /// API key, or a reference to it: "env:VAR_NAME", "file:<path>" or "cmd:<shell command>"
+    api_key: Option<String>,
+    /// Base URL (uses the protocol default if not specified)
+    base_url: Option<String>,
+    /// Model name
+    model: Option<String>,
+    /// Model parameters
+    params: Option<ModelParams>,
+    /// Additional HTTP headers; values may use the same references as api_key
+    headers: Option<HashMap<String, String>>,
+}
+
+/// coro configuration file
+#[allow(dead_code)] // Only used to derive the schema
+#[derive(JsonSchema)]
+#[schemars(title = "coro configuration")]
+struct ConfigSchema {
+    #[serde(flatten)]
+    model: ModelSchema,
+    /// Profile used when none is selected with --profile
+    default_profile: Option<String>,
+    /// Named model profiles
+    profiles: Option<BTreeMap<String, ModelSchema>>,
+    /// Agent settings
+    agent: Option<AgentSettings>,
+    /// Tool settings
+    tools: Option<ToolSettings>,
+}
+
+/// JSON Schema describing config files in every supported format
+pub fn config_schema() -> Value {
+    serde_json::to_value(schema_for!(ConfigSchema)).expect("schema serializes to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_schema() {
+        let schema = config_schema();
+        let properties = schema["properties"].as_object().unwrap();
+
+        for key in [
+            "model",
+            "api_key",
+            "default_profile",
+            "profiles",
+            "agent",
+            "tools",
+        ] {
+            assert!(properties.contains_key(key), "missing property {}", key);
+        }
+        // Partial files are valid, so nothing is required
+        assert!(schema.get("required").is_none());
+        assert!(schema["definitions"]["ToolSettings"]["properties"]
+            .get("bash")
+            .is_some());
+    }
+}
@@ -0,0 +1,299 @@
+//! Secret references in `api_key` and `headers` values
+//!
+//! Instead of the secret itself a value may reference where to get it:
+//! - `env:VAR_NAME` reads an environment variable
+//! - `file:<path>` reads a file, e.g. one mounted by secrets tooling
+//! - `cmd:<shell command>` runs a command, e.g. a password manager CLI. Each
+//!   command runs once per login session: its output is cached in memory and
+//!   in a file of the user's runtime directory, which is cleared at logout.
+//!
+//! Resolved secrets, and literal `api_key` and header values, are registered
+//! with [`coro_core::config::register_secret`] so they are redacted from logs
+//! and trajectories.
+
+use crate::session::store::{create_private_dir, write_private_file};
+use anyhow::{anyhow, bail, Context, Result};
+use coro_core::config::{register_secret, secrets::REDACTED};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Time a `cmd:` secret command may take
+pub const SECRET_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+const REFERENCE_PREFIXES: [&str; 3] = ["env:", "file:", "cmd:"];
+
+/// Output of the `cmd:` commands run in this session
+static COMMAND_CACHE: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+/// Register a credential value for redaction
+///
+/// Besides the whole value, the credential of an `Authorization` style value
+/// such as `Bearer <token>` is registered on its own, so it is masked however
+/// it is printed.
+pub fn register_credential(value: &str) {
+    register_secret(value);
+    if let Some((_, credential)) = value.trim().split_once(char::is_whitespace) {
+        register_secret(credential);
+    }
+}
+
+/// Whether a value references a secret instead of holding it
+pub fn is_secret_reference(value: &str) -> bool {
+    REFERENCE_PREFIXES
+        .iter()
+        .any(|prefix| value.starts_with(prefix))
+}
+
+/// Resolve a value that may reference a secret
+///
+/// Plain values are returned unchanged. Referenced secrets are registered for
+/// redaction.
+pub async fn resolve_secret(value: &str) -> Result<String> {
+    let secret = if let Some(var_name) = value.strip_prefix("env:") {
+        std::env::var(var_name)
+            .with_context(|| format!("Environment variable not found: {}", var_name))?
+    } else if let Some(path) = value.strip_prefix("file:") {
+        let path = expand_home(path.trim());
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read secret file: {}", path.display()))?;
+        content.trim().to_string()
+    } else if let Some(command) = value.strip_prefix("cmd:") {
+        run_secret_command(command.trim()).await?
+    } else {
+        return Ok(value.to_string());
+    };
+
+    register_secret(&secret);
+    Ok(secret)
+}
+
+/// Run a secret command, reusing its output if it already ran
+async fn run_secret_command(command: &str) -> Result<String> {
+    let cached = COMMAND_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(|cache| cache.get(command).cloned());
+    if let Some(secret) = cached {
+        return Ok(secret);
+    }
+
+    let cache_file = command_cache_file();
+    if let Some(secret) = cache_file
+        .as_deref()
+        .and_then(|path| read_command_cache(path).remove(command))
+    {
+        cache_command_output(command, &secret);
+        return Ok(secret);
+    }
+
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(command).kill_on_drop(true);
+
+    let output = tokio::time::timeout(SECRET_COMMAND_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| {
+            anyhow!(
+                "Secret command timed out after {} seconds: {}",
+                SECRET_COMMAND_TIMEOUT.as_secs(),
+                command
+            )
+        })?
+        .with_context(|| format!("Failed to run secret command: {}", command))?;
+
+    if !output.status.success() {
+        bail!(
+            "Secret command failed ({}): {}\n{}",
+            output.status,
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let secret = String::from_utf8(output.stdout)
+        .context("Secret command printed invalid UTF-8")?
+        .trim()
+        .to_string();
+    if secret.is_empty() {
+        bail!("Secret command printed nothing: {}", command);
+    }
+
+    cache_command_output(command, &secret);
+    if let Some(path) = cache_file {
+        let mut cache = read_command_cache(&path);
+        cache.insert(command.to_string(), secret.clone());
+        if let Err(e) = write_command_cache(&path, &cache) {
+            tracing::debug!("Failed to cache secret command output: {}", e);
+        }
+    }
+    Ok(secret)
+}
+
+fn cache_command_output(command: &str, secret: &str) {
+    COMMAND_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .insert(command.to_string(), secret.to_string());
+}
+
+/// File caching `cmd:` outputs for the login session, only when the platform
+/// has a runtime directory
+fn command_cache_file() -> Option<PathBuf> {
+    Some(
+        dirs::runtime_dir()?
+            .join("coro")
+            .join("secret-commands.json"),
+    )
+}
+
+fn read_command_cache(path: &Path) -> HashMap<String, String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Replace the cache file, only readable by the user, in one rename
+fn write_command_cache(path: &Path, cache: &HashMap<String, String>) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        create_private_dir(dir)?;
+    }
+    let tmp_path = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    // A leftover file would keep its old permissions
+    let _ = std::fs::remove_file(&tmp_path);
+    write_private_file(&tmp_path, serde_json::to_string(cache)?.as_bytes())?;
+    std::fs::rename(&tmp_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp_path);
+    })
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Mask literal secrets in a config document, keeping secret references
+///
+/// Covers `api_key` and `headers` values at the top level and in `profiles`.
+pub fn redact_config(value: &mut Value) {
+    redact_model(value);
+    if let Some(profiles) = value.get_mut("profiles").and_then(Value::as_object_mut) {
+        profiles.values_mut().for_each(redact_model);
+    }
+}
+
+fn redact_model(model: &mut Value) {
+    let Some(model) = model.as_object_mut() else {
+        return;
+    };
+
+    for (key, value) in model.iter_mut() {
+        match (key.as_str(), value) {
+            ("api_key", value) => redact_literal(value),
+            ("headers", Value::Object(headers)) => headers.values_mut().for_each(redact_literal),
+            _ => {}
+        }
+    }
+}
+
+fn redact_literal(value: &mut Value) {
+    if value.as_str().is_some_and(|v| !is_secret_reference(v)) {
+        *value = Value::String(REDACTED.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_resolve_secret_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("key");
+        std::fs::write(&path, "sk-from-file-0123\n").unwrap();
+
+        let from_file = resolve_secret(&format!("file:{}", path.display()))
+            .await
+            .unwrap();
+        assert_eq!(from_file, "sk-from-file-0123");
+        assert_eq!(resolve_secret("plain").await.unwrap(), "plain");
+        assert!(resolve_secret("file:/nonexistent/coro-key").await.is_err());
+
+        if cfg!(unix) {
+            let from_cmd = resolve_secret("cmd:echo sk-from-cmd-0123").await.unwrap();
+            assert_eq!(from_cmd, "sk-from-cmd-0123");
+            assert!(resolve_secret("cmd:exit 3").await.is_err());
+        }
+
+        // Resolved secrets are masked from then on
+        assert_eq!(
+            coro_core::config::redact("key sk-from-file-0123"),
+            "key [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_register_credential() {
+        register_credential("Bearer tok-header-0123456789");
+        assert_eq!(
+            coro_core::config::redact("token tok-header-0123456789"),
+            "token [REDACTED]"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_cache_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("coro").join("secret-commands.json");
+        let cache = HashMap::from([("pass show key".to_string(), "sk-0123".to_string())]);
+        write_command_cache(&path, &cache).unwrap();
+
+        assert_eq!(read_command_cache(&path), cache);
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(path.parent().unwrap()), 0o700);
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_redact_config() {
+        let mut config = json!({
+            "api_key": "sk-literal",
+            "headers": { "x-token": "cmd:pass show token", "x-org": "acme" },
+            "profiles": { "local": { "api_key": "env:LOCAL_KEY", "model": "m" } }
+        });
+        redact_config(&mut config);
+
+        assert_eq!(
+            config,
+            json!({
+                "api_key": "[REDACTED]",
+                "headers": { "x-token": "cmd:pass show token", "x-org": "[REDACTED]" },
+                "profiles": { "local": { "api_key": "env:LOCAL_KEY", "model": "m" } }
+            })
+        );
+    }
+}
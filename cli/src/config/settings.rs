@@ -0,0 +1,233 @@
+//! Agent and tool settings from the `agent` and `tools` config sections
+//!
+//! ```json
+//! {
+//!   "agent": { "max_steps": 100, "output_mode": "normal", "instructions": true },
+//!   "tools": {
+//!     "disabled": ["ckg_tool"],
+//!     "max_output_tokens": 8000,
+//!     "bash": { "timeout_secs": 300 },
+//!     "edit": { "read_policy": "strict" },
+//!     "ckg": { "db_path": ".coro/ckg.db" },
+//!     "mcp": { "servers": { "fs": { "command": ["npx", "@modelcontextprotocol/server-filesystem", "."] } } }
+//!   }
+//! }
+//! ```
+
+use crate::tools::edit::ReadPolicy;
+use coro_core::tools::builtin::McpServerConfig;
+use coro_core::tools::output_budget::default_spill_dir;
+use coro_core::{AgentConfig, OutputMode, PromptMode};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Agent and tool settings of the merged configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Settings {
+    /// Agent settings
+    pub agent: AgentSettings,
+    /// Tool settings
+    pub tools: ToolSettings,
+}
+
+impl Settings {
+    /// Build the agent configuration, starting from the tools of the mode
+    pub fn agent_config(&self, default_tools: Vec<String>) -> AgentConfig {
+        let mut config = AgentConfig {
+            tools: self.tools.tool_names(default_tools),
+            system_prompt: self.agent.system_prompt.clone(),
+            tool_output_dir: Some(default_spill_dir()),
+            ..Default::default()
+        };
+        if let Some(max_steps) = self.agent.max_steps {
+            config.max_steps = max_steps;
+        }
+        if let Some(output_mode) = &self.agent.output_mode {
+            config.output_mode = output_mode.clone();
+        }
+        if let Some(instructions) = self.agent.instructions {
+            config.load_instructions = instructions;
+        }
+        if let Some(max_instructions_len) = self.agent.max_instructions_len {
+            config.max_instructions_len = max_instructions_len;
+        }
+        if let Some(mode) = self.agent.mode {
+            config.mode = mode;
+        }
+        if let Some(max_output_tokens) = self.tools.max_output_tokens {
+            config.max_output_tokens = max_output_tokens;
+        }
+        config
+    }
+}
+
+/// `agent` section of the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct AgentSettings {
+    /// Maximum number of execution steps
+    pub max_steps: Option<usize>,
+    /// Output mode, `normal` or `debug`
+    pub output_mode: Option<OutputMode>,
+    /// System prompt replacing the built-in one
+    pub system_prompt: Option<String>,
+    /// Whether CORO.md and AGENTS.md instruction files are added to the system prompt
+    pub instructions: Option<bool>,
+    /// Bytes of instruction files kept in the system prompt
+    pub max_instructions_len: Option<usize>,
+    /// System prompt preset: `bugfix`, `feature`, `review`, `explain` or `refactor`
+    pub mode: Option<PromptMode>,
+}
+
+/// `tools` section of the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ToolSettings {
+    /// Tools to enable instead of the default set of the mode
+    pub enabled: Option<Vec<String>>,
+    /// Tools to remove from the enabled set
+    pub disabled: Vec<String>,
+    /// Bytes a tool keeps of its own output before truncating it
+    pub max_output_len: Option<usize>,
+    /// Tokens of output kept in any tool result; the full output of larger
+    /// results is saved to a file
+    pub max_output_tokens: Option<usize>,
+    /// Options of the bash tool
+    pub bash: BashSettings,
+    /// Options of the file editing tool
+    pub edit: EditSettings,
+    /// Options of the code knowledge graph tool
+    pub ckg: CkgSettings,
+    /// MCP servers the MCP tool can start by name
+    pub mcp: McpSettings,
+}
+
+impl ToolSettings {
+    /// Names of the enabled tools, given the default set of the mode
+    pub fn tool_names(&self, default_tools: Vec<String>) -> Vec<String> {
+        let mut tools = self.enabled.clone().unwrap_or(default_tools);
+        tools.retain(|tool| !self.disabled.contains(tool));
+        tools
+    }
+}
+
+/// `tools.bash` section of the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct BashSettings {
+    /// Timeout of a single command in seconds
+    pub timeout_secs: Option<u64>,
+}
+
+/// `tools.edit` section of the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct EditSettings {
+    /// Whether files must be viewed, and unchanged since, before they are
+    /// edited: `strict`, `modified` or `off`
+    pub read_policy: Option<ReadPolicy>,
+}
+
+/// `tools.ckg` section of the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CkgSettings {
+    /// Database used when a call does not name one
+    pub db_path: Option<PathBuf>,
+}
+
+/// `tools.mcp` section of the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct McpSettings {
+    /// Servers by name
+    pub servers: BTreeMap<String, McpServerSettings>,
+}
+
+impl McpSettings {
+    /// Server configurations for the MCP tool
+    pub fn server_configs(&self) -> Vec<McpServerConfig> {
+        self.servers
+            .iter()
+            .map(|(name, server)| McpServerConfig {
+                name: name.clone(),
+                command: server.command.clone(),
+                args: server.args.clone(),
+                env: server.env.clone(),
+                timeout_seconds: server.timeout_secs.unwrap_or(DEFAULT_MCP_TIMEOUT_SECS),
+            })
+            .collect()
+    }
+}
+
+/// Timeout of MCP requests when a server does not set one
+const DEFAULT_MCP_TIMEOUT_SECS: u64 = 30;
+
+/// A server in the `tools.mcp.servers` section
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct McpServerSettings {
+    /// Program and leading arguments that start the server
+    pub command: Vec<String>,
+    /// Additional arguments
+    pub args: Vec<String>,
+    /// Environment variables of the server process
+    pub env: HashMap<String, String>,
+    /// Timeout of a request in seconds
+    pub timeout_secs: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_agent_config_from_settings() {
+        let settings: Settings = serde_json::from_value(json!({
+            "protocol": "openai",
+            "agent": { "max_steps": 50, "output_mode": "debug", "instructions": false, "mode": "review" },
+            "tools": {
+                "disabled": ["ckg_tool"],
+                "max_output_tokens": 4000,
+                "bash": { "timeout_secs": 300 },
+                "edit": { "read_policy": "modified" }
+            }
+        }))
+        .unwrap();
+
+        let defaults = vec!["bash".to_string(), "ckg_tool".to_string()];
+        let config = settings.agent_config(defaults.clone());
+        assert_eq!(config.max_steps, 50);
+        assert_eq!(config.output_mode, OutputMode::Debug);
+        assert_eq!(config.tools, ["bash"]);
+        assert!(!config.load_instructions);
+        assert_eq!(config.mode, PromptMode::Review);
+        assert_eq!(config.max_output_tokens, 4000);
+        assert_eq!(settings.tools.bash.timeout_secs, Some(300));
+        assert_eq!(settings.tools.edit.read_policy, Some(ReadPolicy::Modified));
+
+        // Without the sections everything keeps its default
+        let config = Settings::default().agent_config(defaults.clone());
+        assert_eq!(config.max_steps, AgentConfig::default().max_steps);
+        assert_eq!(config.tools, defaults);
+        assert!(config.load_instructions);
+        assert_eq!(
+            config.max_output_tokens,
+            AgentConfig::default().max_output_tokens
+        );
+    }
+
+    #[test]
+    fn test_enabled_tools_replace_defaults() {
+        let tools = ToolSettings {
+            enabled: Some(vec!["bash".to_string(), "glob".to_string()]),
+            disabled: vec!["glob".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(tools.tool_names(vec!["task_done".to_string()]), ["bash"]);
+    }
+}
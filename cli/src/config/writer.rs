@@ -0,0 +1,359 @@
+//! Writing settings back to config files
+//!
+//! TOML files are edited in place with `toml_edit`, so their comments and
+//! formatting survive. JSON has no comments, and YAML files are rewritten as a
+//! whole, so a YAML file with comments is only rewritten when forced. Keys
+//! keep their order in every format: JSON and YAML files are edited as a YAML
+//! mapping, which is ordered.
+
+use super::format::ConfigFormat;
+use super::settings::Settings;
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use serde_yaml_ng::Mapping;
+use std::path::Path;
+use toml_edit::{DocumentMut, InlineTable, Item, Table, TableLike};
+
+/// A change to a config file: set a dotted key, or remove it with `None`
+pub type ConfigUpdate<'a> = (&'a str, Option<Value>);
+
+/// Apply updates to a config file, creating it if needed
+///
+/// Nothing is written when the result is not a valid config layer, or when it
+/// would drop the comments of a YAML file unless `force` is set.
+pub fn update_config_file(path: &Path, updates: &[ConfigUpdate], force: bool) -> Result<()> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("Failed to read config file: {}", path.display()))
+        }
+    };
+
+    let format = ConfigFormat::from_path(path);
+    let updated = match format {
+        ConfigFormat::Toml => update_toml(&content, updates),
+        ConfigFormat::Json | ConfigFormat::Yaml => update_document(format, &content, updates),
+    }
+    .with_context(|| format!("Failed to update config file: {}", path.display()))?;
+
+    // Refuse to write a file the loader would reject
+    let document = format.parse(&updated)?;
+    if !document.is_object() {
+        bail!("Config file must contain a table of settings");
+    }
+    serde_json::from_value::<Settings>(document).context("Invalid agent or tools settings")?;
+
+    if format == ConfigFormat::Yaml && !force && yaml_has_comments(&content) {
+        bail!(
+            "{} has comments that would be lost when it is rewritten; edit it by hand or pass --force",
+            path.display()
+        );
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, updated)
+        .with_context(|| format!("Failed to write config file: {}", path.display()))
+}
+
+/// Parse a value given on the command line
+///
+/// Numbers, booleans, arrays and objects are read as JSON; anything else is a
+/// string.
+pub fn parse_value(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Whether a YAML document has comments
+///
+/// A `#` at the start of a line or after whitespace starts a comment unless it
+/// is inside a value, e.g. a quoted string or a block scalar. Cutting the line
+/// there leaves the document unchanged only for a comment.
+fn yaml_has_comments(content: &str) -> bool {
+    let Ok(document) = ConfigFormat::Yaml.parse(content) else {
+        return false;
+    };
+
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let candidates = line.char_indices().filter(|&(index, c)| {
+            c == '#' && line[..index].chars().last().is_none_or(char::is_whitespace)
+        });
+        for (index, _) in candidates {
+            let start = line_start + index;
+            let end = line_start + line.trim_end_matches(['\r', '\n']).len();
+            let cut = format!("{}{}", &content[..start], &content[end..]);
+            if ConfigFormat::Yaml
+                .parse(&cut)
+                .is_ok_and(|cut| cut == document)
+            {
+                return true;
+            }
+        }
+        line_start += line.len();
+    }
+    false
+}
+
+fn split_key(key: &str) -> Result<(&str, Vec<&str>)> {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        bail!("Invalid config key: {}", key);
+    }
+    let leaf = parts.pop().unwrap_or_default();
+    Ok((leaf, parts))
+}
+
+fn update_document(
+    format: ConfigFormat,
+    content: &str,
+    updates: &[ConfigUpdate],
+) -> Result<String> {
+    let mut document = if content.trim().is_empty() {
+        serde_yaml_ng::Value::Mapping(Mapping::new())
+    } else if format == ConfigFormat::Yaml {
+        serde_yaml_ng::from_str(content)?
+    } else {
+        serde_json::from_str(content)?
+    };
+
+    'updates: for (key, value) in updates {
+        let (leaf, parents) = split_key(key)?;
+        let mut table = document
+            .as_mapping_mut()
+            .ok_or_else(|| anyhow!("Config file must contain a table of settings"))?;
+        for parent in parents {
+            if value.is_none() && !table.contains_key(parent) {
+                continue 'updates;
+            }
+            table = table
+                .entry(parent.into())
+                .or_insert_with(|| serde_yaml_ng::Value::Mapping(Mapping::new()))
+                .as_mapping_mut()
+                .ok_or_else(|| anyhow!("{} is not a table", parent))?;
+        }
+
+        match value {
+            Some(value) => table.insert(leaf.into(), serde_yaml_ng::to_value(value)?),
+            None => table.shift_remove(leaf),
+        };
+    }
+
+    Ok(match format {
+        ConfigFormat::Yaml => serde_yaml_ng::to_string(&document)?,
+        _ => serde_json::to_string_pretty(&document)? + "\n",
+    })
+}
+
+fn update_toml(content: &str, updates: &[ConfigUpdate]) -> Result<String> {
+    let mut document: DocumentMut = content.parse()?;
+
+    'updates: for (key, value) in updates {
+        let (leaf, parents) = split_key(key)?;
+        let mut table: &mut dyn TableLike = document.as_table_mut();
+        let mut inline = false;
+        for parent in parents {
+            if value.is_none() && !table.contains_key(parent) {
+                continue 'updates;
+            }
+            let item = table.entry(parent).or_insert_with_table(inline);
+            inline = item.is_inline_table();
+            table = item
+                .as_table_like_mut()
+                .ok_or_else(|| anyhow!("{} is not a table", parent))?;
+        }
+
+        let Some(value) = value else {
+            table.remove(leaf);
+            continue;
+        };
+        let mut value = toml_value(value)?;
+        match table.get_mut(leaf).and_then(Item::as_value_mut) {
+            // Keep comments attached to the old value
+            Some(existing) => {
+                *value.decor_mut() = existing.decor().clone();
+                *existing = value;
+            }
+            None => {
+                table.insert(leaf, Item::Value(value));
+            }
+        }
+    }
+
+    Ok(document.to_string())
+}
+
+trait EntryExt<'a> {
+    fn or_insert_with_table(self, inline: bool) -> &'a mut Item;
+}
+
+impl<'a> EntryExt<'a> for toml_edit::Entry<'a> {
+    /// Insert an empty table of the same kind as the parent
+    fn or_insert_with_table(self, inline: bool) -> &'a mut Item {
+        self.or_insert_with(|| {
+            if inline {
+                Item::Value(InlineTable::new().into())
+            } else {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            }
+        })
+    }
+}
+
+fn toml_value(value: &Value) -> Result<toml_edit::Value> {
+    Ok(match value {
+        Value::Null => bail!("TOML cannot store null values"),
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64().unwrap_or_default().into(),
+        },
+        Value::String(s) => s.as_str().into(),
+        Value::Array(items) => items
+            .iter()
+            .map(toml_value)
+            .collect::<Result<toml_edit::Array>>()?
+            .into(),
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| Ok((key.as_str(), toml_value(value)?)))
+            .collect::<Result<InlineTable>>()?
+            .into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_update_toml_keeps_comments() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "# Team defaults\nmodel = \"gpt-4o\" # fast enough\n\n[agent]\nmax_steps = 50\n",
+        )
+        .unwrap();
+
+        update_config_file(
+            &path,
+            &[
+                ("model", Some(json!("gpt-4o-mini"))),
+                ("agent.max_steps", None),
+                ("tools.bash.timeout_secs", Some(json!(300))),
+            ],
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Team defaults\nmodel = \"gpt-4o-mini\" # fast enough\n\n[agent]\n\n[tools.bash]\ntimeout_secs = 300\n"
+        );
+    }
+
+    #[test]
+    fn test_update_json_and_yaml() {
+        let dir = TempDir::new().unwrap();
+        for name in ["config.json", "config.yaml"] {
+            let path = dir.path().join(".coro").join(name);
+            update_config_file(
+                &path,
+                &[("profiles.fast.model", Some(parse_value("o3")))],
+                false,
+            )
+            .unwrap();
+            update_config_file(
+                &path,
+                &[("agent.max_steps", Some(parse_value("20")))],
+                false,
+            )
+            .unwrap();
+
+            let content = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(
+                ConfigFormat::from_path(&path).parse(&content).unwrap(),
+                json!({ "profiles": { "fast": { "model": "o3" } }, "agent": { "max_steps": 20 } })
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_update_is_not_written() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, "{ \"model\": \"gpt-4o\" }").unwrap();
+
+        assert!(
+            update_config_file(&path, &[("agent.max_steps", Some(json!("many")))], false).is_err()
+        );
+        assert!(update_config_file(&path, &[("model.name", Some(json!("x")))], false).is_err());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{ \"model\": \"gpt-4o\" }"
+        );
+    }
+
+    #[test]
+    fn test_update_keeps_key_order() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            "{ \"model\": \"gpt-4o\", \"api_key\": \"env:KEY\", \"base_url\": \"https://a\", \"agent\": {} }",
+        )
+        .unwrap();
+
+        update_config_file(
+            &path,
+            &[("api_key", None), ("agent.max_steps", Some(json!(20)))],
+            false,
+        )
+        .unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let keys: Vec<_> = serde_yaml_ng::from_str::<Mapping>(&content)
+            .unwrap()
+            .into_keys()
+            .filter_map(|key| key.as_str().map(str::to_string))
+            .collect();
+        assert_eq!(keys, ["model", "base_url", "agent"]);
+        assert!(content.contains("\"max_steps\": 20"));
+    }
+
+    #[test]
+    fn test_commented_yaml_is_not_rewritten() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.yaml");
+        let content = "# Team defaults\nmodel: gpt-4o\n";
+        std::fs::write(&path, content).unwrap();
+
+        let err = update_config_file(&path, &[("model", Some(json!("o3")))], false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+
+        update_config_file(&path, &[("model", Some(json!("o3")))], true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "model: o3\n");
+    }
+
+    #[test]
+    fn test_yaml_has_comments() {
+        assert!(yaml_has_comments("# Team defaults\nmodel: gpt-4o\n"));
+        assert!(yaml_has_comments("model: gpt-4o # fast enough\n"));
+        assert!(yaml_has_comments("prompt: \"a # b\" # note\n"));
+        assert!(!yaml_has_comments(
+            "base_url: https://example.com/v1#section\n"
+        ));
+        assert!(!yaml_has_comments(
+            "model: gpt-4o\nprompt: \"use # for headings\"\n"
+        ));
+        assert!(!yaml_has_comments("prompt: |\n  # Heading\n  Body\n"));
+    }
+}
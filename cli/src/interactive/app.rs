@@ -1,5 +1,6 @@
 //! Interactive application using iocraft
 
+use crate::config::Settings;
 use crate::interactive::animation::UiAnimationConfig;
 use crate::interactive::components::input_section::InputSectionContext;
 use crate::interactive::components::logo::output_logo_to_terminal;
@@ -8,7 +9,11 @@ use crate::interactive::message_handler::{app_message_to_ui_message, AppMessage}
 use crate::interactive::pages::main_page::MainPage;
 use crate::interactive::pages::router_test::RouterTestPage;
 use crate::interactive::router::{UIRouter, UIRouterBuilder};
+use crate::interactive::slash_commands::{
+    parse_slash_command, CommandContext, CommandOutcome, ModelOption, SlashCommandRegistry,
+};
 use crate::interactive::terminal_output::{output_content_block, overwrite_previous_lines};
+use crate::session::SessionRecorder;
 use anyhow::Result;
 use coro_core::ResolvedLlmConfig;
 use iocraft::prelude::*;
@@ -267,16 +272,16 @@ async fn process_input_with_file_references(
 /// Enhanced task submission with file reference processing
 pub fn submit_task_with_file_processing(
     input: String,
-    llm_config: ResolvedLlmConfig,
     project_path: PathBuf,
     ui_sender: broadcast::Sender<AppMessage>,
     agent: Arc<Mutex<Option<coro_core::agent::AgentCore>>>,
+    session: Arc<Mutex<SessionRecorder>>,
+    settings: Arc<Settings>,
 ) {
     use crate::interactive::message_handler::get_random_status_word;
 
     // Process file references asynchronously and send combined message
     let ui_sender_clone = ui_sender.clone();
-    let llm_config_clone = llm_config.clone();
     let project_path_clone = project_path.clone();
     let agent_clone = agent.clone();
 
@@ -302,10 +307,11 @@ pub fn submit_task_with_file_processing(
                 // Use the enhanced spawn_ui_agent_task_with_context with enhanced input
                 crate::interactive::components::input_section::spawn_ui_agent_task_with_context(
                     enhanced_input,
-                    llm_config_clone,
                     project_path_clone,
                     ui_sender_clone,
                     agent_clone.clone(),
+                    session,
+                    settings,
                 );
             }
             Err(e) => {
@@ -322,44 +328,119 @@ pub fn submit_task_with_file_processing(
                 // Fall back to original input
                 crate::interactive::components::input_section::spawn_ui_agent_task_with_context(
                     input_clone,
-                    llm_config_clone,
                     project_path_clone,
                     ui_sender_clone,
                     agent_clone,
+                    session,
+                    settings,
                 );
             }
         }
     });
 }
 
+/// Run a slash command entered in the input area
+pub fn submit_slash_command(
+    input: String,
+    mut context: CommandContext,
+    registry: Arc<SlashCommandRegistry>,
+) {
+    let Some((name, args)) = parse_slash_command(&input) else {
+        return;
+    };
+
+    let _ = context.ui_sender.send(AppMessage::UserMessage(input));
+
+    let Some(command) = registry.get(&name) else {
+        context.print(format!(
+            "Unknown command: /{}. Type /help to see available commands.",
+            name
+        ));
+        return;
+    };
+
+    tokio::spawn(async move {
+        match command.execute(&args, &mut context).await {
+            Ok(CommandOutcome::Handled) => {}
+            Ok(CommandOutcome::Submit(prompt)) => submit_task_with_file_processing(
+                prompt,
+                context.project_path,
+                context.ui_sender,
+                context.agent,
+                context.session,
+                context.settings,
+            ),
+            Ok(CommandOutcome::Quit) => {
+                let _ = context.ui_sender.send(AppMessage::Quit);
+            }
+            Err(e) => context.print(format!("/{} failed: {}", command.name(), e)),
+        }
+    });
+}
+
 /// Context for interactive mode - immutable application configuration
 #[derive(Clone)]
 struct AppContext {
-    llm_config: ResolvedLlmConfig,
+    // Model the session starts with
+    model: String,
+    // Config profiles that can be picked with /model
+    models: Vec<ModelOption>,
     project_path: PathBuf,
     ui_sender: broadcast::Sender<AppMessage>,
     ui_anim: UiAnimationConfig,
     debug_model: bool,
     // Persistent agent instance for conversation continuity
     agent: Arc<Mutex<Option<coro_core::agent::AgentCore>>>,
+    // Session the conversation is persisted to
+    session: Arc<Mutex<SessionRecorder>>,
+    // Built-in and project slash commands
+    commands: Arc<SlashCommandRegistry>,
+    // Agent and tool settings from the config file
+    settings: Arc<Settings>,
+    // Notice shown below the header when a saved session was resumed
+    resumed_notice: Option<String>,
 }
 
 impl AppContext {
     fn new(
         llm_config: ResolvedLlmConfig,
+        profiles: Vec<(String, ResolvedLlmConfig)>,
+        settings: Settings,
         project_path: PathBuf,
         ui_sender: broadcast::Sender<AppMessage>,
         debug_model: bool,
+        session: SessionRecorder,
     ) -> Self {
         let ui_anim = UiAnimationConfig::from_env();
 
+        let resumed = session.session();
+        let resumed_notice = (!resumed.conversation_history.is_empty()).then(|| {
+            format!(
+                "Resumed session {} ({} messages): {}",
+                resumed.short_id(),
+                resumed.message_count(),
+                resumed.title()
+            )
+        });
+
+        let commands = Arc::new(SlashCommandRegistry::for_project(&project_path));
+        let models = profiles
+            .into_iter()
+            .map(|(name, llm_config)| ModelOption { name, llm_config })
+            .collect();
+
         Self {
-            llm_config,
+            model: llm_config.model,
+            models,
             project_path,
             ui_sender,
             ui_anim,
             debug_model,
             agent: Arc::new(Mutex::new(None)),
+            session: Arc::new(Mutex::new(session)),
+            commands,
+            settings: Arc::new(settings),
+            resumed_notice,
         }
     }
 }
@@ -367,12 +448,23 @@ impl AppContext {
 /// Interactive mode using iocraft
 pub async fn run_rich_interactive(
     llm_config: ResolvedLlmConfig,
+    profiles: Vec<(String, ResolvedLlmConfig)>,
+    settings: Settings,
     project_path: PathBuf,
     debug_model: bool,
+    session: SessionRecorder,
 ) -> Result<()> {
     // Create UI broadcast channel and app context
     let (ui_sender, _) = broadcast::channel::<AppMessage>(256);
-    let app_context = AppContext::new(llm_config, project_path, ui_sender, debug_model);
+    let app_context = AppContext::new(
+        llm_config,
+        profiles,
+        settings,
+        project_path,
+        ui_sender,
+        debug_model,
+        session,
+    );
 
     // Run the iocraft-based UI with context provider in render loop mode
     tokio::task::spawn_blocking(move || {
@@ -435,9 +527,28 @@ fn CoroApp(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
         }
     });
 
+    // Exit the render loop when a command asks to quit
+    let should_exit = hooks.use_state(|| false);
+    let ui_sender_quit = ui_sender.clone();
+    let mut should_exit_clone = should_exit;
+    hooks.use_future(async move {
+        let mut rx = ui_sender_quit.subscribe();
+        while let Ok(msg) = rx.recv().await {
+            if matches!(msg, AppMessage::Quit) {
+                should_exit_clone.set(true);
+            }
+        }
+    });
+
+    let mut system = hooks.use_context_mut::<SystemContext>();
+    if should_exit.get() {
+        system.exit();
+    }
+
     // Output header to stdout when component mounts
     let stdout_clone = stdout.clone();
     let show_tips_for_output = show_tips;
+    let resumed_notice = app_context.resumed_notice.clone();
     let mut header_rendered_clone = header_rendered;
     hooks.use_future(async move {
         if !*header_rendered_clone.read() {
@@ -453,6 +564,11 @@ fn CoroApp(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                 stdout_clone.println(""); // Empty line for spacing
             }
 
+            if let Some(notice) = &resumed_notice {
+                stdout_clone.println(notice);
+                stdout_clone.println("");
+            }
+
             header_rendered_clone.set(true);
         }
     });
@@ -546,10 +662,14 @@ fn CoroApp(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
     };
 
     let input_context = InputSectionContext {
-        llm_config: app_context.llm_config.clone(),
+        model: app_context.model.clone(),
         project_path: app_context.project_path.clone(),
         ui_sender: app_context.ui_sender.clone(),
         agent: app_context.agent.clone(),
+        session: app_context.session.clone(),
+        commands: app_context.commands.clone(),
+        models: app_context.models.clone(),
+        settings: app_context.settings.clone(),
     };
 
     // Create router configuration with main page using new API
@@ -3,6 +3,7 @@
 //! This module provides the input section component that handles
 //! user input and displays the status bar.
 
+use crate::config::Settings;
 use crate::interactive::file_search::{
     extract_existing_file_references, extract_search_query, should_show_file_search,
 };
@@ -12,6 +13,11 @@ use crate::interactive::file_search::{
 use crate::interactive::input_history::InputHistory;
 use crate::interactive::message_handler::AppMessage;
 use crate::interactive::router::use_router_handle;
+use crate::interactive::slash_commands::{
+    command_completion_prefix, filter_commands, parse_slash_command, CommandContext, ModelOption,
+    SlashCommandInfo, SlashCommandRegistry,
+};
+use crate::session::{Session, SessionRecorder, SessionStore};
 use coro_core::ResolvedLlmConfig;
 use iocraft::prelude::*;
 use std::cmp::min;
@@ -108,17 +114,31 @@ pub struct InputSectionProps {
 
 impl Default for InputSectionProps {
     fn default() -> Self {
+        let llm_config = ResolvedLlmConfig::new(
+            coro_core::Protocol::OpenAICompat,
+            "https://api.openai.com".to_string(),
+            "test-key".to_string(),
+            "gpt-4o".to_string(),
+        );
+        let session = Session::new(PathBuf::new(), &llm_config);
+
         Self {
             context: InputSectionContext {
-                llm_config: ResolvedLlmConfig::new(
-                    coro_core::Protocol::OpenAICompat,
-                    "https://api.openai.com".to_string(),
-                    "test-key".to_string(),
-                    "gpt-4o".to_string(),
-                ),
+                model: llm_config.model.clone(),
                 project_path: PathBuf::new(),
                 ui_sender: tokio::sync::broadcast::channel(1).0,
                 agent: Arc::new(Mutex::new(None)),
+                session: Arc::new(Mutex::new(SessionRecorder::new(
+                    SessionStore::new(PathBuf::new()),
+                    session,
+                    llm_config.clone(),
+                ))),
+                commands: Arc::new(SlashCommandRegistry::with_builtins()),
+                models: vec![ModelOption {
+                    name: "default".to_string(),
+                    llm_config,
+                }],
+                settings: Arc::new(Settings::default()),
             },
         }
     }
@@ -127,10 +147,28 @@ impl Default for InputSectionProps {
 /// Context for the input section component
 #[derive(Clone)]
 pub struct InputSectionContext {
-    pub llm_config: ResolvedLlmConfig,
+    /// Model active when the UI started; later switches arrive as `ModelChanged`
+    pub model: String,
     pub project_path: PathBuf,
     pub ui_sender: broadcast::Sender<AppMessage>,
     pub agent: Arc<Mutex<Option<coro_core::agent::AgentCore>>>,
+    pub session: Arc<Mutex<SessionRecorder>>,
+    pub commands: Arc<SlashCommandRegistry>,
+    /// Models that can be picked with `/model`
+    pub models: Vec<ModelOption>,
+    /// Agent and tool settings from the config file
+    pub settings: Arc<Settings>,
+}
+
+/// Slash commands matching the command name being typed at the cursor
+fn matching_commands(
+    value: &str,
+    cursor: usize,
+    commands: &[SlashCommandInfo],
+) -> Vec<SlashCommandInfo> {
+    command_completion_prefix(value, cursor)
+        .map(|prefix| filter_commands(commands, prefix))
+        .unwrap_or_default()
 }
 
 /// Enhanced text input component that wraps iocraft's TextInput with submit handling
@@ -146,6 +184,10 @@ pub struct EnhancedTextInputProps {
     pub color: Option<Color>,
     pub cursor_color: Option<Color>,
     pub project_path: PathBuf,
+    /// Slash commands offered for autocomplete
+    pub commands: Vec<SlashCommandInfo>,
+    /// Called when the slash command list is shown or hidden
+    pub on_command_list_toggle: Handler<'static, bool>,
 }
 
 impl Default for EnhancedTextInputProps {
@@ -161,6 +203,8 @@ impl Default for EnhancedTextInputProps {
             color: None,
             cursor_color: None,
             project_path: PathBuf::new(),
+            commands: Vec::new(),
+            on_command_list_toggle: Handler::default(),
         }
     }
 }
@@ -174,6 +218,7 @@ pub fn EnhancedTextInput(
     let has_focus = props.has_focus;
     let width = props.width;
     let project_path = props.project_path.clone();
+    let commands = props.commands.clone();
 
     // Local state for cursor position
     let cursor_pos = hooks.use_state(|| props.value.len());
@@ -183,6 +228,10 @@ pub fn EnhancedTextInput(
     let search_results = hooks.use_state(Vec::<FileSearchResult>::new);
     let selected_file_index = hooks.use_state(|| 0usize);
     let current_query = hooks.use_state(String::new);
+    // State for slash command popup
+    let show_command_list = hooks.use_state(|| false);
+    let command_matches = hooks.use_state(Vec::<SlashCommandInfo>::new);
+    let selected_command_index = hooks.use_state(|| 0usize);
     // Track last text input time to disambiguate paste vs. manual Enter
     let last_text_time = hooks.use_state(|| Instant::now() - Duration::from_secs(10));
 
@@ -199,12 +248,16 @@ pub fn EnhancedTextInput(
         let mut on_change = props.on_change.take();
         let mut on_submit = props.on_submit.take();
         let mut on_cursor_position_change = props.on_cursor_position_change.take();
+        let mut on_command_list_toggle = props.on_command_list_toggle.take();
         let mut value = props.value.clone();
         let mut cursor_pos = cursor_pos;
         let mut show_file_list = show_file_list;
         let mut search_results = search_results;
         let mut selected_file_index = selected_file_index;
         let mut current_query = current_query;
+        let mut show_command_list = show_command_list;
+        let mut command_matches = command_matches;
+        let mut selected_command_index = selected_command_index;
 
         let _project_path = project_path.clone();
         let mut cached_existing_refs = cached_existing_refs;
@@ -226,6 +279,63 @@ pub fn EnhancedTextInput(
                     let mut pos = cursor_pos.get();
                     let mut changed = false;
 
+                    // Handle slash command list navigation when it's shown
+                    if *show_command_list.read() {
+                        match code {
+                            KeyCode::Up => {
+                                let current = selected_command_index.get();
+                                selected_command_index.set(current.saturating_sub(1));
+                                return;
+                            }
+                            KeyCode::Char('p') if modifiers.contains(KeyModifiers::CONTROL) => {
+                                let current = selected_command_index.get();
+                                selected_command_index.set(current.saturating_sub(1));
+                                return;
+                            }
+                            KeyCode::Down => {
+                                let current = selected_command_index.get();
+                                let max_index = command_matches.read().len().saturating_sub(1);
+                                selected_command_index.set(min(current + 1, max_index));
+                                return;
+                            }
+                            KeyCode::Char('n') if modifiers.contains(KeyModifiers::CONTROL) => {
+                                let current = selected_command_index.get();
+                                let max_index = command_matches.read().len().saturating_sub(1);
+                                selected_command_index.set(min(current + 1, max_index));
+                                return;
+                            }
+                            KeyCode::Enter | KeyCode::Tab => {
+                                // Complete the selected command; Enter also runs it
+                                let selected = command_matches
+                                    .read()
+                                    .get(selected_command_index.get())
+                                    .cloned();
+                                if let Some(info) = selected {
+                                    value = format!("/{} ", info.name);
+                                    pos = value.len();
+                                    cursor_pos.set(pos);
+                                    on_change(value.clone());
+                                    on_cursor_position_change(calculate_cursor_position(
+                                        &value, pos,
+                                    ));
+                                }
+                                show_command_list.set(false);
+                                on_command_list_toggle(false);
+
+                                if matches!(code, KeyCode::Enter) {
+                                    on_submit(value.clone());
+                                }
+                                return;
+                            }
+                            KeyCode::Esc => {
+                                show_command_list.set(false);
+                                on_command_list_toggle(false);
+                                return;
+                            }
+                            _ => {}
+                        }
+                    }
+
                     // Handle file list navigation when it's shown
                     if *show_file_list.read() {
                         match code {
@@ -382,6 +492,18 @@ pub fn EnhancedTextInput(
                                 // Should not show list, hide it
                                 show_file_list.set(false);
                             }
+
+                            // Show matching slash commands while the name is typed
+                            let matches = matching_commands(&value, pos, &commands);
+                            if matches != *command_matches.read() {
+                                selected_command_index.set(0);
+                            }
+                            let visible = !matches.is_empty();
+                            if visible != show_command_list.get() {
+                                show_command_list.set(visible);
+                                on_command_list_toggle(visible);
+                            }
+                            command_matches.set(matches);
                         }
                         KeyCode::Backspace => {
                             if pos > 0 {
@@ -458,6 +580,18 @@ pub fn EnhancedTextInput(
                                         // Should not show list, hide it
                                         show_file_list.set(false);
                                     }
+
+                                    // Update matching slash commands
+                                    let matches = matching_commands(&value, pos, &commands);
+                                    if matches != *command_matches.read() {
+                                        selected_command_index.set(0);
+                                    }
+                                    let visible = !matches.is_empty();
+                                    if visible != show_command_list.get() {
+                                        show_command_list.set(visible);
+                                        on_command_list_toggle(visible);
+                                    }
+                                    command_matches.set(matches);
                                 }
                             }
                         }
@@ -811,6 +945,62 @@ pub fn EnhancedTextInput(
                 }
             }
 
+            // Slash command popup
+            #(if *show_command_list.read() {
+                let matches = command_matches.read();
+                let selected_index = selected_command_index.get();
+                let max_display_commands = 10;
+                let labels: Vec<String> = matches
+                    .iter()
+                    .map(|info| match &info.usage {
+                        Some(usage) => format!("/{} {}", info.name, usage),
+                        None => format!("/{}", info.name),
+                    })
+                    .collect();
+                let label_width = labels.iter().map(|label| label.len()).max().unwrap_or(0);
+                // Keep the selected command visible when the list is longer than the popup
+                let offset = (selected_index + 1).saturating_sub(max_display_commands);
+
+                Some(element! {
+                    View(
+                        key: "command-list",
+                        width: width,
+                        height: min(matches.len(), max_display_commands) as u16,
+                        position: Position::Relative,
+                    ) {
+                        View(
+                            flex_direction: FlexDirection::Column,
+                            width: 100pct,
+                            height: 100pct,
+                            padding_left: 2,
+                            padding_right: 2,
+                        ) {
+                            #(matches.iter().zip(&labels).enumerate().skip(offset).take(max_display_commands).map(|(idx, (info, label))| {
+                                let is_selected = idx == selected_index;
+                                element! {
+                                    View(
+                                        key: format!("command-{}", idx),
+                                        height: 1,
+                                        width: 100pct,
+                                    ) {
+                                        Text(
+                                            content: format!("{:width$}  {}", label, info.description, width = label_width),
+                                            color: if is_selected {
+                                                Color::Rgb { r: 100, g: 149, b: 237 }
+                                            } else {
+                                                Color::DarkGrey
+                                            },
+                                        )
+                                    }
+                                }
+                            }))
+                        }
+                    }
+                })
+            } else {
+                None
+            })
+
             // File list popup
             #(if *show_file_list.read() {
                 let results = search_results.read();
@@ -918,10 +1108,11 @@ pub fn spawn_ui_agent_task(
 /// Spawn agent task execution with persistent agent for conversation continuity
 pub fn spawn_ui_agent_task_with_context(
     input: String,
-    llm_config: ResolvedLlmConfig,
     project_path: PathBuf,
     ui_sender: broadcast::Sender<AppMessage>,
     agent: Arc<Mutex<Option<coro_core::agent::AgentCore>>>,
+    session: Arc<Mutex<SessionRecorder>>,
+    settings: Arc<Settings>,
 ) {
     use crate::interactive::message_handler::get_random_status_word;
     use crate::interactive::task_executor::execute_agent_task_with_context;
@@ -953,10 +1144,11 @@ pub fn spawn_ui_agent_task_with_context(
     tokio::spawn(async move {
         match execute_agent_task_with_context(
             input,
-            llm_config,
             project_path,
             ui_sender.clone(),
             agent,
+            session,
+            settings,
         )
         .await
         {
@@ -997,6 +1189,10 @@ pub fn InputSection(mut hooks: Hooks, props: &InputSectionProps) -> impl Into<An
     let is_task_running = hooks.use_state(|| false);
     let current_user_input = hooks.use_state(String::new);
     let cursor_position = hooks.use_state(|| (1usize, 1usize)); // (line, column)
+    let active_model = hooks.use_state(|| context.model.clone());
+
+    // Whether the slash command popup owns the Up/Down keys
+    let command_list_open = hooks.use_state(|| false);
 
     // Input history state
     let input_history = hooks.use_state(InputHistory::new);
@@ -1059,6 +1255,7 @@ pub fn InputSection(mut hooks: Hooks, props: &InputSectionProps) -> impl Into<An
     let ui_sender_status = context.ui_sender.clone();
     let mut is_task_running_clone = is_task_running;
     let mut current_user_input_clone = current_user_input;
+    let mut active_model_clone = active_model;
     hooks.use_future(async move {
         let mut rx = ui_sender_status.subscribe();
         while let Ok(event) = rx.recv().await {
@@ -1074,12 +1271,14 @@ pub fn InputSection(mut hooks: Hooks, props: &InputSectionProps) -> impl Into<An
                 AppMessage::UserMessage(input) => {
                     current_user_input_clone.set(input);
                 }
+                AppMessage::ModelChanged { model } => {
+                    active_model_clone.set(model);
+                }
                 _ => {}
             }
         }
     });
 
-    let llm_config = context.llm_config.clone();
     let project_path = context.project_path.clone();
     let ui_sender = context.ui_sender.clone();
 
@@ -1106,7 +1305,7 @@ pub fn InputSection(mut hooks: Hooks, props: &InputSectionProps) -> impl Into<An
                         }
                         KeyCode::Up => {
                             // Navigate to previous history entry
-                            if !*is_task_running.read() {
+                            if !*is_task_running.read() && !command_list_open.get() {
                                 let current_input = input_value.read().clone();
                                 if let Some(history_text) =
                                     input_history.write().navigate_previous(&current_input)
@@ -1118,7 +1317,7 @@ pub fn InputSection(mut hooks: Hooks, props: &InputSectionProps) -> impl Into<An
                         }
                         KeyCode::Down => {
                             // Navigate to next history entry
-                            if !*is_task_running.read() {
+                            if !*is_task_running.read() && !command_list_open.get() {
                                 if let Some(history_text) = input_history.write().navigate_next() {
                                     input_value.set(history_text.clone());
                                     cursor_position.set((1, history_text.len() + 1));
@@ -1151,6 +1350,13 @@ pub fn InputSection(mut hooks: Hooks, props: &InputSectionProps) -> impl Into<An
                 color: Some(Color::White),
                 cursor_color: Some(Color::Rgb { r: 100, g: 149, b: 237 }),
                 project_path: project_path.clone(),
+                commands: context.commands.list(),
+                on_command_list_toggle: {
+                    let mut command_list_open = command_list_open;
+                    move |open| {
+                        command_list_open.set(open);
+                    }
+                },
                 on_change: {
                     let mut input_value = input_value;
                     move |new_value| {
@@ -1167,25 +1373,19 @@ pub fn InputSection(mut hooks: Hooks, props: &InputSectionProps) -> impl Into<An
                     let mut input_value = input_value;
                     let mut cursor_position = cursor_position;
                     let mut input_history = input_history;
-                    let mut router_handle = router_handle.clone();
+                    let router_handle = router_handle.clone();
                     let ui_sender = ui_sender.clone();
-                    let llm_config = llm_config.clone();
                     let project_path = project_path.clone();
                     let agent = context.agent.clone();
+                    let session = context.session.clone();
+                    let commands = context.commands.clone();
+                    let models = context.models.clone();
+                    let settings = context.settings.clone();
                     move |input: String| {
                         if input.trim().is_empty() {
                             return;
                         }
 
-                        // Check if input is "/test" and navigate to test page
-                        if input.trim().to_lowercase() == "/test" {
-                            // Navigate directly - no async needed!
-                            let _ = router_handle.navigate("router_test");
-                            input_value.set(String::new());
-                            cursor_position.set((1, 1));
-                            return;
-                        }
-
                         // Add to history before clearing input (fast, no I/O)
                         let input_for_history = input.clone();
                         let mut history_clone = input_history.read().clone();
@@ -1198,13 +1398,34 @@ pub fn InputSection(mut hooks: Hooks, props: &InputSectionProps) -> impl Into<An
                         input_value.set(String::new());
                         cursor_position.set((1, 1));
 
+                        // Slash commands are handled locally instead of going to the agent
+                        if parse_slash_command(&input).is_some() {
+                            let command_context = CommandContext {
+                                agent: agent.clone(),
+                                session: session.clone(),
+                                ui_sender: ui_sender.clone(),
+                                router: Some(Box::new(router_handle.clone())),
+                                project_path: project_path.clone(),
+                                commands: commands.list(),
+                                models: models.clone(),
+                                settings: settings.clone(),
+                            };
+                            crate::interactive::app::submit_slash_command(
+                                input,
+                                command_context,
+                                commands.clone(),
+                            );
+                            return;
+                        }
+
                         // Use enhanced task submission with file reference processing
                         crate::interactive::app::submit_task_with_file_processing(
                             input,
-                            llm_config.clone(),
                             project_path.clone(),
                             ui_sender.clone(),
                             agent.clone(),
+                            session.clone(),
+                            settings.clone(),
                         );
                     }
                 },
@@ -1216,8 +1437,8 @@ pub fn InputSection(mut hooks: Hooks, props: &InputSectionProps) -> impl Into<An
                     content: {
                         let (line, col) = cursor_position.get();
                         format!(
-                            "~/projects/trae-agent-rs (main*)  Ln {}, Col {}",
-                            line, col
+                            "~/projects/trae-agent-rs (main*)  Ln {}, Col {}  ·  {}",
+                            line, col, &*active_model.read()
                         )
                     },
                     color: Color::DarkGrey,
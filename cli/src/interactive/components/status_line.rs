@@ -89,7 +89,9 @@ pub fn DynamicStatusLine(
                 }
                 AppMessage::SystemMessage(_)
                 | AppMessage::UserMessage(_)
-                | AppMessage::InteractiveUpdate(_) => {
+                | AppMessage::InteractiveUpdate(_)
+                | AppMessage::ModelChanged { .. }
+                | AppMessage::Quit => {
                     // Ignored for status line
                 }
             }
@@ -75,6 +75,8 @@ pub enum AppMessage {
     AgentExecutionCompleted,
     AgentExecutionInterrupted { user_input: String },
     TokenUpdate { tokens: u32 },
+    ModelChanged { model: String },
+    Quit,
 }
 
 /// Get a random status word
@@ -223,6 +225,8 @@ pub fn app_message_to_ui_message(
             false,
         )),
         AppMessage::TokenUpdate { .. } => None, // Token updates don't create UI messages, they update state directly
+        AppMessage::ModelChanged { .. } => None,
+        AppMessage::Quit => None,
     }
 }
 
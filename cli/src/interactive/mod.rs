@@ -9,6 +9,7 @@ pub mod input_history;
 pub mod message_handler;
 pub mod pages;
 pub mod router;
+pub mod slash_commands;
 pub mod state;
 pub mod task_executor;
 pub mod terminal_output;
@@ -2,7 +2,9 @@
 //!
 //! This page contains the primary interface with status line and input section.
 
-use crate::interactive::components::input_section::{InputSection, InputSectionContext};
+use crate::interactive::components::input_section::{
+    InputSection, InputSectionContext, InputSectionProps,
+};
 use crate::interactive::components::status_line::{DynamicStatusLine, StatusLineContext};
 use iocraft::prelude::*;
 
@@ -22,17 +24,7 @@ impl Default for MainPageProps {
                 ui_sender: tokio::sync::broadcast::channel(1).0,
                 ui_anim: crate::interactive::animation::UiAnimationConfig::default(),
             },
-            input_context: InputSectionContext {
-                llm_config: coro_core::ResolvedLlmConfig::new(
-                    coro_core::Protocol::OpenAICompat,
-                    "https://api.openai.com/v1".to_string(),
-                    "test-key".to_string(),
-                    "gpt-4o".to_string(),
-                ),
-                project_path: std::path::PathBuf::from("."),
-                ui_sender: tokio::sync::broadcast::channel(1).0,
-                agent: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
-            },
+            input_context: InputSectionProps::default().context,
         }
     }
 }
@@ -132,6 +132,13 @@ impl ReactiveRouterHandle {
     }
 }
 
+impl crate::interactive::slash_commands::CommandRouter for ReactiveRouterHandle {
+    fn navigate(&mut self, route: &str) -> anyhow::Result<()> {
+        ReactiveRouterHandle::navigate(self, route)?;
+        Ok(())
+    }
+}
+
 /// Router context for sharing router handle with child components
 #[derive(Clone)]
 pub struct RouterContext {
@@ -0,0 +1,683 @@
+//! Built-in slash commands
+
+use super::registry::{
+    CommandContext, CommandOutcome, ModelOption, SlashCommand, SlashCommandInfo,
+};
+use crate::interactive::message_handler::AppMessage;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use coro_core::agent::instructions::{
+    discover_instructions, project_root, user_instructions_path, INSTRUCTION_FILE_NAMES,
+};
+use coro_core::llm::create_llm_client;
+use coro_core::ResolvedLlmConfig;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// All built-in commands
+pub fn builtin_commands() -> Vec<Arc<dyn SlashCommand>> {
+    vec![
+        Arc::new(HelpCommand),
+        Arc::new(ClearCommand),
+        Arc::new(ModelCommand),
+        Arc::new(ToolsCommand),
+        Arc::new(CostCommand),
+        Arc::new(CompactCommand),
+        Arc::new(ConfigCommand),
+        Arc::new(MemoryCommand),
+        Arc::new(JobsCommand),
+        Arc::new(QuitCommand),
+        Arc::new(RouterTestCommand),
+    ]
+}
+
+/// Format commands as an aligned two-column list
+fn format_command_list(commands: &[&SlashCommandInfo]) -> String {
+    let labels: Vec<String> = commands
+        .iter()
+        .map(|info| match &info.usage {
+            Some(usage) => format!("/{} {}", info.name, usage),
+            None => format!("/{}", info.name),
+        })
+        .collect();
+    let width = labels.iter().map(|label| label.len()).max().unwrap_or(0);
+
+    commands
+        .iter()
+        .zip(&labels)
+        .map(|(info, label)| format!("  {:width$}  {}", label, info.description, width = width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `/help` - list available commands
+pub struct HelpCommand;
+
+#[async_trait]
+impl SlashCommand for HelpCommand {
+    fn name(&self) -> &str {
+        "help"
+    }
+
+    fn description(&self) -> &str {
+        "Show available commands"
+    }
+
+    async fn execute(&self, _args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        let (custom, builtin): (Vec<_>, Vec<_>) = ctx.commands.iter().partition(|info| info.custom);
+
+        let mut help = format!("Available commands:\n{}", format_command_list(&builtin));
+        if !custom.is_empty() {
+            help.push_str(&format!(
+                "\n\nProject commands (.coro/commands):\n{}",
+                format_command_list(&custom)
+            ));
+        }
+        help.push_str("\n\nReference files with @path/to/file. Press Esc to interrupt a task.");
+
+        ctx.print(help);
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+/// `/clear` - drop the conversation and start a new session
+pub struct ClearCommand;
+
+#[async_trait]
+impl SlashCommand for ClearCommand {
+    fn name(&self) -> &str {
+        "clear"
+    }
+
+    fn description(&self) -> &str {
+        "Clear the conversation and start a new session"
+    }
+
+    async fn execute(&self, _args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        // The next task creates a fresh agent for the new session
+        *ctx.agent.lock().await = None;
+        ctx.session.lock().await.start_new();
+
+        let _ = ctx.ui_sender.send(AppMessage::TokenUpdate { tokens: 0 });
+        ctx.print("Conversation cleared. The previous session can still be resumed.");
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+/// Whether two configurations talk to the same model
+fn is_same_model(a: &ResolvedLlmConfig, b: &ResolvedLlmConfig) -> bool {
+    a.protocol == b.protocol && a.base_url == b.base_url && a.model == b.model
+}
+
+/// Resolve the `/model` argument to a configuration
+///
+/// The argument is either the name of a model option or a model id, which is
+/// used with the provider of the active configuration.
+fn resolve_model_choice(
+    choice: &str,
+    options: &[ModelOption],
+    active: &ResolvedLlmConfig,
+) -> ResolvedLlmConfig {
+    match options.iter().find(|option| option.name == choice) {
+        Some(option) => option.llm_config.clone(),
+        None => ResolvedLlmConfig {
+            model: choice.to_string(),
+            ..active.clone()
+        },
+    }
+}
+
+/// `/model` - list models or switch the active one
+pub struct ModelCommand;
+
+#[async_trait]
+impl SlashCommand for ModelCommand {
+    fn name(&self) -> &str {
+        "model"
+    }
+
+    fn description(&self) -> &str {
+        "List models or switch the active model"
+    }
+
+    fn usage(&self) -> Option<&str> {
+        Some("[profile|model-id]")
+    }
+
+    async fn execute(&self, args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        let active = ctx.session.lock().await.llm_config().clone();
+
+        if args.is_empty() {
+            let width = ctx
+                .models
+                .iter()
+                .map(|option| option.name.len())
+                .max()
+                .unwrap_or(0);
+            let lines: Vec<String> = ctx
+                .models
+                .iter()
+                .map(|option| {
+                    let marker = if is_same_model(&option.llm_config, &active) {
+                        "*"
+                    } else {
+                        " "
+                    };
+                    format!(
+                        "  {} {:width$}  {} ({})",
+                        marker,
+                        option.name,
+                        option.llm_config.model,
+                        option.llm_config.protocol.as_str(),
+                        width = width
+                    )
+                })
+                .collect();
+
+            ctx.print(format!(
+                "Active model: {} ({})\n\nConfigured profiles:\n{}\n\nUse /model <profile> to switch, or /model <model-id> for another model of the current provider.",
+                active.model,
+                active.protocol.as_str(),
+                lines.join("\n")
+            ));
+            return Ok(CommandOutcome::Handled);
+        }
+
+        let llm_config = resolve_model_choice(args, &ctx.models, &active);
+        if is_same_model(&llm_config, &active) {
+            ctx.print(format!("Already using {}.", active.model));
+            return Ok(CommandOutcome::Handled);
+        }
+
+        // Create the client first so an unusable configuration leaves everything unchanged
+        llm_config
+            .validate()
+            .map_err(|e| anyhow!("invalid model configuration: {}", e))?;
+        let llm_client = create_llm_client(&llm_config)?;
+
+        if let Some(agent) = ctx.agent.lock().await.as_mut() {
+            agent.set_llm_client(llm_client);
+        }
+        ctx.session.lock().await.set_llm_config(llm_config.clone());
+
+        let _ = ctx.ui_sender.send(AppMessage::ModelChanged {
+            model: llm_config.model.clone(),
+        });
+        ctx.print(format!(
+            "Switched to {} ({}).",
+            llm_config.model,
+            llm_config.protocol.as_str()
+        ));
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+/// `/tools` - list the tools available to the agent
+pub struct ToolsCommand;
+
+#[async_trait]
+impl SlashCommand for ToolsCommand {
+    fn name(&self) -> &str {
+        "tools"
+    }
+
+    fn description(&self) -> &str {
+        "List the tools available to the agent"
+    }
+
+    async fn execute(&self, _args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        let tool_names = match ctx.agent.lock().await.as_ref() {
+            Some(agent) => agent.config().tools.clone(),
+            None => ctx
+                .settings
+                .tools
+                .tool_names(crate::tools::get_interactive_cli_tools()),
+        };
+
+        let registry = crate::tools::create_configured_tool_registry(&ctx.settings.tools);
+        let lines: Vec<String> = tool_names
+            .iter()
+            .map(|name| match registry.get_tool_info(name) {
+                Some((_, description)) => {
+                    let first_line = description.lines().next().unwrap_or(description);
+                    format!("  {}  {}", name, first_line.trim())
+                }
+                None => format!("  {}", name),
+            })
+            .collect();
+
+        ctx.print(format!("Available tools:\n{}", lines.join("\n")));
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+/// `/cost` - show token usage of the session
+pub struct CostCommand;
+
+#[async_trait]
+impl SlashCommand for CostCommand {
+    fn name(&self) -> &str {
+        "cost"
+    }
+
+    fn description(&self) -> &str {
+        "Show token usage of this session"
+    }
+
+    async fn execute(&self, _args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        let agent = ctx.agent.lock().await;
+        let usage = ctx.session.lock().await.token_usage(agent.as_ref());
+
+        ctx.print(format!(
+            "Token usage:\n  Input:  {}\n  Output: {}\n  Total:  {}",
+            usage.input_tokens, usage.output_tokens, usage.total_tokens
+        ));
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+/// `/compact` - replace the conversation with a summary
+pub struct CompactCommand;
+
+#[async_trait]
+impl SlashCommand for CompactCommand {
+    fn name(&self) -> &str {
+        "compact"
+    }
+
+    fn description(&self) -> &str {
+        "Summarize the conversation to free up context"
+    }
+
+    fn usage(&self) -> Option<&str> {
+        Some("[instructions]")
+    }
+
+    async fn execute(&self, args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        let mut agent_guard = ctx.agent.lock().await;
+        let Some(agent) = agent_guard.as_mut() else {
+            ctx.print("No active conversation to compact.");
+            return Ok(CommandOutcome::Handled);
+        };
+
+        let before = agent.conversation_history().len();
+        let _ = ctx.ui_sender.send(AppMessage::AgentTaskStarted {
+            operation: "Compacting".to_string(),
+        });
+        let result = agent
+            .compact_conversation(Some(args).filter(|a| !a.is_empty()))
+            .await;
+        let _ = ctx.ui_sender.send(AppMessage::AgentExecutionCompleted);
+
+        result?;
+
+        if let Err(e) = ctx.session.lock().await.record(agent) {
+            tracing::warn!("Failed to save session: {}", e);
+        }
+
+        ctx.print(format!(
+            "Conversation compacted ({} messages → {}).",
+            before,
+            agent.conversation_history().len()
+        ));
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+/// `/config` - show the effective configuration
+pub struct ConfigCommand;
+
+#[async_trait]
+impl SlashCommand for ConfigCommand {
+    fn name(&self) -> &str {
+        "config"
+    }
+
+    fn description(&self) -> &str {
+        "Show the current configuration"
+    }
+
+    async fn execute(&self, _args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        let (config, session_id) = {
+            let session = ctx.session.lock().await;
+            (session.llm_config().clone(), session.session().id.clone())
+        };
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "default".to_string());
+
+        ctx.print(format!(
+            "Configuration:\n  Protocol:    {}\n  Model:       {}\n  Base URL:    {}\n  API key:     {}\n  Max tokens:  {}\n  Temperature: {}\n  Project:     {}\n  Session:     {}",
+            config.protocol.as_str(),
+            config.model,
+            config.base_url,
+            if config.api_key.is_empty() { "(not set)" } else { "(hidden)" },
+            optional(config.params.max_tokens.map(|v| v.to_string())),
+            optional(config.params.temperature.map(|v| v.to_string())),
+            ctx.project_path.display(),
+            session_id
+        ));
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+/// `/memory` - show the instruction files or add a note to one
+pub struct MemoryCommand;
+
+#[async_trait]
+impl SlashCommand for MemoryCommand {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    fn description(&self) -> &str {
+        "Show the CORO.md instruction files or add a note to them"
+    }
+
+    fn usage(&self) -> Option<&str> {
+        Some("[add [--user] <note>]")
+    }
+
+    async fn execute(&self, args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        let (user, note) = match parse_memory_args(args)? {
+            MemoryAction::List => {
+                let files = discover_instructions(&ctx.project_path);
+                let mut message = if files.is_empty() {
+                    format!(
+                        "No instruction files found. Use /memory add <note> to create {}.",
+                        INSTRUCTION_FILE_NAMES[0]
+                    )
+                } else {
+                    let lines: Vec<String> = files
+                        .iter()
+                        .map(|file| {
+                            format!(
+                                "  {:9}  {} ({} bytes)",
+                                file.scope.as_str(),
+                                file.path.display(),
+                                file.content.len()
+                            )
+                        })
+                        .collect();
+                    format!("Instruction files:\n{}", lines.join("\n"))
+                };
+                if ctx.settings.agent.instructions == Some(false) {
+                    message.push_str("\n\nInstruction files are disabled by agent.instructions.");
+                }
+                ctx.print(message);
+                return Ok(CommandOutcome::Handled);
+            }
+            MemoryAction::Add { user, note } => (user, note),
+        };
+
+        let path = if user {
+            user_instructions_path()
+                .ok_or_else(|| anyhow!("Could not determine the config directory"))?
+        } else {
+            project_root(&ctx.project_path).join(INSTRUCTION_FILE_NAMES[0])
+        };
+
+        append_note(&path, note)?;
+        if let Some(agent) = ctx.agent.lock().await.as_mut() {
+            agent.refresh_system_prompt(&ctx.project_path);
+        }
+        ctx.print(format!("Added to {}.", path.display()));
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+/// What `/memory` was asked to do
+#[derive(Debug, PartialEq)]
+enum MemoryAction<'a> {
+    /// List the instruction files
+    List,
+    /// Add a note to the project file, or the user file with `--user`
+    Add { user: bool, note: &'a str },
+}
+
+/// Split the first whitespace-delimited word off `text`
+fn split_first_word(text: &str) -> (&str, &str) {
+    let text = text.trim();
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim_start()),
+        None => (text, ""),
+    }
+}
+
+fn parse_memory_args(args: &str) -> Result<MemoryAction<'_>> {
+    match split_first_word(args) {
+        ("", _) => Ok(MemoryAction::List),
+        ("add", rest) => {
+            let (user, note) = match split_first_word(rest) {
+                ("--user", note) => (true, note),
+                _ => (false, rest),
+            };
+            if note.is_empty() {
+                return Err(anyhow!("usage: /memory add [--user] <note>"));
+            }
+            Ok(MemoryAction::Add { user, note })
+        }
+        _ => Err(anyhow!("usage: /memory [add [--user] <note>]")),
+    }
+}
+
+/// Append a note as a list item, creating the file if needed
+fn append_note(path: &Path, note: &str) -> Result<()> {
+    // Read only to know whether the last line is terminated
+    let needs_newline = match std::fs::read(path) {
+        Ok(content) => content.last().is_some_and(|&byte| byte != b'\n'),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+        Err(e) => return Err(anyhow!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let entry = format!("{}- {}\n", if needs_newline { "\n" } else { "" }, note);
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(entry.as_bytes()))
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// `/jobs` - list the background jobs of the bash tool
+pub struct JobsCommand;
+
+#[async_trait]
+impl SlashCommand for JobsCommand {
+    fn name(&self) -> &str {
+        "jobs"
+    }
+
+    fn description(&self) -> &str {
+        "List background jobs started by the agent"
+    }
+
+    async fn execute(&self, _args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        let jobs = crate::tools::bash_jobs::all_jobs();
+        if jobs.is_empty() {
+            ctx.print("No background jobs.");
+            return Ok(CommandOutcome::Handled);
+        }
+
+        let lines: Vec<String> = jobs
+            .iter()
+            .map(|job| {
+                format!(
+                    "  [{}] {:<24} {:>6}s  {}",
+                    job.id,
+                    job.status.to_string(),
+                    job.elapsed.as_secs(),
+                    job.command
+                )
+            })
+            .collect();
+        ctx.print(format!("Background jobs:\n{}", lines.join("\n")));
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+/// `/quit` - exit interactive mode
+pub struct QuitCommand;
+
+#[async_trait]
+impl SlashCommand for QuitCommand {
+    fn name(&self) -> &str {
+        "quit"
+    }
+
+    fn description(&self) -> &str {
+        "Exit interactive mode"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["exit"]
+    }
+
+    async fn execute(&self, _args: &str, _ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        Ok(CommandOutcome::Quit)
+    }
+}
+
+/// `/test` - open the router test page (development aid)
+pub struct RouterTestCommand;
+
+#[async_trait]
+impl SlashCommand for RouterTestCommand {
+    fn name(&self) -> &str {
+        "test"
+    }
+
+    fn description(&self) -> &str {
+        "Open the router test page"
+    }
+
+    fn hidden(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, _args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        match ctx.router.as_mut() {
+            Some(router) => router.navigate("router_test")?,
+            None => ctx.print("Navigation is not available."),
+        }
+        Ok(CommandOutcome::Handled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_command_list_aligns_descriptions() {
+        let help = SlashCommandInfo {
+            name: "help".to_string(),
+            description: "Show help".to_string(),
+            usage: None,
+            custom: false,
+        };
+        let compact = SlashCommandInfo {
+            name: "compact".to_string(),
+            description: "Summarize".to_string(),
+            usage: Some("[instructions]".to_string()),
+            custom: false,
+        };
+
+        let list = format_command_list(&[&help, &compact]);
+        let lines: Vec<&str> = list.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0].find("Show help"),
+            lines[1].find("Summarize"),
+            "descriptions should be aligned: {:?}",
+            lines
+        );
+        assert!(lines[1].contains("/compact [instructions]"));
+    }
+
+    #[test]
+    fn test_append_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CORO.md");
+
+        append_note(&path, "Run cargo fmt").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "- Run cargo fmt\n");
+
+        std::fs::write(&path, "# Notes").unwrap();
+        append_note(&path, "Prefer anyhow").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Notes\n- Prefer anyhow\n"
+        );
+
+        // Content that is not UTF-8 is kept as it is
+        std::fs::write(&path, b"caf\xe9\n").unwrap();
+        append_note(&path, "Keep it").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"caf\xe9\n- Keep it\n");
+
+        // A file that cannot be read is not overwritten
+        assert!(append_note(dir.path(), "Not a file").is_err());
+    }
+
+    #[test]
+    fn test_parse_memory_args() {
+        assert_eq!(parse_memory_args("").unwrap(), MemoryAction::List);
+        assert_eq!(
+            parse_memory_args("add Run cargo fmt").unwrap(),
+            MemoryAction::Add {
+                user: false,
+                note: "Run cargo fmt"
+            }
+        );
+        assert_eq!(
+            parse_memory_args(" add  --user  Prefer anyhow ").unwrap(),
+            MemoryAction::Add {
+                user: true,
+                note: "Prefer anyhow"
+            }
+        );
+        assert_eq!(
+            parse_memory_args("add --username is jo").unwrap(),
+            MemoryAction::Add {
+                user: false,
+                note: "--username is jo"
+            }
+        );
+        assert!(parse_memory_args("address the flaky test").is_err());
+        assert!(parse_memory_args("add").is_err());
+        assert!(parse_memory_args("add --user").is_err());
+    }
+
+    #[test]
+    fn test_resolve_model_choice() {
+        let active = ResolvedLlmConfig::new(
+            coro_core::Protocol::OpenAICompat,
+            "https://api.openai.com/v1".to_string(),
+            "key".to_string(),
+            "gpt-4o".to_string(),
+        );
+        let claude = ResolvedLlmConfig::new(
+            coro_core::Protocol::Anthropic,
+            "https://api.anthropic.com".to_string(),
+            "other-key".to_string(),
+            "claude-sonnet".to_string(),
+        );
+        let options = vec![ModelOption {
+            name: "claude".to_string(),
+            llm_config: claude.clone(),
+        }];
+
+        // Named options switch the whole configuration
+        let picked = resolve_model_choice("claude", &options, &active);
+        assert!(is_same_model(&picked, &claude));
+        assert_eq!(picked.api_key, "other-key");
+
+        // Anything else is a model id for the active provider
+        let picked = resolve_model_choice("gpt-4o-mini", &options, &active);
+        assert_eq!(picked.model, "gpt-4o-mini");
+        assert_eq!(picked.protocol, active.protocol);
+        assert_eq!(picked.api_key, "key");
+    }
+}
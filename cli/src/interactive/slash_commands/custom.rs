@@ -0,0 +1,206 @@
+//! Project-defined slash commands loaded from markdown templates
+//!
+//! Every `.coro/commands/<name>.md` file in the project becomes a `/<name>`
+//! command. The file body is a prompt template sent to the agent; `$ARGUMENTS`
+//! is replaced with whatever follows the command name. An optional front
+//! matter block sets the description and argument hint:
+//!
+//! ```markdown
+//! ---
+//! description: Review the staged changes
+//! argument-hint: [focus area]
+//! ---
+//! Review the output of `git diff --staged`. Focus on: $ARGUMENTS
+//! ```
+
+use super::registry::{CommandContext, CommandOutcome, SlashCommand};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::Path;
+
+/// Directory with project command templates, relative to the project root
+pub const CUSTOM_COMMANDS_DIR: &str = ".coro/commands";
+
+/// Placeholder replaced with the command arguments
+const ARGUMENTS_PLACEHOLDER: &str = "$ARGUMENTS";
+
+/// A slash command backed by a prompt template
+#[derive(Debug, Clone)]
+pub struct CustomCommand {
+    name: String,
+    description: String,
+    usage: Option<String>,
+    template: String,
+}
+
+impl CustomCommand {
+    /// Parse a command template
+    pub fn parse(name: &str, content: &str) -> Self {
+        let mut description = None;
+        let mut usage = None;
+        let mut body = content;
+
+        // Optional front matter delimited by `---` lines
+        if let Some(rest) = content
+            .strip_prefix("---\n")
+            .or_else(|| content.strip_prefix("---\r\n"))
+        {
+            if let Some(end) = rest.find("\n---") {
+                for line in rest[..end].lines() {
+                    if let Some((key, value)) = line.split_once(':') {
+                        let value = value.trim().to_string();
+                        match key.trim() {
+                            "description" => description = Some(value),
+                            "argument-hint" => usage = Some(value),
+                            _ => {}
+                        }
+                    }
+                }
+
+                let after = &rest[end + "\n---".len()..];
+                body = after.split_once('\n').map(|(_, body)| body).unwrap_or("");
+            }
+        }
+
+        let template = body.trim().to_string();
+        let description = description.unwrap_or_else(|| {
+            // Fall back to the first line of the template
+            template
+                .lines()
+                .map(|line| line.trim_start_matches('#').trim())
+                .find(|line| !line.is_empty())
+                .unwrap_or("Custom command")
+                .to_string()
+        });
+
+        Self {
+            name: name.to_string(),
+            description,
+            usage: usage.filter(|u| !u.is_empty()),
+            template,
+        }
+    }
+
+    /// Render the prompt for the given arguments
+    pub fn render(&self, args: &str) -> String {
+        if self.template.contains(ARGUMENTS_PLACEHOLDER) {
+            self.template.replace(ARGUMENTS_PLACEHOLDER, args)
+        } else if args.is_empty() {
+            self.template.clone()
+        } else {
+            format!("{}\n\n{}", self.template, args)
+        }
+    }
+}
+
+#[async_trait]
+impl SlashCommand for CustomCommand {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn usage(&self) -> Option<&str> {
+        self.usage.as_deref()
+    }
+
+    fn is_custom(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: &str, _ctx: &mut CommandContext) -> Result<CommandOutcome> {
+        Ok(CommandOutcome::Submit(self.render(args)))
+    }
+}
+
+/// Load the command templates of a project, sorted by name
+///
+/// Files that cannot be read or do not have a valid command name are skipped.
+pub fn load_custom_commands(project_path: &Path) -> Vec<CustomCommand> {
+    let dir = project_path.join(CUSTOM_COMMANDS_DIR);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut commands = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            continue;
+        }
+
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if !super::registry::is_command_name(name) {
+            tracing::warn!(
+                "Skipping command template with invalid name: {}",
+                path.display()
+            );
+            continue;
+        }
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => commands.push(CustomCommand::parse(name, &content)),
+            Err(e) => tracing::warn!("Failed to read command {}: {}", path.display(), e),
+        }
+    }
+
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_front_matter() {
+        let command = CustomCommand::parse(
+            "review",
+            "---\ndescription: Review staged changes\nargument-hint: [focus]\n---\nReview the diff. Focus on: $ARGUMENTS\n",
+        );
+
+        assert_eq!(command.description(), "Review staged changes");
+        assert_eq!(command.usage(), Some("[focus]"));
+        assert_eq!(
+            command.render("error handling"),
+            "Review the diff. Focus on: error handling"
+        );
+    }
+
+    #[test]
+    fn test_parse_without_front_matter() {
+        let command = CustomCommand::parse(
+            "explain",
+            "# Explain the selected code\n\nExplain how it works.",
+        );
+
+        assert_eq!(command.description(), "Explain the selected code");
+        assert_eq!(command.usage(), None);
+        // Arguments are appended when the template has no placeholder
+        assert!(command.render("src/main.rs").ends_with("\n\nsrc/main.rs"));
+        assert!(!command.render("").ends_with('\n'));
+    }
+
+    #[test]
+    fn test_load_custom_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join(CUSTOM_COMMANDS_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("test-plan.md"), "Write a test plan").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a command").unwrap();
+        std::fs::write(dir.join("bad name.md"), "invalid").unwrap();
+
+        let commands = load_custom_commands(temp_dir.path());
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].name(), "test-plan");
+        assert!(commands[0].is_custom());
+
+        assert!(load_custom_commands(&temp_dir.path().join("missing")).is_empty());
+    }
+}
@@ -0,0 +1,16 @@
+//! Slash commands for the interactive input
+//!
+//! Input starting with `/` is dispatched to a [`SlashCommand`] from the
+//! [`SlashCommandRegistry`] instead of being sent to the agent. Built-in
+//! commands live in [`builtin`]; project-defined commands are loaded from
+//! markdown templates in `.coro/commands/` by [`custom`].
+
+pub mod builtin;
+pub mod custom;
+pub mod registry;
+
+pub use registry::{
+    command_completion_prefix, filter_commands, parse_slash_command, CommandContext,
+    CommandOutcome, CommandRouter, ModelOption, SlashCommand, SlashCommandInfo,
+    SlashCommandRegistry,
+};
@@ -0,0 +1,313 @@
+//! Slash command trait, context and registry
+
+use crate::config::Settings;
+use crate::interactive::message_handler::AppMessage;
+use crate::session::SessionRecorder;
+use anyhow::Result;
+use async_trait::async_trait;
+use coro_core::agent::AgentCore;
+use coro_core::ResolvedLlmConfig;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+
+/// Navigation capability handed to slash commands
+///
+/// Implemented by the UI router so commands can switch pages without
+/// depending on the UI framework.
+pub trait CommandRouter: Send {
+    /// Navigate to the route with the given id
+    fn navigate(&mut self, route: &str) -> Result<()>;
+}
+
+/// Everything a slash command can act on
+pub struct CommandContext {
+    /// Persistent agent of the interactive session (created lazily)
+    pub agent: Arc<Mutex<Option<AgentCore>>>,
+    /// Session the conversation is persisted to
+    pub session: Arc<Mutex<SessionRecorder>>,
+    /// Channel for UI messages
+    pub ui_sender: broadcast::Sender<AppMessage>,
+    /// UI router, if navigation is available
+    pub router: Option<Box<dyn CommandRouter>>,
+    pub project_path: PathBuf,
+    /// Commands available in this session, for help output
+    pub commands: Vec<SlashCommandInfo>,
+    /// Models that can be picked with `/model`
+    pub models: Vec<ModelOption>,
+    /// Agent and tool settings from the config file
+    pub settings: Arc<Settings>,
+}
+
+impl CommandContext {
+    /// Show a system message in the UI
+    pub fn print(&self, message: impl Into<String>) {
+        let _ = self
+            .ui_sender
+            .send(AppMessage::SystemMessage(message.into()));
+    }
+}
+
+/// A model configuration offered by the `/model` picker
+#[derive(Debug, Clone)]
+pub struct ModelOption {
+    /// Name used to pick the option
+    pub name: String,
+    pub llm_config: ResolvedLlmConfig,
+}
+
+/// What the UI should do after a command finished
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandOutcome {
+    /// The command was fully handled
+    Handled,
+    /// Send the given prompt to the agent as a regular task
+    Submit(String),
+    /// Exit interactive mode
+    Quit,
+}
+
+/// Display information about a command, used for help and autocomplete
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlashCommandInfo {
+    /// Command name without the leading slash
+    pub name: String,
+    pub description: String,
+    /// Argument hint, e.g. `[instructions]`
+    pub usage: Option<String>,
+    /// Whether the command was loaded from `.coro/commands`
+    pub custom: bool,
+}
+
+/// A command that can be invoked from the interactive input as `/name args`
+#[async_trait]
+pub trait SlashCommand: Send + Sync {
+    /// Command name without the leading slash
+    fn name(&self) -> &str;
+
+    /// One-line description shown in help and autocomplete
+    fn description(&self) -> &str;
+
+    /// Argument hint shown after the name, e.g. `[instructions]`
+    fn usage(&self) -> Option<&str> {
+        None
+    }
+
+    /// Alternative names for the command
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Hidden commands can be invoked but are not listed
+    fn hidden(&self) -> bool {
+        false
+    }
+
+    /// Whether the command comes from a project template
+    fn is_custom(&self) -> bool {
+        false
+    }
+
+    /// Run the command with the raw argument string
+    async fn execute(&self, args: &str, ctx: &mut CommandContext) -> Result<CommandOutcome>;
+
+    /// Display information for this command
+    fn info(&self) -> SlashCommandInfo {
+        SlashCommandInfo {
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            usage: self.usage().map(str::to_string),
+            custom: self.is_custom(),
+        }
+    }
+}
+
+/// Registry of available slash commands
+#[derive(Default)]
+pub struct SlashCommandRegistry {
+    commands: Vec<Arc<dyn SlashCommand>>,
+}
+
+impl SlashCommandRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in commands
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        for command in super::builtin::builtin_commands() {
+            registry.register(command);
+        }
+        registry
+    }
+
+    /// Create a registry with the built-in commands and the project's custom commands
+    pub fn for_project(project_path: &std::path::Path) -> Self {
+        let mut registry = Self::with_builtins();
+        for command in super::custom::load_custom_commands(project_path) {
+            let name = command.name().to_string();
+            if !registry.register(Arc::new(command)) {
+                tracing::warn!(
+                    "Custom command /{} conflicts with an existing command and was skipped",
+                    name
+                );
+            }
+        }
+        registry
+    }
+
+    /// Register a command
+    /// Returns false (and keeps the existing command) if the name is taken
+    pub fn register(&mut self, command: Arc<dyn SlashCommand>) -> bool {
+        if self.get(command.name()).is_some() {
+            return false;
+        }
+        self.commands.push(command);
+        true
+    }
+
+    /// Look up a command by name or alias (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<Arc<dyn SlashCommand>> {
+        self.commands
+            .iter()
+            .find(|command| {
+                command.name().eq_ignore_ascii_case(name)
+                    || command
+                        .aliases()
+                        .iter()
+                        .any(|alias| alias.eq_ignore_ascii_case(name))
+            })
+            .cloned()
+    }
+
+    /// List visible commands sorted by name
+    pub fn list(&self) -> Vec<SlashCommandInfo> {
+        let mut infos: Vec<SlashCommandInfo> = self
+            .commands
+            .iter()
+            .filter(|command| !command.hidden())
+            .map(|command| command.info())
+            .collect();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+}
+
+/// Filter command infos by name prefix (case-insensitive)
+pub fn filter_commands(commands: &[SlashCommandInfo], prefix: &str) -> Vec<SlashCommandInfo> {
+    let prefix = prefix.to_lowercase();
+    commands
+        .iter()
+        .filter(|info| info.name.to_lowercase().starts_with(&prefix))
+        .cloned()
+        .collect()
+}
+
+/// Whether a string is a valid command name
+pub(super) fn is_command_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+}
+
+/// Split input of the form `/name args` into the command name and its arguments
+///
+/// Returns `None` for input that is not a command, such as an absolute path
+/// like `/etc/hosts`.
+pub fn parse_slash_command(input: &str) -> Option<(String, String)> {
+    let rest = input.trim().strip_prefix('/')?;
+    let (name, args) = match rest.find(char::is_whitespace) {
+        Some(pos) => (&rest[..pos], rest[pos..].trim()),
+        None => (rest, ""),
+    };
+
+    is_command_name(name).then(|| (name.to_string(), args.to_string()))
+}
+
+/// The partial command name being typed, if the cursor is in a leading `/name`
+pub fn command_completion_prefix(value: &str, cursor: usize) -> Option<&str> {
+    let before_cursor = value.get(..cursor)?;
+    let prefix = before_cursor.strip_prefix('/')?;
+
+    (prefix.is_empty() || is_command_name(prefix)).then_some(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoCommand;
+
+    #[async_trait]
+    impl SlashCommand for EchoCommand {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the arguments back"
+        }
+
+        fn aliases(&self) -> &[&str] {
+            &["say"]
+        }
+
+        async fn execute(&self, args: &str, _ctx: &mut CommandContext) -> Result<CommandOutcome> {
+            Ok(CommandOutcome::Submit(args.to_string()))
+        }
+    }
+
+    #[test]
+    fn test_parse_slash_command() {
+        assert_eq!(
+            parse_slash_command("/help"),
+            Some(("help".to_string(), String::new()))
+        );
+        assert_eq!(
+            parse_slash_command("  /compact keep the todo list  "),
+            Some(("compact".to_string(), "keep the todo list".to_string()))
+        );
+        assert_eq!(parse_slash_command("hello /help"), None);
+        assert_eq!(parse_slash_command("/etc/hosts is broken"), None);
+        assert_eq!(parse_slash_command("/"), None);
+    }
+
+    #[test]
+    fn test_command_completion_prefix() {
+        assert_eq!(command_completion_prefix("/", 1), Some(""));
+        assert_eq!(command_completion_prefix("/he", 3), Some("he"));
+        assert_eq!(command_completion_prefix("/help me", 8), None);
+        assert_eq!(command_completion_prefix("/src/main.rs", 12), None);
+        assert_eq!(command_completion_prefix("hi", 2), None);
+    }
+
+    #[test]
+    fn test_registry_lookup_and_completion() {
+        let mut registry = SlashCommandRegistry::new();
+        assert!(registry.register(Arc::new(EchoCommand)));
+        assert!(!registry.register(Arc::new(EchoCommand)));
+
+        assert!(registry.get("echo").is_some());
+        assert!(registry.get("SAY").is_some());
+        assert!(registry.get("missing").is_none());
+
+        assert_eq!(filter_commands(&registry.list(), "EC").len(), 1);
+        assert!(filter_commands(&registry.list(), "x").is_empty());
+    }
+
+    #[test]
+    fn test_builtins_are_registered() {
+        let registry = SlashCommandRegistry::with_builtins();
+        for name in [
+            "help", "clear", "model", "tools", "cost", "compact", "config", "quit",
+        ] {
+            assert!(registry.get(name).is_some(), "missing /{}", name);
+        }
+        assert!(registry.get("exit").is_some());
+        // Hidden commands are not listed
+        assert!(registry.list().iter().all(|info| info.name != "test"));
+    }
+}
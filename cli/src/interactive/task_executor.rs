@@ -3,8 +3,10 @@
 //! This module handles agent task execution with UI integration,
 //! including token tracking and status updates.
 
+use crate::config::Settings;
 use crate::interactive::message_handler::AppMessage;
 use crate::output::interactive_handler::{InteractiveMessage, InteractiveOutputConfig};
+use crate::session::SessionRecorder;
 use anyhow::Result;
 use coro_core::ResolvedLlmConfig;
 use std::path::PathBuf;
@@ -78,10 +80,11 @@ impl coro_core::output::AgentOutput for TokenTrackingOutputHandler {
 /// Execute agent task with persistent agent to maintain conversation context
 pub async fn execute_agent_task_with_context(
     task: String,
-    llm_config: ResolvedLlmConfig,
     project_path: PathBuf,
     ui_sender: broadcast::Sender<AppMessage>,
     agent: std::sync::Arc<tokio::sync::Mutex<Option<coro_core::agent::AgentCore>>>,
+    session: std::sync::Arc<tokio::sync::Mutex<SessionRecorder>>,
+    settings: std::sync::Arc<Settings>,
 ) -> Result<()> {
     // Create a receiver to listen for interruption signals
     let mut interrupt_receiver = ui_sender.subscribe();
@@ -104,13 +107,7 @@ pub async fn execute_agent_task_with_context(
     // If no agent exists, create one
     if agent_guard.is_none() {
         // Create agent configuration with CLI tools and status_report tool for interactive mode
-        let mut agent_config = coro_core::AgentConfig {
-            tools: crate::tools::get_default_cli_tools(),
-            ..Default::default()
-        };
-        if !agent_config.tools.contains(&"status_report".to_string()) {
-            agent_config.tools.push("status_report".to_string());
-        }
+        let agent_config = settings.agent_config(crate::tools::get_interactive_cli_tools());
 
         // Create TokenTrackingOutputHandler with UI integration
         let interactive_config = InteractiveOutputConfig {
@@ -124,13 +121,14 @@ pub async fn execute_agent_task_with_context(
         ));
 
         // Create CLI tool registry with status_report tool for interactive mode
-        let mut tool_registry = crate::tools::create_cli_tool_registry();
+        let mut tool_registry = crate::tools::create_configured_tool_registry(&settings.tools);
         tool_registry.register_factory(Box::new(StatusReportToolFactory::with_ui_sender(
             ui_sender.clone(),
         )));
 
-        // Create new agent
-        let new_agent = coro_core::agent::AgentCore::new_with_output_and_registry(
+        // Create new agent with the model the session currently uses
+        let llm_config = session.lock().await.llm_config().clone();
+        let mut new_agent = coro_core::agent::AgentCore::new_with_output_and_registry(
             agent_config,
             llm_config,
             token_tracking_output,
@@ -138,6 +136,9 @@ pub async fn execute_agent_task_with_context(
         )
         .await?;
 
+        // Seed the agent with the conversation of a resumed session
+        new_agent.import_conversation_history(session.lock().await.conversation_history());
+
         *agent_guard = Some(new_agent);
     }
 
@@ -166,20 +167,25 @@ pub async fn execute_agent_task_with_context(
     let timeout_future = tokio::time::sleep(tokio::time::Duration::from_secs(300)); // 5 minutes timeout
 
     // Race between task execution, interruption, and timeout
-    tokio::select! {
+    let outcome = tokio::select! {
         result = task_future => {
-            result?;
+            result.map(|_| ()).map_err(anyhow::Error::from)
         }
         interrupt_result = interrupt_future => {
-            interrupt_result?;
+            interrupt_result
         }
         _ = timeout_future => {
             tracing::error!("Task execution timed out after 5 minutes");
-            return Err(anyhow::anyhow!("Task execution timed out"));
+            Err(anyhow::anyhow!("Task execution timed out"))
         }
+    };
+
+    // Persist the session after every task, including interrupted ones
+    if let Err(e) = session.lock().await.record(agent_ref) {
+        tracing::warn!("Failed to save session: {}", e);
     }
 
-    Ok(())
+    outcome
 }
 
 /// Execute agent task asynchronously and send updates to UI
@@ -194,13 +200,11 @@ pub async fn execute_agent_task(
     use crate::tools::StatusReportToolFactory;
 
     // Create agent configuration with CLI tools and status_report tool for interactive mode
-    let mut agent_config = coro_core::AgentConfig {
-        tools: crate::tools::get_default_cli_tools(),
+    let agent_config = coro_core::AgentConfig {
+        tools: crate::tools::get_interactive_cli_tools(),
+        tool_output_dir: Some(coro_core::tools::output_budget::default_spill_dir()),
         ..Default::default()
     };
-    if !agent_config.tools.contains(&"status_report".to_string()) {
-        agent_config.tools.push("status_report".to_string());
-    }
 
     // Create channel for InteractiveMessage and forward to AppMessage
     let (interactive_sender, mut interactive_receiver) = mpsc::unbounded_channel();
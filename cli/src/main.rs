@@ -6,6 +6,17 @@
 //!
 //! - `coro` - Start interactive mode
 //! - `coro "task description"` - Execute a single task
+//! - `coro -p -` / `coro --task-file task.md` - Read the task from stdin or a file
+//! - `cargo test 2>&1 | coro "fix these failures"` - Attach piped input as context
+//! - `coro --output-format stream-json "task"` - Execute a task with NDJSON event output
+//! - `coro --continue` / `coro --resume [id]` - Continue a saved interactive session
+//! - `coro sessions list` - List saved sessions
+//! - `coro config init` - Create a config file with an interactive wizard
+//! - `coro config set agent.max_steps 100` - Set a value in the project config file
+//! - `coro config validate` - Check every profile and its connection
+//! - `coro config show --origin` - Show the merged configuration and where each value comes from
+//! - `coro config schema` - Print the JSON Schema of config files
+//! - `coro doctor` - Diagnose the configuration and environment
 //! - `coro tools` - Show available tools
 //! - `coro test` - Run basic tests
 //!
@@ -13,18 +24,28 @@
 //! with a beautiful terminal UI powered by iocraft.
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Parser, Subcommand};
+use coro_core::config::RedactingWriter;
+use coro_core::PromptMode;
 use std::path::PathBuf;
 
 mod commands;
 mod config;
 mod interactive;
 mod output;
+mod session;
 mod tools;
 mod ui;
 
-use commands::{interactive_command, run_command, test_command, tools_command};
+use commands::config::ConfigCommand;
+use commands::interactive::SessionSelection;
+use commands::sessions::SessionsCommand;
+use commands::{
+    config_command, doctor_command, interactive_command, run_command, sessions_command,
+    test_command, tools_command,
+};
 use config::CliConfigLoader;
+use output::json_handler::OutputFormat;
 
 /// coro - A high-performance AI coding agent
 #[derive(Parser)]
@@ -32,11 +53,16 @@ use config::CliConfigLoader;
 #[command(version = env!("CARGO_PKG_VERSION"))]
 #[command(about = "A high-performance AI coding agent written in Rust")]
 #[command(long_about = None)]
+#[command(group(ArgGroup::new("session").args(["resume", "continue_session"])))]
 struct Cli {
-    /// Configuration file or directory path
+    /// Configuration file or directory path, merged on top of the discovered config files
     #[arg(short, long)]
     config: Option<PathBuf>,
 
+    /// Named model profile from the config file
+    #[arg(long, env = "CORO_PROFILE")]
+    profile: Option<String>,
+
     /// Protocol to use (openai, anthropic, google_ai, azure_openai)
     #[arg(long)]
     protocol: Option<String>,
@@ -65,6 +91,10 @@ struct Cli {
     #[arg(long)]
     working_dir: Option<PathBuf>,
 
+    /// System prompt preset: bugfix, feature, review, explain or refactor
+    #[arg(long)]
+    mode: Option<PromptMode>,
+
     /// Maximum number of steps (for run mode)
     #[arg(long)]
     max_steps: Option<usize>,
@@ -81,6 +111,30 @@ struct Cli {
     #[arg(long, default_value = "changes.patch")]
     patch_path: PathBuf,
 
+    /// Output format (for run mode); json formats write logs to stderr
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+
+    /// Task prompt for single-task mode; use "-" to read it from stdin
+    #[arg(short = 'p', long = "prompt", conflicts_with_all = ["task", "task_file"])]
+    prompt: Option<String>,
+
+    /// Read the task for single-task mode from a file
+    #[arg(long, conflicts_with = "task")]
+    task_file: Option<PathBuf>,
+
+    /// Resume a saved session (interactive mode); without an id, pick one from a list
+    #[arg(long, value_name = "ID", conflicts_with_all = ["task", "prompt", "task_file"])]
+    resume: Option<Option<String>>,
+
+    /// Continue the most recent session in the current directory (interactive mode)
+    #[arg(long = "continue", conflicts_with_all = ["task", "prompt", "task_file"])]
+    continue_session: bool,
+
+    /// Fork the resumed session into a new one instead of appending to it
+    #[arg(long, requires = "session")]
+    fork_session: bool,
+
     /// The task to execute (if provided, runs in single-task mode)
     task: Option<String>,
 
@@ -95,6 +149,29 @@ enum Commands {
 
     /// Run basic tests
     Test,
+
+    /// Manage saved interactive sessions
+    Sessions {
+        #[command(subcommand)]
+        command: SessionsCommand,
+    },
+
+    /// Diagnose the configuration and environment
+    Doctor {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Skip the API connectivity check
+        #[arg(long)]
+        offline: bool,
+    },
+
+    /// Create, edit and inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
 }
 
 /// Build a configuration loader from CLI arguments
@@ -105,6 +182,10 @@ fn build_config_loader(cli: &Cli) -> CliConfigLoader {
         loader = loader.with_config_override(config_path.clone());
     }
 
+    if let Some(profile) = &cli.profile {
+        loader = loader.with_profile(profile.clone());
+    }
+
     if let Some(protocol) = &cli.protocol {
         loader = loader.with_protocol_override(protocol.clone());
     }
@@ -121,6 +202,10 @@ fn build_config_loader(cli: &Cli) -> CliConfigLoader {
         loader = loader.with_model_override(model.clone());
     }
 
+    if let Some(mode) = cli.mode {
+        loader = loader.with_mode_override(mode);
+    }
+
     loader
 }
 
@@ -129,15 +214,26 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     // Initialize tracing, only for run mode to debug
-    let filter = if (cli.verbose || cli.debug_output) && cli.task.is_some() {
+    let is_run_mode = cli.task.is_some() || cli.prompt.is_some() || cli.task_file.is_some();
+    let filter = if (cli.verbose || cli.debug_output) && is_run_mode {
         "debug"
     } else {
         "info"
     };
 
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::new(filter))
-        .init();
+    let subscriber =
+        tracing_subscriber::fmt().with_env_filter(tracing_subscriber::EnvFilter::new(filter));
+
+    // Keep stdout clean for machine-readable output
+    if cli.output_format.is_json() {
+        subscriber
+            .with_writer(|| RedactingWriter::new(std::io::stderr()))
+            .init();
+    } else {
+        subscriber
+            .with_writer(|| RedactingWriter::new(std::io::stdout()))
+            .init();
+    }
 
     // Change working directory if specified
     if let Some(working_dir) = &cli.working_dir {
@@ -147,11 +243,15 @@ async fn main() -> Result<()> {
     // Build configuration loader
     let config_loader = build_config_loader(&cli);
 
-    match (cli.task, cli.command) {
+    // Resolve the task from argument, stdin or file
+    let task_input = commands::run::read_task_input(cli.task, cli.prompt, cli.task_file)?;
+
+    match (task_input, cli.command) {
         // If task is provided, run in single-task mode
-        (Some(task), None) => {
-            run_command(commands::run::RunConfig {
-                task,
+        (Some(task_input), None) => {
+            let exit_code = run_command(commands::run::RunConfig {
+                task: task_input.task,
+                piped_context: task_input.piped_context,
                 config_loader,
                 max_steps: cli.max_steps,
                 trajectory_file: cli.trajectory_file,
@@ -159,8 +259,13 @@ async fn main() -> Result<()> {
                 patch_path: cli.patch_path,
                 working_dir: cli.working_dir,
                 debug_output: cli.debug_output,
+                output_format: cli.output_format,
             })
-            .await
+            .await?;
+            if exit_code != 0 {
+                std::process::exit(exit_code);
+            }
+            Ok(())
         }
         // If task is provided with a subcommand, that's an error
         (Some(_), Some(_)) => {
@@ -170,9 +275,27 @@ async fn main() -> Result<()> {
         // Handle subcommands
         (None, Some(Commands::Tools)) => tools_command().await,
         (None, Some(Commands::Test)) => test_command().await,
+        (None, Some(Commands::Sessions { command })) => sessions_command(command).await,
+        (None, Some(Commands::Config { command })) => config_command(command, config_loader).await,
+        (None, Some(Commands::Doctor { json, offline })) => {
+            doctor_command(config_loader, json, offline).await
+        }
         // Default to interactive mode
         (None, None) => {
-            interactive_command(config_loader, cli.trajectory_file, cli.debug_output).await
+            let session_selection = match cli.resume {
+                Some(id) => SessionSelection::Resume(id),
+                None if cli.continue_session => SessionSelection::Continue,
+                None => SessionSelection::New,
+            };
+
+            interactive_command(
+                config_loader,
+                cli.trajectory_file,
+                cli.debug_output,
+                session_selection,
+                cli.fork_session,
+            )
+            .await
         }
     }
 }
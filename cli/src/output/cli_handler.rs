@@ -1,9 +1,11 @@
 //! CLI output handler implementation
 
-use super::formatters::{DiffFormatter, ToolFormatter};
+use super::formatters::{DiffFormatter, LiveTail, ToolFormatter};
+use crate::interactive::text_utils::get_terminal_width;
 use async_trait::async_trait;
 use coro_core::output::{AgentEvent, AgentOutput, MessageLevel};
 use std::collections::HashMap;
+use std::io::IsTerminal;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
@@ -38,6 +40,49 @@ pub struct CliOutputHandler {
     diff_formatter: DiffFormatter,
     /// Track active tool executions for real-time updates
     active_tools: Arc<Mutex<HashMap<String, coro_core::output::ToolExecutionInfo>>>,
+    /// Output tails of running tools, only drawn when stdout is a terminal
+    live_region: Arc<Mutex<LiveRegion>>,
+    live_output: bool,
+}
+
+/// Output tails of all running tools, drawn together below the last line
+#[derive(Default)]
+struct LiveRegion {
+    tails: Vec<(String, LiveTail)>,
+    /// Lines drawn the last time
+    shown: usize,
+}
+
+impl LiveRegion {
+    fn tail(&mut self, execution_id: &str) -> &mut LiveTail {
+        let index = match self.tails.iter().position(|(id, _)| id == execution_id) {
+            Some(index) => index,
+            None => {
+                self.tails
+                    .push((execution_id.to_string(), LiveTail::default()));
+                self.tails.len() - 1
+            }
+        };
+        &mut self.tails[index].1
+    }
+
+    /// Erase the drawn lines, leaving the cursor where they started
+    fn erase(&mut self) {
+        if self.shown > 0 {
+            print!("\x1b[{}A\x1b[0J", self.shown);
+            self.shown = 0;
+        }
+    }
+
+    fn draw(&mut self) {
+        let width = get_terminal_width();
+        for (_, tail) in &self.tails {
+            for line in tail.render(width) {
+                println!("{}", line);
+                self.shown += 1;
+            }
+        }
+    }
 }
 
 impl CliOutputHandler {
@@ -48,6 +93,8 @@ impl CliOutputHandler {
             tool_formatter: ToolFormatter::new(),
             diff_formatter: DiffFormatter::new(),
             active_tools: Arc::new(Mutex::new(HashMap::new())),
+            live_region: Arc::new(Mutex::new(LiveRegion::default())),
+            live_output: std::io::stdout().is_terminal(),
         }
     }
 
@@ -124,8 +171,23 @@ impl AgentOutput for CliOutputHandler {
                 active_tools.insert(tool_info.execution_id.clone(), tool_info);
             }
 
-            AgentEvent::ToolExecutionUpdated { tool_info: _ } => {
-                // ...
+            AgentEvent::ToolExecutionUpdated {
+                tool_info,
+                output_chunk,
+            } => {
+                if is_silent_tool(&tool_info.tool_name) || !self.live_output {
+                    return Ok(());
+                }
+
+                // Redraw the tails of all running tools below the status lines
+                use std::io::Write;
+                let mut live_region = self.live_region.lock().await;
+                live_region
+                    .tail(&tool_info.execution_id)
+                    .push(&output_chunk);
+                live_region.erase();
+                live_region.draw();
+                std::io::stdout().flush().unwrap_or(());
             }
 
             AgentEvent::ToolExecutionCompleted { tool_info } => {
@@ -139,8 +201,13 @@ impl AgentOutput for CliOutputHandler {
                 if active_tools.contains_key(&tool_info.execution_id) {
                     // Tool was tracked, try to update the existing line
                     use std::io::Write;
-                    // Try a different approach: move up and clear
-                    print!("\x1b[1A\x1b[2K\r");
+                    // Collapse the live output tails together with the status line
+                    let mut live_region = self.live_region.lock().await;
+                    live_region.erase();
+                    live_region
+                        .tails
+                        .retain(|(id, _)| id != &tool_info.execution_id);
+                    print!("\x1b[1A\x1b[0J\r");
                     std::io::stdout().flush().unwrap_or(());
 
                     active_tools.remove(&tool_info.execution_id);
@@ -162,11 +229,17 @@ impl AgentOutput for CliOutputHandler {
                 }
 
                 // Show diff for edit tools
-                if tool_info.tool_name == "str_replace_based_edit_tool" {
+                if matches!(
+                    tool_info.tool_name.as_str(),
+                    "str_replace_based_edit_tool" | "apply_patch"
+                ) {
                     if let Some(diff_display) = self.diff_formatter.format_edit_result(&tool_info) {
                         println!("{}", diff_display);
                     }
                 }
+
+                // Tools still running keep their tails below
+                self.live_region.lock().await.draw();
             }
 
             AgentEvent::AgentThinking {
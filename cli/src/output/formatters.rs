@@ -1,6 +1,8 @@
 //! Formatting utilities for CLI output
 
+use crate::interactive::text_utils::char_width;
 use coro_core::output::{ToolExecutionInfo, ToolExecutionStatus};
+use std::collections::VecDeque;
 use std::path::Path;
 use tracing::debug;
 
@@ -63,7 +65,10 @@ impl ToolFormatter {
                 // Determine operation type based on parameters
                 if tool_info.parameters.contains_key("file_text") {
                     "Create".to_string()
-                } else if tool_info.parameters.contains_key("old_str") {
+                } else if tool_info.parameters.contains_key("old_str")
+                    || tool_info.parameters.contains_key("edits")
+                    || tool_info.parameters.contains_key("files")
+                {
                     "Update".to_string()
                 } else if tool_info.parameters.contains_key("view_range")
                     || tool_info.parameters.get("command").and_then(|v| v.as_str()) == Some("view")
@@ -73,6 +78,7 @@ impl ToolFormatter {
                     "Edit".to_string()
                 }
             }
+            "apply_patch" => "Patch".to_string(),
             "task_done" => "Complete".to_string(),
             "sequentialthinking" => "Think".to_string(),
             _ => {
@@ -100,7 +106,25 @@ impl ToolFormatter {
                 .and_then(|v| v.as_str())
                 .unwrap_or("")
                 .to_string(),
-            "str_replace_based_edit_tool" => tool_info
+            "str_replace_based_edit_tool" => {
+                match tool_info.parameters.get("files").and_then(|v| v.as_array()) {
+                    // batch_edit has no single path
+                    Some(files) => format!("{} files", files.len()),
+                    None => tool_info
+                        .parameters
+                        .get("path")
+                        .and_then(|v| v.as_str())
+                        .map(|path| {
+                            Path::new(path)
+                                .file_name()
+                                .and_then(|name| name.to_str())
+                                .unwrap_or(path)
+                                .to_string()
+                        })
+                        .unwrap_or_else(|| "file".to_string()),
+                }
+            }
+            "read" => tool_info
                 .parameters
                 .get("path")
                 .and_then(|v| v.as_str())
@@ -112,6 +136,23 @@ impl ToolFormatter {
                         .to_string()
                 })
                 .unwrap_or_else(|| "file".to_string()),
+            "apply_patch" => {
+                let patch = tool_info
+                    .parameters
+                    .get("patch")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                let files = patch
+                    .lines()
+                    .filter(|line| {
+                        line.starts_with("+++ ")
+                            || line.starts_with("*** Add File:")
+                            || line.starts_with("*** Update File:")
+                            || line.starts_with("*** Delete File:")
+                    })
+                    .count();
+                format!("{} files", files)
+            }
             _ => {
                 // For other tools, try to find a reasonable display parameter
                 tool_info
@@ -162,7 +203,10 @@ impl ToolFormatter {
                     if tool_info.parameters.contains_key("file_text") {
                         // Create operation
                         None // Diff view will be shown separately
-                    } else if tool_info.parameters.contains_key("old_str") {
+                    } else if tool_info.parameters.contains_key("old_str")
+                        || tool_info.parameters.contains_key("edits")
+                        || tool_info.parameters.contains_key("files")
+                    {
                         // Update operation - no message needed, diff view will be shown
                         None
                     } else if tool_info.parameters.contains_key("view_range")
@@ -178,6 +222,34 @@ impl ToolFormatter {
                     }
                 }
             }
+            "read" => {
+                if !result.success {
+                    Some(format!("  ⎿  Error: {}", result.content))
+                } else if let Some(data) = &result.data {
+                    // Text files report the range of lines shown
+                    let first = data["first_line"].as_u64().unwrap_or(1);
+                    let last = data["last_line"].as_u64().unwrap_or(0);
+                    Some(format!(
+                        "  ⎿  Read {} lines",
+                        (last + 1).saturating_sub(first)
+                    ))
+                } else {
+                    // Images, PDFs, notebooks and directories start with a summary line
+                    result
+                        .content
+                        .lines()
+                        .next()
+                        .map(|line| format!("  ⎿  {}", line))
+                }
+            }
+            "apply_patch" => {
+                if result.success {
+                    // Diff view will be shown separately
+                    None
+                } else {
+                    Some(format!("  ⎿  Error: {}", result.content))
+                }
+            }
             "task_done" => {
                 // Extract details from the task_done result content
                 // Format: "Summary: {summary}\n\nDetails:\n{details}"
@@ -224,6 +296,24 @@ impl DiffFormatter {
             return None;
         }
 
+        // multi_edit and batch_edit return a unified diff per file
+        if let Some(files) = result
+            .data
+            .as_ref()
+            .and_then(|data| data.get("files"))
+            .and_then(|files| files.as_array())
+        {
+            let views: Vec<String> = files
+                .iter()
+                .filter_map(|file| {
+                    let path = file.get("path")?.as_str()?;
+                    let diff = file.get("diff")?.as_str()?;
+                    Some(self.create_patch_view(path, diff))
+                })
+                .collect();
+            return (!views.is_empty()).then(|| views.join("\n"));
+        }
+
         let path = tool_info.parameters.get("path").and_then(|v| v.as_str())?;
 
         let file_name = Path::new(path)
@@ -312,6 +402,70 @@ impl DiffFormatter {
         result
     }
 
+    /// Create a diff view from a unified diff, numbering lines from the hunk headers
+    fn create_patch_view(&self, path: &str, diff: &str) -> String {
+        let file_name = Path::new(path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(path);
+
+        let mut result = String::new();
+        result.push_str(&format!("╭{:─<120}╮\n", ""));
+        result.push_str(&format!("│ {:<118} │\n", file_name));
+        result.push_str(&format!("│{:<120}│\n", ""));
+
+        let (mut old_line, mut new_line) = (0, 0);
+        let mut in_hunk = false;
+        for line in diff.lines() {
+            if line.starts_with("@@") {
+                (old_line, new_line) = parse_hunk_header(line).unwrap_or((1, 1));
+                in_hunk = true;
+                let header = self.truncate_line(line);
+                result.push_str(&format!("│   {}{:<107}{} │\n", GRAY, header, RESET));
+                continue;
+            }
+            // Skip the file headers before the first hunk
+            if !in_hunk {
+                continue;
+            }
+
+            match line.chars().next() {
+                Some('-') => {
+                    let content =
+                        self.format_line_with_background_and_prefix(&line[1..], RED_BG, "-");
+                    result.push_str(&format!(
+                        "│   {}{:>3}{} {} │\n",
+                        GRAY, old_line, RESET, content
+                    ));
+                    old_line += 1;
+                }
+                Some('+') => {
+                    let content =
+                        self.format_line_with_background_and_prefix(&line[1..], GREEN_BG, "+");
+                    result.push_str(&format!(
+                        "│   {}{:>3}{} {} │\n",
+                        GRAY, new_line, RESET, content
+                    ));
+                    new_line += 1;
+                }
+                // "\ No newline at end of file"
+                Some('\\') => {}
+                _ => {
+                    let content = self.truncate_line(line.get(1..).unwrap_or(""));
+                    result.push_str(&format!(
+                        "│   {}{:>3}{}    {:<100} │\n",
+                        GRAY, new_line, RESET, content
+                    ));
+                    old_line += 1;
+                    new_line += 1;
+                }
+            }
+        }
+
+        result.push_str(&format!("╰{:─<120}╯", ""));
+        result
+    }
+
     /// Format a line with background color including prefix symbol
     fn format_line_with_background_and_prefix(
         &self,
@@ -334,3 +488,148 @@ impl DiffFormatter {
         }
     }
 }
+
+/// Number of output lines shown under a running tool
+const LIVE_TAIL_LINES: usize = 5;
+
+/// Bytes kept of a line whose end has not arrived yet
+const MAX_PARTIAL_LINE: usize = 4096;
+
+/// The latest output lines of a running tool, shown until it completes
+#[derive(Debug, Default)]
+pub struct LiveTail {
+    lines: VecDeque<String>,
+    /// Start of a line whose end has not arrived yet
+    partial: String,
+}
+
+impl LiveTail {
+    /// Add a chunk of output
+    pub fn push(&mut self, chunk: &str) {
+        self.partial.push_str(chunk);
+        while let Some(end) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=end).collect();
+            if self.lines.len() == LIVE_TAIL_LINES {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(line);
+        }
+
+        // A carriage return redraws the line, e.g. a progress bar. One at the
+        // end may still be followed by a newline, so it is kept.
+        if let Some(start) = self.partial.trim_end_matches('\r').rfind('\r') {
+            self.partial.drain(..=start);
+        }
+        if self.partial.len() > MAX_PARTIAL_LINE {
+            let mut cut = self.partial.len() - MAX_PARTIAL_LINE;
+            while !self.partial.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.partial.drain(..cut);
+        }
+    }
+
+    /// Render the tail as gray lines that fit in `width` columns
+    pub fn render(&self, width: usize) -> Vec<String> {
+        let partial = Some(&self.partial).filter(|partial| !partial.is_empty());
+        let lines: Vec<&String> = self.lines.iter().chain(partial).collect();
+        lines[lines.len().saturating_sub(LIVE_TAIL_LINES)..]
+            .iter()
+            .map(|line| {
+                format!(
+                    "    {}{}{}",
+                    GRAY,
+                    fit_to_width(line, width.saturating_sub(4)),
+                    RESET
+                )
+            })
+            .collect()
+    }
+}
+
+/// Strip control characters and escape sequences from a line of tool output
+/// and cut it to `width` columns
+fn fit_to_width(line: &str, width: usize) -> String {
+    // Progress bars redraw their line after a carriage return
+    let line = line.trim_end_matches(['\n', '\r']);
+    let line = line.rsplit('\r').next().unwrap_or_default();
+
+    let mut fitted = String::new();
+    let mut used = 0;
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            // Skip a CSI sequence such as a color code
+            if chars.next() == Some('[') {
+                for ch in chars.by_ref() {
+                    if ('@'..='~').contains(&ch) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        let ch = if ch == '\t' { ' ' } else { ch };
+        if ch.is_control() {
+            continue;
+        }
+        used += char_width(ch);
+        if used > width {
+            break;
+        }
+        fitted.push(ch);
+    }
+    fitted
+}
+
+/// Parse the old and new start lines from a `@@ -a,b +c,d @@` hunk header
+fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let mut parts = line.split_whitespace().skip(1);
+    let start = |part: &str| part.split(',').next()?.parse().ok();
+    let old = start(parts.next()?.strip_prefix('-')?)?;
+    let new = start(parts.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_tail_keeps_the_latest_lines() {
+        let mut tail = LiveTail::default();
+        tail.push("one\ntwo\nthree\nfour\n");
+        tail.push("five\nsix\nsev");
+        tail.push("en");
+
+        let lines = tail.render(80);
+        assert_eq!(lines.len(), LIVE_TAIL_LINES);
+        assert!(lines[0].contains("three"));
+        assert!(lines[4].contains("seven"));
+    }
+
+    #[test]
+    fn test_live_tail_bounds_the_unfinished_line() {
+        let mut tail = LiveTail::default();
+        for percent in 0..=100 {
+            tail.push(&format!("{}%\r", percent));
+        }
+        assert_eq!(tail.partial, "100%\r");
+        tail.push("\ndone");
+        assert!(tail.render(80)[0].contains("100%"));
+
+        tail.push(&"x".repeat(MAX_PARTIAL_LINE * 3));
+        assert_eq!(tail.partial.len(), MAX_PARTIAL_LINE);
+    }
+
+    #[test]
+    fn test_fit_to_width() {
+        assert_eq!(
+            fit_to_width("\x1b[1;32mCompiling\x1b[0m foo\n", 80),
+            "Compiling foo"
+        );
+        assert_eq!(fit_to_width("10%\r50%\r100%\r\n", 80), "100%");
+        assert_eq!(fit_to_width("abcdef", 4), "abcd");
+        assert_eq!(fit_to_width("构建中", 4), "构建");
+    }
+}
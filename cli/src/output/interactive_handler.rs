@@ -2,7 +2,8 @@
 //! Delegates all output behavior to CliOutputHandler while maintaining UI integration
 
 use super::cli_handler::{CliOutputConfig, CliOutputHandler};
-use super::formatters::{DiffFormatter, ToolFormatter};
+use super::formatters::{DiffFormatter, LiveTail, ToolFormatter};
+use crate::interactive::text_utils::get_terminal_width;
 use async_trait::async_trait;
 use coro_core::output::{AgentEvent, AgentOutput, MessageLevel};
 use coro_core::tools::output_formatter::{GRAY, RESET};
@@ -76,6 +77,8 @@ pub struct InteractiveOutputHandler {
     diff_formatter: DiffFormatter,
     /// Track active tool executions
     active_tools: Arc<Mutex<HashMap<String, coro_core::output::ToolExecutionInfo>>>,
+    /// Output tails of running tools
+    live_tails: Arc<Mutex<HashMap<String, LiveTail>>>,
 }
 
 impl InteractiveOutputHandler {
@@ -96,6 +99,7 @@ impl InteractiveOutputHandler {
             tool_formatter: ToolFormatter::new(),
             diff_formatter: DiffFormatter::new(),
             active_tools: Arc::new(Mutex::new(HashMap::new())),
+            live_tails: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -145,6 +149,32 @@ impl AgentOutput for InteractiveOutputHandler {
                     active_tools.insert(tool_info.execution_id.clone(), tool_info);
                 }
 
+                AgentEvent::ToolExecutionUpdated {
+                    tool_info,
+                    output_chunk,
+                } => {
+                    if is_silent_tool(&tool_info.tool_name) {
+                        return Ok(());
+                    }
+
+                    let mut live_tails = self.live_tails.lock().await;
+                    let tail = live_tails
+                        .entry(tool_info.execution_id.clone())
+                        .or_default();
+                    tail.push(&output_chunk);
+
+                    // The tail shares the status message so completion replaces both
+                    let mut status_msg = self.tool_formatter.format_tool_status(&tool_info);
+                    for line in tail.render(get_terminal_width()) {
+                        status_msg.push('\n');
+                        status_msg.push_str(&line);
+                    }
+                    let _ = ui_sender.send(InteractiveMessage::ToolStatus {
+                        execution_id: tool_info.execution_id,
+                        status: status_msg,
+                    });
+                }
+
                 AgentEvent::ToolExecutionCompleted { tool_info } => {
                     // !!! IMPORTANT
                     // These tools, such as “thinking,” are already handled in `Agent::Thinking`.
@@ -158,6 +188,7 @@ impl AgentOutput for InteractiveOutputHandler {
                     // Remove from active tools tracking
                     let mut active_tools = self.active_tools.lock().await;
                     active_tools.remove(&tool_info.execution_id);
+                    self.live_tails.lock().await.remove(&tool_info.execution_id);
 
                     // Use same format as CLI mode
                     let status_msg = self.tool_formatter.format_tool_status(&tool_info);
@@ -175,7 +206,10 @@ impl AgentOutput for InteractiveOutputHandler {
                     }
 
                     // Show diff for edit tools
-                    if tool_info.tool_name == "str_replace_based_edit_tool" {
+                    if matches!(
+                        tool_info.tool_name.as_str(),
+                        "str_replace_based_edit_tool" | "apply_patch"
+                    ) {
                         if let Some(diff_display) =
                             self.diff_formatter.format_edit_result(&tool_info)
                         {
@@ -0,0 +1,262 @@
+//! Headless JSON output handler for run mode
+//!
+//! Serializes agent events as newline-delimited JSON so that CI jobs and
+//! wrapper scripts can consume coro output without parsing terminal text.
+//! Every record carries a `schema_version` field; bump it whenever the shape
+//! of a record changes in a way that is not purely additive.
+
+use async_trait::async_trait;
+use coro_core::agent::AgentExecution;
+use coro_core::output::{AgentEvent, AgentOutput, TokenUsage};
+use serde::Serialize;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Version of the JSON record schema emitted by [`JsonOutputHandler`]
+pub const STREAM_SCHEMA_VERSION: u32 = 1;
+
+/// Output format for run mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-oriented terminal output
+    #[default]
+    Text,
+    /// A single JSON result object printed when the task finishes
+    Json,
+    /// One JSON record per agent event, followed by a final result record
+    StreamJson,
+}
+
+impl OutputFormat {
+    /// Whether this format writes machine-readable JSON to stdout
+    pub fn is_json(&self) -> bool {
+        !matches!(self, OutputFormat::Text)
+    }
+}
+
+/// A single line of headless output
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamRecord<'a> {
+    /// An agent event, emitted as it happens in `stream-json` mode
+    Event {
+        schema_version: u32,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        event: &'a AgentEvent,
+    },
+    /// The final result of the run, always the last record
+    Result {
+        schema_version: u32,
+        success: bool,
+        exit_code: i32,
+        execution: &'a AgentExecution,
+        token_usage: &'a TokenUsage,
+        /// All events of the run (only populated in `json` mode)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        events: Option<&'a [serde_json::Value]>,
+    },
+}
+
+struct JsonOutputState {
+    writer: Box<dyn Write + Send>,
+    token_usage: TokenUsage,
+    buffered_events: Vec<serde_json::Value>,
+}
+
+/// Output handler that writes agent events as JSON
+///
+/// The handler is cheap to clone; clones share the same writer and state so
+/// the caller can keep one to write the final result after the agent (which
+/// owns the other) has finished.
+#[derive(Clone)]
+pub struct JsonOutputHandler {
+    format: OutputFormat,
+    state: Arc<Mutex<JsonOutputState>>,
+}
+
+impl JsonOutputHandler {
+    /// Create a handler writing to the given writer
+    pub fn new(format: OutputFormat, writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            format,
+            state: Arc::new(Mutex::new(JsonOutputState {
+                writer,
+                token_usage: TokenUsage::default(),
+                buffered_events: Vec::new(),
+            })),
+        }
+    }
+
+    /// Create a handler writing to stdout
+    pub fn stdout(format: OutputFormat) -> Self {
+        Self::new(format, Box::new(std::io::stdout()))
+    }
+
+    /// Write the final result record and return the process exit code
+    pub fn finish(&self, execution: &AgentExecution) -> std::io::Result<i32> {
+        let exit_code = if execution.success { 0 } else { 1 };
+        let mut state = self.state.lock().expect("json output state poisoned");
+        let state = &mut *state;
+
+        let events = match self.format {
+            OutputFormat::Json => Some(state.buffered_events.as_slice()),
+            _ => None,
+        };
+        let record = StreamRecord::Result {
+            schema_version: STREAM_SCHEMA_VERSION,
+            success: execution.success,
+            exit_code,
+            execution,
+            token_usage: &state.token_usage,
+            events,
+        };
+
+        let line = serde_json::to_string(&record)?;
+        writeln!(state.writer, "{}", line)?;
+        state.writer.flush()?;
+
+        Ok(exit_code)
+    }
+}
+
+#[async_trait]
+impl AgentOutput for JsonOutputHandler {
+    async fn emit_event(
+        &self,
+        event: AgentEvent,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut state = self.state.lock().expect("json output state poisoned");
+
+        // Keep the latest token usage for the final result
+        match &event {
+            AgentEvent::TokenUsageUpdated { token_usage } => {
+                state.token_usage = token_usage.clone();
+            }
+            AgentEvent::ExecutionCompleted { context, .. } => {
+                state.token_usage = context.token_usage.clone();
+            }
+            _ => {}
+        }
+
+        let record = StreamRecord::Event {
+            schema_version: STREAM_SCHEMA_VERSION,
+            timestamp: chrono::Utc::now(),
+            event: &event,
+        };
+
+        match self.format {
+            OutputFormat::StreamJson => {
+                let line = serde_json::to_string(&record)?;
+                writeln!(state.writer, "{}", line)?;
+                state.writer.flush()?;
+            }
+            OutputFormat::Json => {
+                let value = serde_json::to_value(&record)?;
+                state.buffered_events.push(value);
+            }
+            OutputFormat::Text => {}
+        }
+
+        Ok(())
+    }
+
+    fn supports_realtime_updates(&self) -> bool {
+        self.format == OutputFormat::StreamJson
+    }
+
+    async fn flush(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut state = self.state.lock().expect("json output state poisoned");
+        state.writer.flush().map_err(|e| e.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Writer that appends into a shared buffer so tests can inspect output
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn lines(&self) -> Vec<serde_json::Value> {
+            let bytes = self.0.lock().unwrap().clone();
+            String::from_utf8(bytes)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    fn token_event(total: u32) -> AgentEvent {
+        AgentEvent::TokenUsageUpdated {
+            token_usage: TokenUsage {
+                input_tokens: total / 2,
+                output_tokens: total - total / 2,
+                total_tokens: total,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_json_writes_one_line_per_event_and_result() {
+        let buffer = SharedBuffer::default();
+        let handler = JsonOutputHandler::new(OutputFormat::StreamJson, Box::new(buffer.clone()));
+
+        handler
+            .emit_event(AgentEvent::Message {
+                level: coro_core::output::MessageLevel::Normal,
+                content: "hello".to_string(),
+                metadata: HashMap::new(),
+            })
+            .await
+            .unwrap();
+        handler.emit_event(token_event(42)).await.unwrap();
+
+        let exit_code = handler
+            .finish(&AgentExecution::success("done".to_string(), 2, 10))
+            .unwrap();
+        assert_eq!(exit_code, 0);
+
+        let lines = buffer.lines();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["type"], "event");
+        assert_eq!(lines[0]["schema_version"], STREAM_SCHEMA_VERSION);
+        assert_eq!(lines[2]["type"], "result");
+        assert_eq!(lines[2]["token_usage"]["total_tokens"], 42);
+        assert_eq!(lines[2]["execution"]["steps_executed"], 2);
+        assert!(lines[2].get("events").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_json_buffers_events_into_single_result() {
+        let buffer = SharedBuffer::default();
+        let handler = JsonOutputHandler::new(OutputFormat::Json, Box::new(buffer.clone()));
+
+        handler.emit_event(token_event(7)).await.unwrap();
+
+        let exit_code = handler
+            .finish(&AgentExecution::failure("boom".to_string(), 1, 5))
+            .unwrap();
+        assert_eq!(exit_code, 1);
+
+        let lines = buffer.lines();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["success"], false);
+        assert_eq!(lines[0]["exit_code"], 1);
+        assert_eq!(lines[0]["events"].as_array().unwrap().len(), 1);
+    }
+}
@@ -6,3 +6,4 @@
 pub mod cli_handler;
 pub mod formatters;
 pub mod interactive_handler;
+pub mod json_handler;
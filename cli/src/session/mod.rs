@@ -0,0 +1,7 @@
+//! Persisted interactive sessions
+
+pub mod recorder;
+pub mod store;
+
+pub use recorder::SessionRecorder;
+pub use store::{Session, SessionStore};
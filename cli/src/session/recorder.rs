@@ -0,0 +1,79 @@
+//! Session recording for the interactive agent
+
+use super::store::{Session, SessionStore};
+use anyhow::Result;
+use coro_core::agent::AgentCore;
+use coro_core::llm::LlmMessage;
+use coro_core::output::TokenUsage;
+use coro_core::ResolvedLlmConfig;
+
+/// Tracks the session of an interactive agent and persists it after each task
+pub struct SessionRecorder {
+    store: SessionStore,
+    session: Session,
+    /// LLM configuration the session currently runs with
+    llm_config: ResolvedLlmConfig,
+    /// Token usage recorded before this process resumed the session
+    base_usage: TokenUsage,
+}
+
+impl SessionRecorder {
+    /// Create a recorder for a new, resumed or forked session
+    pub fn new(store: SessionStore, session: Session, llm_config: ResolvedLlmConfig) -> Self {
+        let base_usage = session.token_usage.clone();
+        Self {
+            store,
+            session,
+            llm_config,
+            base_usage,
+        }
+    }
+
+    /// The session being recorded
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Conversation history to seed a newly created agent with
+    pub fn conversation_history(&self) -> Vec<LlmMessage> {
+        self.session.conversation_history.clone()
+    }
+
+    /// LLM configuration the session currently runs with
+    pub fn llm_config(&self) -> &ResolvedLlmConfig {
+        &self.llm_config
+    }
+
+    /// Switch the LLM configuration, e.g. after picking another model
+    pub fn set_llm_config(&mut self, llm_config: ResolvedLlmConfig) {
+        self.llm_config = llm_config;
+    }
+
+    /// Switch to a fresh session, leaving the current one on disk
+    pub fn start_new(&mut self) {
+        self.session = Session::new(self.session.project_path.clone(), &self.llm_config);
+        self.base_usage = TokenUsage::default();
+    }
+
+    /// Token usage of the whole session, including the running agent
+    pub fn token_usage(&self, agent: Option<&AgentCore>) -> TokenUsage {
+        let agent_usage = agent.map(AgentCore::token_usage).unwrap_or_default();
+        TokenUsage {
+            input_tokens: self.base_usage.input_tokens + agent_usage.input_tokens,
+            output_tokens: self.base_usage.output_tokens + agent_usage.output_tokens,
+            total_tokens: self.base_usage.total_tokens + agent_usage.total_tokens,
+        }
+    }
+
+    /// Copy the agent's current state into the session and save it
+    pub fn record(&mut self, agent: &AgentCore) -> Result<()> {
+        let token_usage = self.token_usage(Some(agent));
+
+        self.session.update(
+            agent.conversation_history().to_vec(),
+            token_usage,
+            &self.llm_config,
+        );
+        self.store.save(&self.session)
+    }
+}
@@ -0,0 +1,448 @@
+//! Session persistence for interactive mode
+//!
+//! Each session is stored as a single JSON file named `<id>.json` in the
+//! sessions directory (`~/.local/share/coro/sessions/` on Linux). A session
+//! holds the agent's conversation history together with the project path,
+//! a snapshot of the LLM configuration and the accumulated token usage, so it
+//! can be resumed or forked later.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use coro_core::llm::{LlmMessage, MessageContent, MessageRole};
+use coro_core::output::TokenUsage;
+use coro_core::{ModelParams, Protocol, ResolvedLlmConfig};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Maximum length of a session title derived from the first user message
+const MAX_TITLE_LEN: usize = 60;
+
+/// LLM configuration recorded with a session
+///
+/// Credentials (API key and headers) are never persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionConfigSnapshot {
+    pub protocol: Protocol,
+    pub base_url: String,
+    pub model: String,
+    #[serde(default)]
+    pub params: ModelParams,
+}
+
+impl From<&ResolvedLlmConfig> for SessionConfigSnapshot {
+    fn from(config: &ResolvedLlmConfig) -> Self {
+        Self {
+            protocol: config.protocol.clone(),
+            base_url: config.base_url.clone(),
+            model: config.model.clone(),
+            params: config.params.clone(),
+        }
+    }
+}
+
+/// A persisted interactive session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Unique session identifier
+    pub id: String,
+    /// Session this one was forked from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Project directory the session was started in
+    pub project_path: PathBuf,
+    /// LLM configuration the session was last run with
+    pub config: SessionConfigSnapshot,
+    /// Token usage accumulated over the whole session
+    #[serde(default)]
+    pub token_usage: TokenUsage,
+    /// Agent conversation history
+    #[serde(default)]
+    pub conversation_history: Vec<LlmMessage>,
+}
+
+impl Session {
+    /// Create a new empty session
+    pub fn new(project_path: PathBuf, llm_config: &ResolvedLlmConfig) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            parent_id: None,
+            created_at: now,
+            updated_at: now,
+            project_path,
+            config: SessionConfigSnapshot::from(llm_config),
+            token_usage: TokenUsage::default(),
+            conversation_history: Vec::new(),
+        }
+    }
+
+    /// Create a new session that starts from a copy of this session's state
+    pub fn fork(&self) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            parent_id: Some(self.id.clone()),
+            created_at: now,
+            updated_at: now,
+            ..self.clone()
+        }
+    }
+
+    /// Short identifier used for display
+    pub fn short_id(&self) -> &str {
+        self.id.get(..8).unwrap_or(&self.id)
+    }
+
+    /// Title derived from the first user message
+    pub fn title(&self) -> String {
+        let first_user_message = self
+            .conversation_history
+            .iter()
+            .filter(|message| message.role == MessageRole::User)
+            .find_map(|message| match &message.content {
+                MessageContent::Text(text) => Some(text.as_str()),
+                MessageContent::MultiModal(_) => None,
+            });
+
+        let Some(text) = first_user_message else {
+            return "(empty session)".to_string();
+        };
+
+        let line = text
+            .lines()
+            .map(|line| line.trim_start_matches("[Task]:").trim())
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+
+        if line.chars().count() > MAX_TITLE_LEN {
+            let truncated: String = line.chars().take(MAX_TITLE_LEN).collect();
+            format!("{}…", truncated)
+        } else {
+            line.to_string()
+        }
+    }
+
+    /// Number of user and assistant messages in the conversation
+    pub fn message_count(&self) -> usize {
+        self.conversation_history
+            .iter()
+            .filter(|message| matches!(message.role, MessageRole::User | MessageRole::Assistant))
+            .count()
+    }
+
+    /// Record the latest agent state in the session
+    pub fn update(
+        &mut self,
+        conversation_history: Vec<LlmMessage>,
+        token_usage: TokenUsage,
+        llm_config: &ResolvedLlmConfig,
+    ) {
+        self.conversation_history = conversation_history;
+        self.token_usage = token_usage;
+        self.config = SessionConfigSnapshot::from(llm_config);
+        self.updated_at = Utc::now();
+    }
+}
+
+/// File-based session store
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    /// Create a store backed by the given directory
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Create a store in the default sessions directory
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(Self::default_dir()?))
+    }
+
+    /// Get the default sessions directory
+    pub fn default_dir() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_dir().ok_or_else(|| anyhow!("Could not determine data directory"))?;
+        Ok(data_dir.join("coro").join("sessions"))
+    }
+
+    /// Path of a session file; ids are uuids, so anything else is rejected
+    /// before it can name a file outside the sessions directory
+    fn session_path(&self, id: &str) -> Result<PathBuf> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+            bail!("Invalid session id '{}'", id);
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    /// Write a session to disk, replacing any previous version
+    ///
+    /// Sessions hold whole conversations and tool output, so the directory
+    /// and the files are only accessible by the user.
+    pub fn save(&self, session: &Session) -> Result<()> {
+        let path = self.session_path(&session.id)?;
+        create_private_dir(&self.dir).with_context(|| {
+            format!(
+                "Failed to create sessions directory: {}",
+                self.dir.display()
+            )
+        })?;
+        let content = serde_json::to_string_pretty(session)?;
+
+        // Write to a temporary file first so an interrupted save never
+        // leaves a truncated session behind
+        let tmp_path = path.with_extension("json.tmp");
+        write_private_file(&tmp_path, content.as_bytes())
+            .with_context(|| format!("Failed to write session: {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Failed to write session: {}", path.display()))?;
+
+        Ok(())
+    }
+
+    /// Load a session by its full id or a unique id prefix
+    pub fn load(&self, id: &str) -> Result<Session> {
+        let path = self.session_path(id)?;
+        if path.is_file() {
+            return Self::read_session(&path);
+        }
+
+        let matches: Vec<Session> = self
+            .list()?
+            .into_iter()
+            .filter(|session| session.id.starts_with(id))
+            .collect();
+
+        match matches.len() {
+            0 => bail!("No session found with id '{}'", id),
+            1 => Ok(matches.into_iter().next().unwrap()),
+            n => bail!("Session id '{}' is ambiguous ({} sessions match)", id, n),
+        }
+    }
+
+    /// List all sessions, most recently updated first
+    ///
+    /// Files that cannot be parsed are skipped.
+    pub fn list(&self) -> Result<Vec<Session>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut sessions = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read sessions directory: {}", self.dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            match Self::read_session(&path) {
+                Ok(session) => sessions.push(session),
+                Err(e) => tracing::warn!("Skipping unreadable session {}: {}", path.display(), e),
+            }
+        }
+
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+        Ok(sessions)
+    }
+
+    /// List the sessions started in the given project directory
+    pub fn list_for_project(&self, project_path: &Path) -> Result<Vec<Session>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|session| session.project_path == project_path)
+            .collect())
+    }
+
+    /// Get the most recently updated session for the given project directory
+    pub fn latest_for_project(&self, project_path: &Path) -> Result<Option<Session>> {
+        Ok(self.list_for_project(project_path)?.into_iter().next())
+    }
+
+    /// Fork a stored session into a new, persisted session
+    pub fn fork(&self, id: &str) -> Result<Session> {
+        let forked = self.load(id)?.fork();
+        self.save(&forked)?;
+        Ok(forked)
+    }
+
+    fn read_session(path: &Path) -> Result<Session> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read session: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse session: {}", path.display()))
+    }
+}
+
+/// Create `dir` with mode 0700 on unix
+pub(crate) fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+    }
+    #[cfg(not(unix))]
+    {
+        std::fs::create_dir_all(dir)
+    }
+}
+
+/// Write a file with mode 0600 on unix
+pub(crate) fn write_private_file(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_config() -> ResolvedLlmConfig {
+        ResolvedLlmConfig::new(
+            Protocol::OpenAICompat,
+            "https://api.openai.com/v1".to_string(),
+            "secret-key".to_string(),
+            "gpt-4o".to_string(),
+        )
+    }
+
+    fn test_session(project: &str, task: &str) -> Session {
+        let mut session = Session::new(PathBuf::from(project), &test_config());
+        session.conversation_history = vec![
+            LlmMessage::system("system prompt"),
+            LlmMessage::user(format!("[Task]: {}", task)),
+            LlmMessage::assistant("done"),
+        ];
+        session
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SessionStore::new(temp_dir.path());
+
+        let session = test_session("/project", "fix the build");
+        store.save(&session).unwrap();
+
+        let loaded = store.load(&session.id).unwrap();
+        assert_eq!(loaded.id, session.id);
+        assert_eq!(loaded.project_path, PathBuf::from("/project"));
+        assert_eq!(loaded.conversation_history.len(), 3);
+        assert_eq!(loaded.title(), "fix the build");
+        assert_eq!(loaded.message_count(), 2);
+
+        // Credentials must never be written to disk
+        let raw =
+            std::fs::read_to_string(temp_dir.path().join(format!("{}.json", session.id))).unwrap();
+        assert!(!raw.contains("secret-key"));
+    }
+
+    #[test]
+    fn test_load_by_prefix() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SessionStore::new(temp_dir.path());
+
+        let session = test_session("/project", "task");
+        store.save(&session).unwrap();
+
+        let loaded = store.load(session.short_id()).unwrap();
+        assert_eq!(loaded.id, session.id);
+        assert!(store.load("does-not-exist").is_err());
+    }
+
+    #[test]
+    fn test_ids_cannot_leave_the_sessions_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SessionStore::new(temp_dir.path().join("sessions"));
+
+        let mut session = test_session("/project", "task");
+        store.save(&session).unwrap();
+        for id in ["../x", "/etc/passwd", "", "a/b"] {
+            assert!(store.load(id).is_err());
+        }
+
+        // The id read from a session file is not trusted either
+        session.id = "../escaped".to_string();
+        assert!(store.save(&session).is_err());
+        assert!(!temp_dir.path().join("escaped.json").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sessions_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("sessions");
+        let store = SessionStore::new(&dir);
+        let session = test_session("/project", "task");
+        store.save(&session).unwrap();
+
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&dir), 0o700);
+        assert_eq!(mode(&dir.join(format!("{}.json", session.id))), 0o600);
+    }
+
+    #[test]
+    fn test_latest_for_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SessionStore::new(temp_dir.path());
+
+        let mut older = test_session("/project", "older");
+        older.updated_at = Utc::now() - chrono::Duration::hours(1);
+        let newer = test_session("/project", "newer");
+        let other = test_session("/elsewhere", "other");
+
+        store.save(&older).unwrap();
+        store.save(&newer).unwrap();
+        store.save(&other).unwrap();
+
+        let latest = store
+            .latest_for_project(Path::new("/project"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.id, newer.id);
+        assert_eq!(store.list().unwrap().len(), 3);
+        assert!(store
+            .latest_for_project(Path::new("/missing"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_fork_copies_history_with_new_id() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SessionStore::new(temp_dir.path());
+
+        let session = test_session("/project", "task");
+        store.save(&session).unwrap();
+
+        let forked = store.fork(&session.id).unwrap();
+        assert_ne!(forked.id, session.id);
+        assert_eq!(forked.parent_id.as_deref(), Some(session.id.as_str()));
+        assert_eq!(
+            forked.conversation_history.len(),
+            session.conversation_history.len()
+        );
+        assert_eq!(store.list().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,1408 @@
+//! Tool applying patches in unified diff or `*** Begin Patch` format
+//!
+//! Models trained on git output write unified diffs, others write the
+//! envelope format below. Both are parsed into the same [`FileChange`]s:
+//!
+//! ```text
+//! *** Begin Patch
+//! *** Update File: src/lib.rs
+//! *** Move to: src/core.rs
+//! @@ fn main() {
+//!      let x = 1;
+//! -    println!("{}", x);
+//! +    println!("x = {}", x);
+//! *** Add File: notes.txt
+//! +hello
+//! *** Delete File: old.txt
+//! *** End Patch
+//! ```
+//!
+//! Hunks are located by their context rather than their line numbers, first
+//! exactly and then with growing tolerance for whitespace and trimmed context.
+//! Every file is patched in memory before any is written, so a failing hunk
+//! leaves all files unchanged.
+
+use crate::tools::edit::{ReadPolicy, ReadTracker};
+use async_trait::async_trait;
+use coro_core::error::Result;
+use coro_core::tools::utils::{maybe_truncate, resolve_workspace_path};
+use coro_core::tools::{Tool, ToolCall, ToolExample, ToolFactory, ToolResult};
+use serde_json::json;
+use similar::TextDiff;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Context lines a hunk may lose at each end when it does not match
+const MAX_FUZZ: usize = 2;
+
+/// Expected lines quoted when a hunk cannot be located
+const MAX_QUOTED_LINES: usize = 8;
+
+/// Lines of context around each hunk of the diffs in results
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// A line of a hunk
+#[derive(Debug, Clone, PartialEq)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+/// A change to a contiguous block of lines
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Hunk {
+    /// Text after `@@`, a line to search for in the envelope format
+    header: String,
+    /// First line of the old side, from unified diff headers
+    old_start: Option<usize>,
+    lines: Vec<HunkLine>,
+    /// The hunk applies to the end of the file
+    end_of_file: bool,
+    /// Whether the new side ends with a newline, when the patch says so
+    new_eof_newline: Option<bool>,
+}
+
+impl Hunk {
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+
+    /// Copy of the hunk without `leading` and `trailing` context lines
+    fn trim_context(&self, leading: usize, trailing: usize) -> Option<Hunk> {
+        let lines = &self.lines;
+        let is_context = |line: &HunkLine| matches!(line, HunkLine::Context(_));
+        if lines.len() < leading + trailing
+            || !lines[..leading].iter().all(is_context)
+            || !lines[lines.len() - trailing..].iter().all(is_context)
+        {
+            return None;
+        }
+        let trimmed = Hunk {
+            old_start: self.old_start.map(|start| start + leading),
+            lines: lines[leading..lines.len() - trailing].to_vec(),
+            end_of_file: self.end_of_file && trailing == 0,
+            ..self.clone()
+        };
+        // Without any old line left the hunk would be placed blindly
+        if trimmed.old_lines().is_empty() && !self.old_lines().is_empty() {
+            return None;
+        }
+        Some(trimmed)
+    }
+
+    /// Label used in failure reports
+    fn describe(&self, number: usize) -> String {
+        if self.header.is_empty() {
+            format!("hunk {}", number)
+        } else {
+            format!("hunk {} (@@ {})", number, self.header)
+        }
+    }
+}
+
+/// A change to one file of a patch
+#[derive(Debug, Clone, PartialEq)]
+enum FileChange {
+    Add {
+        path: String,
+        content: String,
+    },
+    Delete {
+        path: String,
+    },
+    Update {
+        path: String,
+        move_to: Option<String>,
+        hunks: Vec<Hunk>,
+    },
+}
+
+impl FileChange {
+    fn path(&self) -> &str {
+        match self {
+            FileChange::Add { path, .. }
+            | FileChange::Delete { path }
+            | FileChange::Update { path, .. } => path,
+        }
+    }
+}
+
+/// A file patched in memory, not yet written
+#[derive(Debug)]
+struct PatchedFile {
+    path: PathBuf,
+    /// Destination of a moved file
+    move_to: Option<PathBuf>,
+    /// Content before the patch, `None` for added files
+    original: Option<String>,
+    /// Content after the patch, `None` for deleted files
+    updated: Option<String>,
+}
+
+impl PatchedFile {
+    fn action(&self) -> &'static str {
+        match (&self.original, &self.updated) {
+            (None, _) => "add",
+            (_, None) => "delete",
+            _ if self.move_to.is_some() => "move",
+            _ => "update",
+        }
+    }
+}
+
+/// Tool applying unified diffs and `*** Begin Patch` patches
+pub struct ApplyPatchTool {
+    read_policy: ReadPolicy,
+    tracker: ReadTracker,
+    /// Workspace files can be patched in, the current directory by default
+    root: Option<PathBuf>,
+}
+
+impl ApplyPatchTool {
+    pub fn new() -> Self {
+        Self::with_options(ReadPolicy::default(), ReadTracker::default())
+    }
+
+    /// Create a tool that refuses to patch files changed on disk since they
+    /// were viewed, unless `read_policy` is off, and records the files it
+    /// writes in `tracker`
+    pub fn with_options(read_policy: ReadPolicy, tracker: ReadTracker) -> Self {
+        Self {
+            read_policy,
+            tracker,
+            root: None,
+        }
+    }
+
+    /// Only patch files inside `root` instead of the current directory
+    pub fn with_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Check that the patched files have not changed since they were viewed
+    ///
+    /// Hunks are located by their context, so files that were never viewed
+    /// can be patched.
+    fn check_files(&self, files: &[PatchedFile]) -> std::result::Result<(), Vec<String>> {
+        let policy = match self.read_policy {
+            ReadPolicy::Off => ReadPolicy::Off,
+            _ => ReadPolicy::Modified,
+        };
+        let errors: Vec<String> = files
+            .iter()
+            .filter(|file| file.original.is_some())
+            .filter_map(|file| self.tracker.check(&file.path, policy).err())
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Remember the written files, so they can be edited without viewing
+    /// them again
+    fn record_files(&self, files: &[PatchedFile]) {
+        for file in files {
+            let target = file.move_to.as_ref().unwrap_or(&file.path);
+            if target != &file.path || file.updated.is_none() {
+                self.tracker.forget(&file.path);
+            }
+            if let Some(updated) = &file.updated {
+                self.tracker.record(target, updated);
+            }
+        }
+    }
+}
+
+impl Default for ApplyPatchTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for ApplyPatchTool {
+    fn name(&self) -> &str {
+        "apply_patch"
+    }
+
+    fn description(&self) -> &str {
+        "Apply a patch that adds, updates, deletes or moves files.\n\
+         * Accepts unified diffs (`--- a/file`, `+++ b/file`, `@@ -1,3 +1,4 @@`), as produced by `git diff`\n\
+         * Also accepts the envelope format:\n\
+         \x20 *** Begin Patch\n\
+         \x20 *** Update File: src/lib.rs\n\
+         \x20 *** Move to: src/core.rs (optional)\n\
+         \x20 @@ fn main() { (optional line near the change)\n\
+         \x20  context line\n\
+         \x20 -removed line\n\
+         \x20 +added line\n\
+         \x20 *** Add File: notes.txt (every line starts with `+`)\n\
+         \x20 *** Delete File: old.txt\n\
+         \x20 *** End Patch\n\
+         * Hunks are found by their context lines, so include about 3 unchanged lines around each change; line numbers are only hints\n\
+         * Paths are relative to `workdir` (default: the workspace root) or absolute inside the workspace\n\
+         * Every hunk is checked before anything is written. If any hunk fails, no file is changed and each failing hunk is reported"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "patch": {
+                    "type": "string",
+                    "description": "The patch, as a unified diff or in the `*** Begin Patch` format."
+                },
+                "workdir": {
+                    "type": "string",
+                    "description": "Directory inside the workspace the patch paths are relative to (default: the workspace root). Files outside the workspace cannot be patched."
+                }
+            },
+            "required": ["patch"]
+        })
+    }
+
+    async fn execute(&self, call: ToolCall) -> Result<ToolResult> {
+        let patch: String = call.get_parameter("patch")?;
+        let root = match &self.root {
+            Some(root) => root.clone(),
+            None => std::env::current_dir()
+                .map_err(|e| format!("Cannot get current directory: {}", e))?,
+        };
+        // The workdir only changes how relative paths are resolved, never
+        // which files can be patched
+        let base = match call.get_parameter::<String>("workdir") {
+            Ok(workdir) => match resolve_workspace_path(&root, &workdir) {
+                Ok(base) if base.is_dir() => base,
+                Ok(_) => {
+                    return Ok(ToolResult::error(
+                        &call.id,
+                        &format!("The workdir {} is not a directory", workdir),
+                    ))
+                }
+                Err(e) => return Ok(ToolResult::error(&call.id, &e.to_string())),
+            },
+            Err(_) => root.clone(),
+        };
+
+        let changes = match parse_patch(&patch) {
+            Ok(changes) => changes,
+            Err(e) => {
+                return Ok(ToolResult::error(
+                    &call.id,
+                    &format!("Invalid patch: {}", e),
+                ))
+            }
+        };
+
+        let files = match prepare_changes(&root, &base, &changes)
+            .and_then(|files| self.check_files(&files).map(|_| files))
+        {
+            Ok(files) => files,
+            Err(errors) => {
+                return Ok(ToolResult::error(
+                    &call.id,
+                    &format!(
+                        "No files were changed. The patch failed to apply:\n{}",
+                        errors.join("\n\n")
+                    ),
+                ))
+            }
+        };
+
+        if let Err(e) = write_files(&files) {
+            return Ok(ToolResult::error(
+                &call.id,
+                &format!("No files were changed. {}", e),
+            ));
+        }
+
+        self.record_files(&files);
+        Ok(patch_result(&call.id, &root, &files))
+    }
+
+    fn examples(&self) -> Vec<ToolExample> {
+        vec![
+            ToolExample {
+                description: "Update a file with a unified diff".to_string(),
+                parameters: json!({
+                    "patch": "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    println!(\"Hello\");\n+    println!(\"Hello, world!\");\n }\n"
+                }),
+                expected_result: "Patch applied with a diff of the changes".to_string(),
+            },
+            ToolExample {
+                description: "Add one file and delete another".to_string(),
+                parameters: json!({
+                    "patch": "*** Begin Patch\n*** Add File: docs/usage.md\n+# Usage\n*** Delete File: USAGE.txt\n*** End Patch"
+                }),
+                expected_result: "One file added and one deleted".to_string(),
+            },
+        ]
+    }
+}
+
+/// Parse a patch in either supported format
+fn parse_patch(patch: &str) -> std::result::Result<Vec<FileChange>, String> {
+    let mut lines: Vec<&str> = patch.lines().collect();
+    // Patches are often wrapped in a markdown code block
+    if lines.first().is_some_and(|line| line.starts_with("```")) {
+        lines.remove(0);
+        if lines.last().is_some_and(|line| line.starts_with("```")) {
+            lines.pop();
+        }
+    }
+
+    let changes = match lines
+        .iter()
+        .position(|line| line.trim() == "*** Begin Patch")
+    {
+        Some(start) => parse_envelope(&lines, start + 1)?,
+        None => parse_unified(&lines)?,
+    };
+    if changes.is_empty() {
+        return Err("no file changes found".to_string());
+    }
+    Ok(changes)
+}
+
+/// Parse the `*** Begin Patch` format, starting after the begin marker
+fn parse_envelope(lines: &[&str], start: usize) -> std::result::Result<Vec<FileChange>, String> {
+    let mut changes = Vec::new();
+    let mut i = start;
+
+    while i < lines.len() {
+        let line = lines[i].trim();
+        i += 1;
+
+        if line.is_empty() {
+            continue;
+        } else if line == "*** End Patch" {
+            break;
+        } else if let Some(path) = line.strip_prefix("*** Add File:") {
+            let mut content = String::new();
+            while i < lines.len() && !lines[i].starts_with("*** ") {
+                let Some(text) = lines[i].strip_prefix('+') else {
+                    return Err(format!(
+                        "line {}: lines of the added file {} must start with `+`",
+                        i + 1,
+                        path.trim()
+                    ));
+                };
+                content.push_str(text);
+                content.push('\n');
+                i += 1;
+            }
+            changes.push(FileChange::Add {
+                path: path.trim().to_string(),
+                content,
+            });
+        } else if let Some(path) = line.strip_prefix("*** Delete File:") {
+            changes.push(FileChange::Delete {
+                path: path.trim().to_string(),
+            });
+        } else if let Some(path) = line.strip_prefix("*** Update File:") {
+            let mut move_to = None;
+            if let Some(to) = lines
+                .get(i)
+                .and_then(|l| l.trim().strip_prefix("*** Move to:"))
+            {
+                move_to = Some(to.trim().to_string());
+                i += 1;
+            }
+            let (hunks, next) = parse_envelope_hunks(lines, i, path.trim())?;
+            i = next;
+            changes.push(FileChange::Update {
+                path: path.trim().to_string(),
+                move_to,
+                hunks,
+            });
+        } else {
+            return Err(format!(
+                "line {}: expected `*** Add File:`, `*** Update File:`, `*** Delete File:` or `*** End Patch`, found `{}`",
+                i, line
+            ));
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Parse the hunks of an updated file, returning them with the next line index
+fn parse_envelope_hunks(
+    lines: &[&str],
+    mut i: usize,
+    path: &str,
+) -> std::result::Result<(Vec<Hunk>, usize), String> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    // Blank lines only count as context when more hunk lines follow
+    let mut blank_lines = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if line.trim() == "*** End of File" {
+            if let Some(hunk) = hunks.last_mut() {
+                hunk.end_of_file = true;
+            }
+            i += 1;
+            continue;
+        }
+        if line.starts_with("*** ") {
+            break;
+        }
+        i += 1;
+
+        if let Some(header) = line.strip_prefix("@@") {
+            blank_lines = 0;
+            hunks.push(Hunk {
+                header: header.trim().trim_end_matches("@@").trim().to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+
+        let Some(hunk_line) = parse_hunk_line(line) else {
+            return Err(format!(
+                "line {}: hunk lines of {} must start with ` `, `-` or `+`, found `{}`",
+                i, path, line
+            ));
+        };
+        if hunks.is_empty() {
+            hunks.push(Hunk::default());
+        }
+        let hunk = hunks.last_mut().expect("a hunk was just pushed");
+        hunk.lines
+            .extend((0..blank_lines).map(|_| HunkLine::Context(String::new())));
+        blank_lines = 0;
+        hunk.lines.push(hunk_line);
+    }
+
+    Ok((hunks, i))
+}
+
+/// Parse a unified diff with one or more files
+fn parse_unified(lines: &[&str]) -> std::result::Result<Vec<FileChange>, String> {
+    let mut changes = Vec::new();
+    let mut rename: Option<(String, String)> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+
+        if line.starts_with("diff --git ") {
+            // A rename without content changes has no `---`/`+++` headers
+            if let Some((from, to)) = rename.take() {
+                changes.push(rename_change(from, to));
+            }
+        } else if let Some(from) = line.strip_prefix("rename from ") {
+            rename = Some((from.to_string(), String::new()));
+        } else if let Some(to) = line.strip_prefix("rename to ") {
+            if let Some((_, target)) = rename.as_mut() {
+                *target = to.to_string();
+            }
+        } else if line.starts_with("--- ")
+            && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ "))
+        {
+            rename = None;
+            let (old, new) =
+                strip_git_prefixes(header_path(&line[4..]), header_path(&lines[i + 1][4..]));
+            let (hunks, next) = parse_unified_hunks(lines, i + 2)?;
+            i = next;
+            changes.push(match (old, new) {
+                (None, Some(path)) => FileChange::Add {
+                    path,
+                    content: added_content(&hunks),
+                },
+                (Some(path), None) => FileChange::Delete { path },
+                (Some(path), Some(new)) => FileChange::Update {
+                    move_to: (new != path).then_some(new),
+                    path,
+                    hunks,
+                },
+                (None, None) => return Err(format!("line {}: both files are /dev/null", i)),
+            });
+            continue;
+        }
+        i += 1;
+    }
+
+    if let Some((from, to)) = rename {
+        changes.push(rename_change(from, to));
+    }
+    Ok(changes)
+}
+
+fn rename_change(from: String, to: String) -> FileChange {
+    FileChange::Update {
+        path: from,
+        move_to: Some(to),
+        hunks: Vec::new(),
+    }
+}
+
+/// Parse the hunks following a file header, returning them with the next line index
+fn parse_unified_hunks(
+    lines: &[&str],
+    mut i: usize,
+) -> std::result::Result<(Vec<Hunk>, usize), String> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut blank_lines = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let next_file = line.starts_with("diff ")
+            || (line.starts_with("--- ")
+                && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ ")));
+        if next_file {
+            break;
+        }
+
+        if line.starts_with("@@") {
+            blank_lines = 0;
+            hunks.push(Hunk {
+                old_start: parse_hunk_start(line),
+                header: line.trim_start_matches('@').trim().to_string(),
+                ..Default::default()
+            });
+            i += 1;
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            // Text between the file header and the first hunk
+            i += 1;
+            continue;
+        };
+
+        if line.is_empty() {
+            // Editors strip the space of empty context lines
+            blank_lines += 1;
+        } else if line.starts_with('\\') {
+            // "\ No newline at end of file" applies to the line above
+            match hunk.lines.last() {
+                Some(HunkLine::Remove(_)) => hunk.new_eof_newline = Some(true),
+                Some(_) => hunk.new_eof_newline = Some(false),
+                None => {}
+            }
+        } else {
+            // Anything else ends the diff of this file
+            let Some(hunk_line) = parse_hunk_line(line) else {
+                break;
+            };
+            hunk.lines
+                .extend((0..blank_lines).map(|_| HunkLine::Context(String::new())));
+            blank_lines = 0;
+            hunk.lines.push(hunk_line);
+        }
+        i += 1;
+    }
+
+    Ok((hunks, i))
+}
+
+fn parse_hunk_line(line: &str) -> Option<HunkLine> {
+    let text = line.get(1..)?.to_string();
+    match line.as_bytes().first()? {
+        b' ' => Some(HunkLine::Context(text)),
+        b'-' => Some(HunkLine::Remove(text)),
+        b'+' => Some(HunkLine::Add(text)),
+        _ => None,
+    }
+}
+
+/// Start line of the old side of a `@@ -a,b +c,d @@` header
+fn parse_hunk_start(header: &str) -> Option<usize> {
+    let old = header.split_whitespace().nth(1)?.strip_prefix('-')?;
+    old.split(',').next()?.parse().ok()
+}
+
+/// Path of a `---`/`+++` header, `None` for /dev/null
+fn header_path(header: &str) -> Option<String> {
+    // Headers may end with a tab and a timestamp
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    let path = path.trim_matches('"');
+    (path != "/dev/null").then(|| path.to_string())
+}
+
+/// Remove the `a/` and `b/` prefixes git puts on diff paths
+fn strip_git_prefixes(
+    old: Option<String>,
+    new: Option<String>,
+) -> (Option<String>, Option<String>) {
+    let prefixed = old.as_deref().is_none_or(|p| p.starts_with("a/"))
+        && new.as_deref().is_none_or(|p| p.starts_with("b/"))
+        && (old.is_some() || new.is_some());
+    if !prefixed {
+        return (old, new);
+    }
+    let strip = |path: Option<String>| path.map(|p| p[2..].to_string());
+    (strip(old), strip(new))
+}
+
+/// Content of a file added by a unified diff
+fn added_content(hunks: &[Hunk]) -> String {
+    let lines: Vec<&str> = hunks.iter().flat_map(Hunk::new_lines).collect();
+    let mut content = lines.join("\n");
+    let eof_newline = hunks.last().and_then(|h| h.new_eof_newline).unwrap_or(true);
+    if eof_newline && !content.is_empty() {
+        content.push('\n');
+    }
+    content
+}
+
+/// Apply the changes in memory, collecting every failure
+///
+/// Relative paths are resolved against `base`, and every path must be inside
+/// `root`.
+fn prepare_changes(
+    root: &Path,
+    base: &Path,
+    changes: &[FileChange],
+) -> std::result::Result<Vec<PatchedFile>, Vec<String>> {
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen = HashSet::new();
+
+    for change in changes {
+        if !seen.insert(change.path()) {
+            errors.push(format!(
+                "{}: the file is changed more than once, combine its hunks into one change",
+                change.path()
+            ));
+            continue;
+        }
+        match prepare_change(root, base, change) {
+            Ok(file) => files.push(file),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(files)
+    } else {
+        Err(errors)
+    }
+}
+
+fn prepare_change(
+    root: &Path,
+    base: &Path,
+    change: &FileChange,
+) -> std::result::Result<PatchedFile, String> {
+    let resolve = |path: &str| {
+        resolve_workspace_path(root, &base.join(path).to_string_lossy()).map_err(|e| e.to_string())
+    };
+    let label = change.path();
+    let path = resolve(label)?;
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).map_err(|e| format!("{}: cannot read the file: {}", label, e))
+    };
+
+    match change {
+        FileChange::Add { content, .. } => {
+            if path.exists() {
+                return Err(format!(
+                    "{}: the file already exists, use an update instead",
+                    label
+                ));
+            }
+            Ok(PatchedFile {
+                path,
+                move_to: None,
+                original: None,
+                updated: Some(content.clone()),
+            })
+        }
+        FileChange::Delete { .. } => Ok(PatchedFile {
+            original: Some(read(&path)?),
+            path,
+            move_to: None,
+            updated: None,
+        }),
+        FileChange::Update { move_to, hunks, .. } => {
+            let original = read(&path)?;
+            let updated = apply_hunks(&original, hunks)
+                .map_err(|errors| format!("{}:\n{}", label, errors.join("\n")))?;
+            let move_to = match move_to {
+                Some(target) => {
+                    let target = resolve(target)?;
+                    if target != path && target.exists() {
+                        return Err(format!(
+                            "{}: cannot move to {}, the file already exists",
+                            label,
+                            target.display()
+                        ));
+                    }
+                    Some(target)
+                }
+                None => None,
+            };
+            Ok(PatchedFile {
+                path,
+                move_to,
+                original: Some(original),
+                updated: Some(updated),
+            })
+        }
+    }
+}
+
+/// Apply hunks in order, reporting each one that cannot be located
+fn apply_hunks(content: &str, hunks: &[Hunk]) -> std::result::Result<String, Vec<String>> {
+    let line_ending = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut eof_newline = content.is_empty() || content.ends_with('\n');
+    let mut errors = Vec::new();
+    // Hunks apply in order, each after the previous one
+    let mut cursor = 0;
+    // Lines added minus lines removed so far, to shift line number hints
+    let mut shift: isize = 0;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let located = fuzz_steps()
+            .into_iter()
+            .filter_map(|(leading, trailing)| hunk.trim_context(leading, trailing))
+            .find_map(|trimmed| {
+                locate_hunk(&lines, &trimmed, cursor, shift).map(|pos| (pos, trimmed))
+            });
+
+        let Some((pos, hunk)) = located else {
+            errors.push(describe_failure(index + 1, hunk, &lines, cursor));
+            continue;
+        };
+
+        let old_len = hunk.old_lines().len();
+        // Context lines keep the file's text, which may differ in whitespace
+        let mut old = lines[pos..pos + old_len].iter();
+        let new_lines: Vec<String> = hunk
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(_) => old.next().cloned(),
+                HunkLine::Remove(_) => {
+                    old.next();
+                    None
+                }
+                HunkLine::Add(text) => Some(text.clone()),
+            })
+            .collect();
+        let new_len = new_lines.len();
+        lines.splice(pos..pos + old_len, new_lines);
+        cursor = pos + new_len;
+        shift += new_len as isize - old_len as isize;
+        if let Some(newline) = hunk.new_eof_newline {
+            eof_newline = newline;
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let mut updated = lines.join(line_ending);
+    if eof_newline && !lines.is_empty() {
+        updated.push_str(line_ending);
+    }
+    Ok(updated)
+}
+
+/// Context lines to drop at the start and end of a hunk, least first
+fn fuzz_steps() -> Vec<(usize, usize)> {
+    let mut steps: Vec<_> = (0..=MAX_FUZZ)
+        .flat_map(|leading| (0..=MAX_FUZZ).map(move |trailing| (leading, trailing)))
+        .collect();
+    steps.sort_by_key(|(leading, trailing)| leading + trailing);
+    steps
+}
+
+/// Find where the old side of a hunk starts, from the cursor on
+fn locate_hunk(lines: &[String], hunk: &Hunk, cursor: usize, shift: isize) -> Option<usize> {
+    let old = hunk.old_lines();
+    let shifted = |line: usize| (line as isize + shift).max(0) as usize;
+
+    if old.is_empty() {
+        // Pure additions go after line `old_start`, or at the end of the file
+        return Some(match hunk.old_start {
+            Some(start) if !hunk.end_of_file => shifted(start).min(lines.len()),
+            _ => lines.len(),
+        });
+    }
+
+    // A header line in the envelope format narrows the search
+    let mut from = cursor;
+    if hunk.old_start.is_none() && !hunk.header.is_empty() {
+        from = (0..MATCHERS.len()).find_map(|level| {
+            (cursor..lines.len()).find(|&i| lines_match(&lines[i], &hunk.header, level))
+        })?;
+    }
+    let hint = hunk.old_start.map(|start| shifted(start.saturating_sub(1)));
+
+    for level in 0..MATCHERS.len() {
+        let matches_at = |pos: usize| {
+            old.iter()
+                .enumerate()
+                .all(|(offset, expected)| lines_match(&lines[pos + offset], expected, level))
+        };
+        let last = lines.len().checked_sub(old.len())?;
+        let candidates: Vec<usize> = (from.min(last + 1)..=last)
+            .filter(|&pos| matches_at(pos))
+            .collect();
+
+        let best = if hunk.end_of_file {
+            candidates.last().copied()
+        } else if let Some(hint) = hint {
+            candidates
+                .iter()
+                .copied()
+                .min_by_key(|&pos| pos.abs_diff(hint))
+        } else {
+            candidates.first().copied()
+        };
+        if best.is_some() {
+            return best;
+        }
+    }
+    None
+}
+
+/// Line comparisons from strict to lenient
+const MATCHERS: [fn(&str) -> String; 4] = [
+    |line| line.to_string(),
+    |line| line.trim_end().to_string(),
+    |line| line.trim().to_string(),
+    |line| normalize_punctuation(line.trim()),
+];
+
+fn lines_match(actual: &str, expected: &str, level: usize) -> bool {
+    if level == 0 {
+        return actual == expected;
+    }
+    MATCHERS[level](actual) == MATCHERS[level](expected)
+}
+
+/// Replace typographic punctuation models sometimes emit with ASCII
+fn normalize_punctuation(line: &str) -> String {
+    line.chars()
+        .map(|c| match c {
+            '\u{2010}'..='\u{2015}' | '\u{2212}' => '-',
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+            '\u{00A0}' | '\u{2002}'..='\u{200A}' | '\u{202F}' => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+/// Explain why a hunk could not be located
+fn describe_failure(number: usize, hunk: &Hunk, lines: &[String], cursor: usize) -> String {
+    let old = hunk.old_lines();
+    let mut report = format!(
+        "  {}: could not find these lines{}:\n",
+        hunk.describe(number),
+        if cursor > 0 {
+            " after the previous hunk"
+        } else {
+            ""
+        }
+    );
+    for line in old.iter().take(MAX_QUOTED_LINES) {
+        report.push_str(&format!("    |{}\n", line));
+    }
+    if old.len() > MAX_QUOTED_LINES {
+        report.push_str(&format!("    ... {} more\n", old.len() - MAX_QUOTED_LINES));
+    }
+
+    // Point at where the first distinctive line appears, if anywhere
+    if let Some(first) = old.iter().find(|line| !line.trim().is_empty()) {
+        let found: Vec<String> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.trim() == first.trim())
+            .map(|(i, _)| (i + 1).to_string())
+            .take(5)
+            .collect();
+        if found.is_empty() {
+            report.push_str(&format!(
+                "    `{}` does not appear in the file; view the file and regenerate the hunk",
+                first.trim()
+            ));
+        } else {
+            report.push_str(&format!(
+                "    `{}` appears at line(s) {} but the lines around it differ; view them and regenerate the hunk",
+                first.trim(),
+                found.join(", ")
+            ));
+        }
+    }
+    report.trim_end().to_string()
+}
+
+/// Write the patched files, restoring the written ones if any write fails
+fn write_files(files: &[PatchedFile]) -> std::result::Result<(), String> {
+    // Directories created for new files, parents first
+    let mut created_dirs = Vec::new();
+    for (written, file) in files.iter().enumerate() {
+        if let Err(e) = write_file(file, &mut created_dirs) {
+            // The failing file may be half written, e.g. a move whose source
+            // could not be removed after its target was written
+            for done in files[..=written].iter().rev() {
+                undo_file(done);
+            }
+            for dir in created_dirs.iter().rev() {
+                let _ = std::fs::remove_dir(dir);
+            }
+            return Err(format!("Failed to write {}: {}", file.path.display(), e));
+        }
+    }
+    Ok(())
+}
+
+fn write_file(file: &PatchedFile, created_dirs: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let target = file.move_to.as_ref().unwrap_or(&file.path);
+    match &file.updated {
+        Some(content) => {
+            if let Some(parent) = target.parent() {
+                let missing: Vec<&Path> =
+                    parent.ancestors().take_while(|dir| !dir.exists()).collect();
+                std::fs::create_dir_all(parent)?;
+                created_dirs.extend(missing.into_iter().rev().map(Path::to_path_buf));
+            }
+            std::fs::write(target, content)?;
+            if target != &file.path {
+                std::fs::remove_file(&file.path)?;
+            }
+        }
+        None => std::fs::remove_file(&file.path)?,
+    }
+    Ok(())
+}
+
+fn undo_file(file: &PatchedFile) {
+    if let Some(target) = &file.move_to {
+        if target != &file.path {
+            let _ = std::fs::remove_file(target);
+        }
+    }
+    let _ = match &file.original {
+        Some(original) => std::fs::write(&file.path, original),
+        None => std::fs::remove_file(&file.path),
+    };
+}
+
+/// Build the result listing the changed files with a diff for each
+fn patch_result(call_id: &str, root: &Path, files: &[PatchedFile]) -> ToolResult {
+    let relative = |path: &Path| {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+
+    let mut summary = Vec::new();
+    let mut diffs = Vec::new();
+    let mut data = Vec::new();
+    for file in files {
+        let old_name = relative(&file.path);
+        let new_name = relative(file.move_to.as_ref().unwrap_or(&file.path));
+        let (old_header, new_header) = match (&file.original, &file.updated) {
+            (None, _) => ("/dev/null".to_string(), format!("b/{}", new_name)),
+            (_, None) => (format!("a/{}", old_name), "/dev/null".to_string()),
+            _ => (format!("a/{}", old_name), format!("b/{}", new_name)),
+        };
+        let diff = TextDiff::from_lines(
+            file.original.as_deref().unwrap_or(""),
+            file.updated.as_deref().unwrap_or(""),
+        )
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT_LINES)
+        .header(&old_header, &new_header)
+        .to_string();
+
+        let action = file.action();
+        summary.push(match action {
+            "move" => format!("R {} -> {}", old_name, new_name),
+            "add" => format!("A {}", new_name),
+            "delete" => format!("D {}", old_name),
+            _ => format!("M {}", old_name),
+        });
+        data.push(json!({
+            "path": file.move_to.as_ref().unwrap_or(&file.path).display().to_string(),
+            "action": action,
+            "moved_from": file.move_to.as_ref().map(|_| file.path.display().to_string()),
+            "diff": diff,
+        }));
+        diffs.push(diff);
+    }
+
+    let content = format!(
+        "Applied the patch to {} file(s):\n{}\n\n{}",
+        files.len(),
+        summary.join("\n"),
+        maybe_truncate(&diffs.concat(), None)
+    );
+    ToolResult::success(call_id, &content).with_data(json!({ "files": data }))
+}
+
+/// Factory for creating patch tools that share a read tracker with the edit tool
+pub struct ApplyPatchToolFactory {
+    read_policy: ReadPolicy,
+    tracker: ReadTracker,
+}
+
+impl ApplyPatchToolFactory {
+    /// Create tools that check patched files according to `read_policy`
+    /// against the files recorded by `tracker`
+    pub fn with_options(read_policy: ReadPolicy, tracker: ReadTracker) -> Self {
+        Self {
+            read_policy,
+            tracker,
+        }
+    }
+}
+
+impl ToolFactory for ApplyPatchToolFactory {
+    fn create(&self) -> Box<dyn Tool> {
+        Box::new(ApplyPatchTool::with_options(
+            self.read_policy,
+            self.tracker.clone(),
+        ))
+    }
+
+    fn tool_name(&self) -> &str {
+        "apply_patch"
+    }
+
+    fn tool_description(&self) -> &str {
+        "Apply unified diffs or *** Begin Patch patches that add, update, delete or move files"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::edit::EditTool;
+    use tempfile::TempDir;
+
+    fn apply(root: &Path, patch: &str) -> ToolResult {
+        let call = ToolCall::new("apply_patch", json!({ "patch": patch }));
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(ApplyPatchTool::new().with_root(root).execute(call))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_envelope() {
+        let changes = parse_patch(
+            "*** Begin Patch\n\
+             *** Update File: src/lib.rs\n\
+             *** Move to: src/core.rs\n\
+             @@ fn main() {\n \
+             let x = 1;\n\
+             \n\
+             -old\n\
+             +new\n\
+             *** End of File\n\
+             *** Add File: notes.txt\n\
+             +hello\n\
+             *** Delete File: old.txt\n\
+             *** End Patch",
+        )
+        .unwrap();
+
+        assert_eq!(changes.len(), 3);
+        let FileChange::Update {
+            path,
+            move_to,
+            hunks,
+        } = &changes[0]
+        else {
+            panic!("expected an update, got {:?}", changes[0]);
+        };
+        assert_eq!(path, "src/lib.rs");
+        assert_eq!(move_to.as_deref(), Some("src/core.rs"));
+        assert_eq!(hunks[0].header, "fn main() {");
+        assert_eq!(hunks[0].old_lines(), ["let x = 1;", "", "old"]);
+        assert_eq!(hunks[0].new_lines(), ["let x = 1;", "", "new"]);
+        assert!(hunks[0].end_of_file);
+        assert_eq!(
+            changes[1],
+            FileChange::Add {
+                path: "notes.txt".to_string(),
+                content: "hello\n".to_string()
+            }
+        );
+        assert_eq!(
+            changes[2],
+            FileChange::Delete {
+                path: "old.txt".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let changes = parse_patch(
+            "```diff\n\
+             diff --git a/src/a.rs b/src/a.rs\n\
+             --- a/src/a.rs\t2024-01-01\n\
+             +++ b/src/a.rs\n\
+             @@ -3,2 +3,2 @@ fn a()\n \
+             one\n\
+             -two\n\
+             +three\n\
+             \\ No newline at end of file\n\
+             --- /dev/null\n\
+             +++ b/new.txt\n\
+             @@ -0,0 +1 @@\n\
+             +created\n\
+             diff --git a/x.rs b/y.rs\n\
+             similarity index 100%\n\
+             rename from x.rs\n\
+             rename to y.rs\n\
+             ```",
+        )
+        .unwrap();
+
+        let FileChange::Update { path, hunks, .. } = &changes[0] else {
+            panic!("expected an update, got {:?}", changes[0]);
+        };
+        assert_eq!(path, "src/a.rs");
+        assert_eq!(hunks[0].old_start, Some(3));
+        assert_eq!(hunks[0].new_eof_newline, Some(false));
+        assert_eq!(
+            changes[1],
+            FileChange::Add {
+                path: "new.txt".to_string(),
+                content: "created\n".to_string()
+            }
+        );
+        assert_eq!(changes[2], rename_change("x.rs".into(), "y.rs".into()));
+    }
+
+    #[test]
+    fn test_apply_hunks_with_fuzz() {
+        let content = "fn a() {\n    one();\n}\n\nfn b() {\n    one();\n}\n";
+        let hunk = |old_start, lines: &[HunkLine]| Hunk {
+            old_start,
+            lines: lines.to_vec(),
+            ..Default::default()
+        };
+        use HunkLine::*;
+
+        // The line hint picks the second of two identical blocks, and
+        // trailing whitespace in the patch is tolerated
+        let second = hunk(
+            Some(6),
+            &[
+                Remove("    one();".into()),
+                Add("    two();".into()),
+                Context("}  ".into()),
+            ],
+        );
+        assert_eq!(
+            apply_hunks(content, &[second]).unwrap(),
+            "fn a() {\n    one();\n}\n\nfn b() {\n    two();\n}\n"
+        );
+
+        // A stale leading context line is dropped
+        let stale = hunk(
+            None,
+            &[
+                Context("fn a(x: u32) {".into()),
+                Remove("    one();".into()),
+                Add("    zero();".into()),
+                Context("}".into()),
+            ],
+        );
+        assert!(apply_hunks(content, &[stale])
+            .unwrap()
+            .starts_with("fn a() {\n    zero();"));
+
+        let missing = hunk(None, &[Remove("    three();".into())]);
+        let errors = apply_hunks(content, &[missing]).unwrap_err();
+        assert!(errors[0].contains("hunk 1: could not find these lines"));
+        assert!(errors[0].contains("does not appear in the file"));
+    }
+
+    #[test]
+    fn test_apply_patch_is_all_or_nothing() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("a.txt"), "alpha\nbeta\n").unwrap();
+        std::fs::write(root.join("b.txt"), "gamma\n").unwrap();
+
+        let result = apply(
+            root,
+            "*** Begin Patch\n*** Update File: a.txt\n-beta\n+BETA\n*** Update File: b.txt\n-delta\n+DELTA\n*** End Patch",
+        );
+        assert!(!result.success);
+        assert!(result.content.contains("b.txt:\n  hunk 1: could not find"));
+        assert_eq!(
+            std::fs::read_to_string(root.join("a.txt")).unwrap(),
+            "alpha\nbeta\n"
+        );
+
+        let result = apply(
+            root,
+            "--- a/../outside.txt\n+++ b/../outside.txt\n@@ -1 +1 @@\n-x\n+y\n",
+        );
+        assert!(result.content.contains("outside the workspace"));
+
+        let result = apply(
+            root,
+            "*** Begin Patch\n*** Update File: a.txt\n*** Move to: docs/a.md\n@@\n alpha\n-beta\n+BETA\n*** Delete File: b.txt\n*** End Patch",
+        );
+        assert!(result.success, "{}", result.content);
+        assert!(result.content.contains("R a.txt -> docs/a.md\nD b.txt"));
+        assert_eq!(
+            std::fs::read_to_string(root.join("docs/a.md")).unwrap(),
+            "alpha\nBETA\n"
+        );
+        assert!(!root.join("a.txt").exists() && !root.join("b.txt").exists());
+        assert_eq!(result.data.unwrap()["files"][0]["action"], "move");
+    }
+
+    #[tokio::test]
+    async fn test_workdir_cannot_widen_the_workspace() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "x\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "x\n").unwrap();
+        let tool = ApplyPatchTool::new().with_root(&root);
+        let patch = "--- a/secret.txt\n+++ b/secret.txt\n@@ -1 +1 @@\n-x\n+y\n";
+
+        let result = tool
+            .execute(ToolCall::new(
+                "apply_patch",
+                json!({ "patch": patch, "workdir": dir.path() }),
+            ))
+            .await
+            .unwrap();
+        assert!(result.content.contains("outside the workspace"));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("secret.txt")).unwrap(),
+            "x\n"
+        );
+
+        // Relative paths are resolved against a workdir inside the workspace
+        let patch = "--- a/lib.rs\n+++ b/lib.rs\n@@ -1 +1 @@\n-x\n+y\n";
+        let result = tool
+            .execute(ToolCall::new(
+                "apply_patch",
+                json!({ "patch": patch, "workdir": "src" }),
+            ))
+            .await
+            .unwrap();
+        assert!(result.success, "{}", result.content);
+        assert!(result.content.contains("M src/lib.rs"));
+    }
+
+    #[test]
+    fn test_failed_write_undoes_the_failing_file() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("missing.txt");
+        let target = dir.path().join("moved.txt");
+        let file = PatchedFile {
+            path: source.clone(),
+            move_to: Some(target.clone()),
+            original: Some("old\n".to_string()),
+            updated: Some("new\n".to_string()),
+        };
+
+        // The target is written, then removing the source fails
+        assert!(write_files(&[file]).is_err());
+        assert!(!target.exists());
+        assert_eq!(std::fs::read_to_string(&source).unwrap(), "old\n");
+    }
+
+    #[test]
+    fn test_failed_write_removes_created_directories() {
+        let dir = TempDir::new().unwrap();
+        let added = PatchedFile {
+            path: dir.path().join("new/deep/added.txt"),
+            move_to: None,
+            original: None,
+            updated: Some("added\n".to_string()),
+        };
+        let moved = PatchedFile {
+            path: dir.path().join("missing.txt"),
+            move_to: Some(dir.path().join("other/moved.txt")),
+            original: Some("old\n".to_string()),
+            updated: Some("new\n".to_string()),
+        };
+
+        assert!(write_files(&[added, moved]).is_err());
+        assert!(!dir.path().join("new").exists());
+        assert!(!dir.path().join("other").exists());
+    }
+
+    #[tokio::test]
+    async fn test_patched_files_stay_editable() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "one\n").unwrap();
+        let tracker = ReadTracker::default();
+        let edit = EditTool::with_options(None, ReadPolicy::Strict, tracker.clone());
+        let patch = ApplyPatchTool::with_options(ReadPolicy::Strict, tracker).with_root(dir.path());
+        let edit_call = |params: serde_json::Value| {
+            edit.execute(ToolCall::new("str_replace_based_edit_tool", params))
+        };
+        let patch_call = |patch_text: &str| {
+            patch.execute(ToolCall::new("apply_patch", json!({ "patch": patch_text })))
+        };
+
+        edit_call(json!({"command": "view", "path": path}))
+            .await
+            .unwrap();
+        let result = patch_call("--- a/notes.txt\n+++ b/notes.txt\n@@ -1 +1 @@\n-one\n+two\n")
+            .await
+            .unwrap();
+        assert!(result.success, "{}", result.content);
+
+        let result = edit_call(
+            json!({"command": "str_replace", "path": path, "old_str": "two", "new_str": "three"}),
+        )
+        .await
+        .unwrap();
+        assert!(result.success, "{}", result.content);
+
+        // A change the agent did not make still blocks the patch
+        std::fs::write(&path, "changed elsewhere\n").unwrap();
+        let result = patch_call(
+            "--- a/notes.txt\n+++ b/notes.txt\n@@ -1 +1 @@\n-changed elsewhere\n+four\n",
+        )
+        .await
+        .unwrap();
+        assert!(result.content.contains("has changed on disk"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "changed elsewhere\n"
+        );
+    }
+}
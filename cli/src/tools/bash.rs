@@ -1,17 +1,23 @@
 //! Cross-platform shell execution tool
 
+use crate::tools::bash_jobs::{signal_process_group, BackgroundJobs, JobStatus};
 use async_trait::async_trait;
 use coro_core::error::Result;
-use coro_core::impl_tool_factory;
 use coro_core::tools::utils::maybe_truncate;
-use coro_core::tools::{Tool, ToolCall, ToolExample, ToolResult};
+use coro_core::tools::{Tool, ToolCall, ToolExample, ToolFactory, ToolProgress, ToolResult};
 use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
 use std::process::Stdio;
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, Command};
+use tokio::process::{Child, ChildStdout, Command};
 use tokio::sync::Mutex;
-use tokio::time::{sleep, timeout, Duration};
+use tokio::time::{timeout_at, Duration, Instant};
+
+/// Default timeout of a single command
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
 
 /// Warning information for potentially dangerous commands
 #[derive(Debug)]
@@ -59,7 +65,7 @@ fn check_windows_command_safety(command: &str) -> Option<CommandWarning> {
 
 /// Shell configuration for different operating systems
 #[derive(Debug, Clone)]
-struct ShellConfig {
+pub struct ShellConfig {
     command: String,
     args: Vec<String>,
     sentinel: String,
@@ -67,7 +73,8 @@ struct ShellConfig {
 }
 
 impl ShellConfig {
-    fn new() -> Self {
+    /// Detect the shell of the current platform
+    pub fn new() -> Self {
         if cfg!(target_os = "windows") {
             Self {
                 command: "cmd.exe".to_string(),
@@ -88,27 +95,73 @@ impl ShellConfig {
             }
         }
     }
+
+    /// Program started for shell sessions
+    pub fn program(&self) -> &str {
+        &self.command
+    }
+}
+
+impl Default for ShellConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Time a timed-out command gets to exit after SIGTERM before it is killed
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// How often a running command's stderr file is checked for new output
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Options of a single command
+#[derive(Debug, Clone, Default)]
+struct RunOptions {
+    /// Timeout replacing the session's
+    timeout: Option<Duration>,
+    /// Directory the command runs in
+    cwd: Option<String>,
+    /// Variables added to the command's environment
+    env: BTreeMap<String, String>,
+}
+
+/// Result of a command run in a shell session
+#[derive(Debug, Default)]
+struct CommandOutput {
+    exit_code: i32,
+    stdout: String,
+    stderr: String,
+    /// The command did not finish in time
+    timed_out: bool,
+    /// The timed-out command could not be stopped without the session
+    session_lost: bool,
 }
 
 /// A session of a cross-platform shell
 struct ShellSession {
     process: Option<Child>,
+    stdout: Option<BufReader<ChildStdout>>,
     started: bool,
     timed_out: bool,
     config: ShellConfig,
-    output_delay: Duration,
     timeout: Duration,
+    /// Private directory holding the stderr files of commands
+    stderr_dir: Option<tempfile::TempDir>,
+    /// Number of commands run, used to name their stderr files
+    commands_run: u64,
 }
 
 impl ShellSession {
-    fn new() -> Self {
+    fn new(timeout: Duration) -> Self {
         Self {
             process: None,
+            stdout: None,
             started: false,
             timed_out: false,
             config: ShellConfig::new(),
-            output_delay: Duration::from_millis(200),
-            timeout: Duration::from_secs(120),
+            timeout,
+            stderr_dir: None,
+            commands_run: 0,
         }
     }
 
@@ -117,6 +170,16 @@ impl ShellSession {
             return Ok(());
         }
 
+        // Only readable by the user, and removed with the session
+        let mut stderr_dir = tempfile::Builder::new();
+        stderr_dir.prefix("coro-bash-");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            stderr_dir.permissions(std::fs::Permissions::from_mode(0o700));
+        }
+        let stderr_dir = stderr_dir.tempdir()?;
+
         let mut cmd = Command::new(&self.config.command);
 
         // Add shell-specific arguments
@@ -124,9 +187,10 @@ impl ShellSession {
             cmd.args(&self.config.args);
         }
 
+        // Commands redirect their own stderr, the shell's only has job notices
         cmd.stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+            .stderr(Stdio::null());
 
         // On Unix-like systems, set process group
         #[cfg(unix)]
@@ -136,7 +200,18 @@ impl ShellSession {
             cmd.process_group(0);
         }
 
-        self.process = Some(cmd.spawn()?);
+        let mut process = cmd.spawn()?;
+        self.stdout = process.stdout.take().map(BufReader::new);
+        if !self.config.is_windows {
+            // Job control gives every command a process group that can be
+            // killed on timeout without killing the session
+            if let Some(stdin) = process.stdin.as_mut() {
+                stdin.write_all(b"set -m\n").await?;
+                stdin.flush().await?;
+            }
+        }
+        self.process = Some(process);
+        self.stderr_dir = Some(stderr_dir);
         self.started = true;
         Ok(())
     }
@@ -151,20 +226,26 @@ impl ShellSession {
                 std::mem::drop(process.kill());
             }
         }
+        self.stdout = None;
+        self.stderr_dir = None;
         self.started = false;
     }
 
-    async fn run(&mut self, command: &str) -> Result<(i32, String, String)> {
+    async fn run(
+        &mut self,
+        command: &str,
+        options: &RunOptions,
+        progress: &ToolProgress,
+    ) -> Result<CommandOutput> {
         if !self.started || self.process.is_none() {
             return Err("Session has not started.".into());
         }
 
         if self.timed_out {
-            return Err(format!(
-                "timed out: shell has not returned in {} seconds and must be restarted",
-                self.timeout.as_secs()
-            )
-            .into());
+            return Err(
+                "timed out: a previous command could not be stopped and the shell must be restarted"
+                    .into(),
+            );
         }
 
         let process = self.process.as_mut().unwrap();
@@ -178,35 +259,69 @@ impl ShellSession {
             .into());
         }
 
-        let _error_code = 0;
         let (sentinel_before, sentinel_after) = self
             .config
             .sentinel
             .split_once("__ERROR_CODE__")
             .ok_or("Invalid sentinel format")?;
+        let (pid_before, pid_after) = PID_SENTINEL
+            .split_once("__PID__")
+            .ok_or("Invalid sentinel format")?;
+
+        self.commands_run += 1;
+        let stderr_path = self
+            .stderr_dir
+            .as_ref()
+            .ok_or("Session has not started.")?
+            .path()
+            .join(format!("{}.stderr", self.commands_run));
+        let stderr_file = stderr_path.display().to_string();
 
         // Build command based on shell type
         let full_command = if self.config.is_windows {
-            // CMD syntax
-            let errcode_retriever = "%ERRORLEVEL%";
+            // CMD syntax: `setlocal` has no effect outside batch files, so a
+            // command with its own directory or variables runs in a child cmd
+            let command = if options.cwd.is_none() && options.env.is_empty() {
+                format!("({})", command)
+            } else {
+                let mut inner = String::new();
+                if let Some(cwd) = &options.cwd {
+                    inner.push_str(&format!("cd /d {} && ", cmd_escape(cwd)));
+                }
+                for (key, value) in &options.env {
+                    inner.push_str(&format!("(set {}={}) && ", key, cmd_escape(value)));
+                }
+                inner.push_str(command);
+                format!("cmd /d /s /c ^\"{}^\"", cmd_escape(&inner))
+            };
             format!(
-                "{} & echo {}\r\n",
+                "{} 2>\"{}\" & echo {}\r\n",
                 command,
+                stderr_file,
                 self.config
                     .sentinel
-                    .replace("__ERROR_CODE__", errcode_retriever)
+                    .replace("__ERROR_CODE__", "%ERRORLEVEL%")
             )
         } else {
-            // Bash syntax
-            let errcode_retriever = "$?";
-            let command_sep = ";";
+            // Bash syntax: the command runs as a job so it can be killed alone
+            let mut prefix = String::new();
+            if let Some(cwd) = &options.cwd {
+                prefix.push_str(&format!("cd -- {} || exit 1\n", shell_quote(cwd)));
+            }
+            for (key, value) in &options.env {
+                prefix.push_str(&format!("export {}={}\n", key, shell_quote(value)));
+            }
             format!(
-                "(\n{}\n){} echo {}\n",
+                "(\n{}{}\n) </dev/null 2>{} &\n\
+                 __coro_pid=$!\n\
+                 echo {}\n\
+                 wait $__coro_pid\n\
+                 echo {}\n",
+                prefix,
                 command,
-                command_sep,
-                self.config
-                    .sentinel
-                    .replace("__ERROR_CODE__", errcode_retriever)
+                shell_quote(&stderr_file),
+                PID_SENTINEL.replace("__PID__", "$__coro_pid"),
+                self.config.sentinel.replace("__ERROR_CODE__", "$?")
             )
         };
 
@@ -218,91 +333,337 @@ impl ShellSession {
             return Err("No stdin available".into());
         }
 
-        // Read output from the process until sentinel is found
-        let result = timeout(self.timeout, async {
-            let mut output = String::new();
-            let mut error_code = 0;
-
-            if let Some(stdout) = process.stdout.as_mut() {
-                let mut reader = BufReader::new(stdout);
-                let mut buffer = Vec::new();
-
-                loop {
-                    sleep(self.output_delay).await;
-
-                    // Try to read available data
-                    match reader.read_until(b'\n', &mut buffer).await {
-                        Ok(0) => break, // EOF
-                        Ok(_) => {
-                            let line = String::from_utf8_lossy(&buffer);
-                            output.push_str(&line);
-                            buffer.clear();
-
-                            if output.contains(sentinel_before) {
-                                // Extract the sentinel and error code
-                                if let Some(pos) = output.rfind(sentinel_before) {
-                                    let content = output[..pos].to_string();
-                                    let rest = &output[pos..];
-
-                                    // Extract error code from the sentinel
-                                    if let Some(code_start) = rest.find(sentinel_before) {
-                                        let code_part = &rest[code_start + sentinel_before.len()..];
-                                        if let Some(code_end) = code_part.find(sentinel_after) {
-                                            let code_str = &code_part[..code_end];
-                                            error_code = code_str.trim().parse().unwrap_or(-1);
-                                        }
-                                    }
-
-                                    output = content;
-                                    break;
-                                }
-                            }
+        let reader = self.stdout.as_mut().ok_or("No stdout available")?;
+        let timeout = options.timeout.unwrap_or(self.timeout);
+        let mut deadline = Instant::now() + timeout;
+        let mut output = String::new();
+        let mut buffer = Vec::new();
+        let mut pid: Option<u32> = None;
+        // SIGTERM is sent at the timeout, SIGKILL if the command outlives it
+        let mut stop_signals = 0;
+        // stderr goes to a file, so it is forwarded by polling the file
+        let mut stderr_offset = 0;
+        let mut poll = tokio::time::interval(PROGRESS_POLL_INTERVAL);
+
+        // Read output until the exit sentinel, stopping the command on timeout
+        let exit_code = loop {
+            let read = tokio::select! {
+                read = timeout_at(deadline, reader.read_until(b'\n', &mut buffer)) => read,
+                _ = poll.tick(), if progress.is_enabled() => {
+                    let lines = read_new_lines(&stderr_path, &mut stderr_offset);
+                    if !lines.is_empty() {
+                        progress.output(lines);
+                    }
+                    continue;
+                }
+            };
+            match read {
+                Ok(Ok(0)) => {
+                    return Err("shell has exited. tool must be restarted.".into());
+                }
+                Ok(Ok(_)) => {
+                    // Markers are echoed on lines of their own, so only the
+                    // line just read can hold one
+                    let line_start = output.len();
+                    output.push_str(&String::from_utf8_lossy(&buffer));
+                    buffer.clear();
+
+                    if let Some(start) = output[line_start..].find(pid_before) {
+                        let start = line_start + start;
+                        let rest = &output[start + pid_before.len()..];
+                        if let Some(end) = rest.find(pid_after) {
+                            pid = rest[..end].trim().parse().ok();
+                            let marker_end = start + pid_before.len() + end + pid_after.len();
+                            let marker_end = if output[marker_end..].starts_with('\n') {
+                                marker_end + 1
+                            } else {
+                                marker_end
+                            };
+                            output.replace_range(start..marker_end, "");
                         }
-                        Err(_) => break,
                     }
+
+                    let line_start = line_start.min(output.len());
+                    let exit = output[line_start..]
+                        .rfind(sentinel_before)
+                        .map(|pos| line_start + pos)
+                        .and_then(|pos| {
+                            let code_part = &output[pos + sentinel_before.len()..];
+                            let code_end = code_part.find(sentinel_after)?;
+                            Some((pos, code_part[..code_end].trim().parse().unwrap_or(-1)))
+                        });
+
+                    let chunk_end = exit.map_or(output.len(), |(pos, _)| pos);
+                    if chunk_end > line_start {
+                        progress.output(&output[line_start..chunk_end]);
+                    }
+                    if let Some((pos, code)) = exit {
+                        output.truncate(pos);
+                        break code;
+                    }
+                }
+                Ok(Err(e)) => return Err(e.into()),
+                Err(_) if stop_signals < 2 && pid.is_some() => {
+                    // Stop only the command, the sentinel follows once it exits
+                    signal_process_group(pid.unwrap_or_default(), stop_signals > 0);
+                    stop_signals += 1;
+                    deadline = Instant::now() + KILL_GRACE_PERIOD;
+                }
+                Err(_) => {
+                    // The command cannot be stopped without the session
+                    self.timed_out = true;
+                    let _ = std::fs::remove_file(&stderr_path);
+                    return Ok(CommandOutput {
+                        exit_code: -1,
+                        stdout: output,
+                        stderr: String::new(),
+                        timed_out: true,
+                        session_lost: true,
+                    });
                 }
             }
+        };
 
-            Ok::<(i32, String, String), coro_core::error::Error>((
-                error_code,
-                output,
-                String::new(),
-            ))
+        let stderr = std::fs::read_to_string(&stderr_path).unwrap_or_default();
+        let _ = std::fs::remove_file(&stderr_path);
+
+        Ok(CommandOutput {
+            exit_code,
+            stdout: output.trim_end_matches('\n').to_string(),
+            stderr: stderr.trim_end_matches('\n').to_string(),
+            timed_out: stop_signals > 0,
+            session_lost: false,
         })
-        .await;
+    }
+}
 
-        match result {
-            Ok(Ok((exit_code, stdout, stderr))) => {
-                let stdout_clean = if stdout.ends_with('\n') {
-                    stdout.trim_end_matches('\n').to_string()
-                } else {
-                    stdout
-                };
-                Ok((exit_code, stdout_clean, stderr))
-            }
-            Ok(Err(e)) => Err(e),
-            Err(_) => {
-                self.timed_out = true;
-                Err(format!(
-                    "timed out: bash has not returned in {} seconds and must be restarted",
-                    self.timeout.as_secs()
-                )
-                .into())
-            }
+/// Read the complete lines appended to a file since `offset`, advancing it
+fn read_new_lines(path: &Path, offset: &mut u64) -> String {
+    let mut bytes = Vec::new();
+    let read = std::fs::File::open(path).and_then(|mut file| {
+        file.seek(SeekFrom::Start(*offset))?;
+        file.read_to_end(&mut bytes)
+    });
+    if read.is_err() {
+        return String::new();
+    }
+
+    // A partial line is picked up once it is complete
+    match bytes.iter().rposition(|&byte| byte == b'\n') {
+        Some(end) => {
+            *offset += end as u64 + 1;
+            String::from_utf8_lossy(&bytes[..=end]).into_owned()
+        }
+        None => String::new(),
+    }
+}
+
+/// Read the per-call `timeout_ms`, `cwd` and `env` parameters
+fn run_options(call: &ToolCall) -> std::result::Result<RunOptions, String> {
+    let timeout = match call.get_parameter::<u64>("timeout_ms") {
+        Ok(0) => return Err("Parameter `timeout_ms` must be greater than 0".to_string()),
+        Ok(ms) => Some(Duration::from_millis(ms)),
+        Err(_) => None,
+    };
+
+    let cwd: Option<String> = call.get_parameter("cwd").ok();
+    if let Some(cwd) = &cwd {
+        // A relative path would be checked against the process's directory,
+        // not the session's
+        if !Path::new(cwd).is_absolute() {
+            return Err(format!("The cwd {} must be an absolute path", cwd));
+        }
+        if !Path::new(cwd).is_dir() {
+            return Err(format!("The cwd {} is not a directory", cwd));
+        }
+    }
+
+    let env: BTreeMap<String, String> = call.get_parameter("env").unwrap_or_default();
+    if let Some(name) = env.keys().find(|name| !is_env_name(name)) {
+        return Err(format!("Invalid environment variable name: {}", name));
+    }
+
+    Ok(RunOptions { timeout, cwd, env })
+}
+
+/// Marker echoed with the process id of a command run in a bash session
+const PID_SENTINEL: &str = ",,,,shell-command-pid-__PID__-banner,,,,";
+
+/// Quote a string for bash
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// Escape a string for the cmd.exe command line, outside quotes
+///
+/// Special characters are escaped with `^`, and a caret after each `%` keeps
+/// it from starting a variable reference.
+fn cmd_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut after_percent = false;
+    for c in text.chars() {
+        if after_percent || "^&|<>()\"".contains(c) {
+            escaped.push('^');
         }
+        escaped.push(c);
+        after_percent = c == '%';
     }
+    escaped
+}
+
+/// Whether a name can be exported as an environment variable
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
+/// Operations on background jobs
+const JOB_ACTIONS: &[&str] = &["output", "status", "input", "kill", "list"];
+
 /// Tool for executing shell commands with session management
 pub struct BashTool {
     session: Arc<Mutex<Option<ShellSession>>>,
+    jobs: BackgroundJobs,
+    timeout: Duration,
+    max_output_len: Option<usize>,
 }
 
 impl BashTool {
     /// Create a new shell tool
     pub fn new() -> Self {
+        Self::with_options(DEFAULT_COMMAND_TIMEOUT, None)
+    }
+
+    /// Create a shell tool with a command timeout and an output length limit
+    pub fn with_options(timeout: Duration, max_output_len: Option<usize>) -> Self {
         Self {
             session: Arc::new(Mutex::new(None)),
+            jobs: BackgroundJobs::new(),
+            timeout,
+            max_output_len,
+        }
+    }
+
+    /// Truncate output to the configured length; without one the tool
+    /// executor's output budget keeps its head and tail
+    fn clip(&self, output: &str) -> String {
+        match self.max_output_len {
+            Some(limit) => maybe_truncate(output, Some(limit)),
+            None => output.to_string(),
+        }
+    }
+
+    /// Start a command as a background job
+    fn start_job(&self, call_id: &str, command: &str, options: &RunOptions) -> ToolResult {
+        let started = self.jobs.start(
+            command,
+            &ShellConfig::new(),
+            options.cwd.as_deref().map(Path::new),
+            &options.env,
+        );
+        match started {
+            Ok(job_id) => ToolResult::success(
+                call_id,
+                &format!(
+                    "Started background job {}: {}\n\
+                     Read its output with job_action \"output\" and job_id {}, and stop it with job_action \"kill\" when it is no longer needed.",
+                    job_id, command, job_id
+                ),
+            )
+            .with_data(json!({ "job_id": job_id })),
+            Err(e) => ToolResult::error(
+                call_id,
+                &format!("Failed to start background job: {}", e),
+            ),
+        }
+    }
+
+    /// Handle an operation on background jobs
+    async fn job_action(&self, call: &ToolCall, action: &str) -> ToolResult {
+        if action == "list" {
+            let jobs = self.jobs.list();
+            if jobs.is_empty() {
+                return ToolResult::success(call.id.as_str(), "No background jobs.");
+            }
+            let lines: Vec<String> = jobs
+                .iter()
+                .map(|job| {
+                    format!(
+                        "[{}] {} ({}s, {} unread bytes): {}",
+                        job.id,
+                        job.status,
+                        job.elapsed.as_secs(),
+                        job.unread,
+                        job.command
+                    )
+                })
+                .collect();
+            return ToolResult::success(&call.id, &lines.join("\n"));
+        }
+
+        let job_id: u32 = match call.get_parameter("job_id") {
+            Ok(job_id) => job_id,
+            Err(_) => {
+                return ToolResult::error(
+                    &call.id,
+                    &format!("Parameter `job_id` is required for job_action: {}", action),
+                )
+            }
+        };
+
+        let result = match action {
+            "output" => self.jobs.read_output(job_id).map(|chunk| {
+                let mut output = format!("Job {} is {}.", job_id, chunk.status);
+                if chunk.missed > 0 {
+                    output.push_str(&format!(
+                        "\n[{} bytes of older output were discarded]",
+                        chunk.missed
+                    ));
+                }
+                if chunk.text.is_empty() {
+                    output.push_str(" No new output.");
+                } else {
+                    output.push_str(" New output:\n");
+                    output.push_str(&self.clip(&chunk.text));
+                }
+                output
+            }),
+            "status" => self.jobs.info(job_id).map(|job| {
+                format!(
+                    "Job {} is {} after {}s with {} unread bytes of output: {}",
+                    job.id,
+                    job.status,
+                    job.elapsed.as_secs(),
+                    job.unread,
+                    job.command
+                )
+            }),
+            "input" => match call.get_parameter::<String>("input") {
+                Ok(input) => self
+                    .jobs
+                    .send_input(job_id, &input)
+                    .await
+                    .map(|_| format!("Sent {} bytes to job {}.", input.len(), job_id)),
+                Err(_) => Err("Parameter `input` is required for job_action: input".into()),
+            },
+            "kill" => self.jobs.kill(job_id).await.map(|status| match status {
+                JobStatus::Killed => format!("Job {} has been killed.", job_id),
+                status => format!("Job {} is no longer running: {}.", job_id, status),
+            }),
+            _ => Err(format!(
+                "Unrecognized job_action {}. The allowed actions are: {}",
+                action,
+                JOB_ACTIONS.join(", ")
+            )
+            .into()),
+        };
+
+        match result {
+            Ok(output) => ToolResult::success(&call.id, &output),
+            Err(e) => ToolResult::error(&call.id, &e.to_string()),
         }
     }
 }
@@ -317,22 +678,22 @@ impl Tool for BashTool {
         if cfg!(target_os = "windows") {
             "Run commands in Windows Command Prompt (cmd.exe)\n\
              * When invoking this tool, the contents of the \"command\" parameter does NOT need to be XML-escaped.\n\
-             * State is persistent across command calls and discussions with the user.\n\
+             * State is persistent across command calls and discussions with the user: the working directory and variables set by a command stay for later commands. The `cwd` and `env` parameters only apply to the call that sets them.\n\
              * Uses Windows Command Prompt with UTF-8 encoding for proper Chinese character support.\n\
              * Supports both Windows built-in commands and external programs.\n\
              * IMPORTANT: Avoid recursive operations like 'dir /s' on large directories (target/, node_modules/, .git/).\n\
              * Start with simple 'dir' to see directory structure before using recursive commands.\n\
              * For large projects, use specific paths or exclude large folders to prevent timeouts.\n\
              * Please avoid commands that may produce a very large amount of output.\n\
-             * Please run long lived commands in the background when appropriate."
+             * Run long lived commands such as dev servers, watchers or long builds with `run_in_background`. This returns a job id; use `job_action` with `job_id` to read new `output`, check `status`, send `input` or `kill` the job, and `list` to see all jobs."
         } else {
             "Run commands in a bash shell\n\
              * When invoking this tool, the contents of the \"command\" parameter does NOT need to be XML-escaped.\n\
          * You have access to a mirror of common linux and python packages via apt and pip.\n\
-             * State is persistent across command calls and discussions with the user.\n\
+             * State is persistent across command calls and discussions with the user: the working directory and variables set by a command stay for later commands. The `cwd` and `env` parameters only apply to the call that sets them.\n\
          * To inspect a particular line range of a file, e.g. lines 10-25, try 'sed -n 10,25p /path/to/the/file'.\n\
              * Please avoid commands that may produce a very large amount of output.\n\
-         * Please run long lived commands in the background, e.g. 'sleep 10 &' or start a server in the background."
+         * Run long lived commands such as dev servers, watchers or long builds with `run_in_background`. This returns a job id; use `job_action` with `job_id` to read new `output`, check `status`, send `input` or `kill` the job, and `list` to see all jobs."
         }
     }
 
@@ -353,13 +714,51 @@ impl Tool for BashTool {
                 "restart": {
                     "type": "boolean",
                     "description": "Set to true to restart the shell session."
+                },
+                "run_in_background": {
+                    "type": "boolean",
+                    "description": "Run `command` as a background job outside the shell session and return its job id immediately. The session's working directory and variables do not apply."
+                },
+                "job_action": {
+                    "type": "string",
+                    "enum": JOB_ACTIONS,
+                    "description": "Operation on background jobs instead of running a command: `output` returns the output written since the last read, `status` reports whether the job is running, `input` writes `input` to its standard input, `kill` stops it with its child processes and `list` shows all jobs."
+                },
+                "job_id": {
+                    "type": "integer",
+                    "description": "Id of the background job for `job_action`, as returned by `run_in_background`."
+                },
+                "input": {
+                    "type": "string",
+                    "description": "Text written to the job's standard input for job_action `input`. Include a trailing newline to submit a line."
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "Timeout of this command in milliseconds. On timeout only the command is stopped and its partial output is returned; the session keeps running."
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Absolute path of the directory to run this command in. Does not change the directory of later commands."
+                },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": {"type": "string"},
+                    "description": "Environment variables set for this command only."
                 }
-            },
-            "required": ["command"]
+            }
         })
     }
 
     async fn execute(&self, call: ToolCall) -> Result<ToolResult> {
+        self.execute_with_progress(call, ToolProgress::disabled())
+            .await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        call: ToolCall,
+        progress: ToolProgress,
+    ) -> Result<ToolResult> {
         let restart: bool = call.get_parameter_or("restart", false);
 
         if restart {
@@ -369,7 +768,7 @@ impl Tool for BashTool {
                 if let Some(ref mut session) = *session_guard {
                     session.stop();
                 }
-                *session_guard = Some(ShellSession::new());
+                *session_guard = Some(ShellSession::new(self.timeout));
             }
 
             // Start the new session
@@ -386,6 +785,10 @@ impl Tool for BashTool {
             ));
         }
 
+        if let Ok(action) = call.get_parameter::<String>("job_action") {
+            return Ok(self.job_action(&call, &action).await);
+        }
+
         let command: String = call.get_parameter("command")?;
 
         // Windows-specific safety check for potentially dangerous recursive commands
@@ -401,11 +804,20 @@ impl Tool for BashTool {
             }
         }
 
+        let options = match run_options(&call) {
+            Ok(options) => options,
+            Err(e) => return Ok(ToolResult::error(&call.id, &e)),
+        };
+
+        if call.get_parameter_or("run_in_background", false) {
+            return Ok(self.start_job(&call.id, &command, &options));
+        }
+
         // Ensure session exists and is started
         let needs_start = {
             let mut session_guard = self.session.lock().await;
             if session_guard.is_none() {
-                *session_guard = Some(ShellSession::new());
+                *session_guard = Some(ShellSession::new(self.timeout));
                 true
             } else if let Some(ref session) = *session_guard {
                 !session.started
@@ -425,36 +837,65 @@ impl Tool for BashTool {
         let result = {
             let mut session_guard = self.session.lock().await;
             if let Some(ref mut session) = *session_guard {
-                session.run(&command).await
+                session.run(&command, &options, &progress).await
             } else {
                 return Err("No session available".into());
             }
         };
 
         match result {
-            Ok((exit_code, stdout, stderr)) => {
+            Ok(CommandOutput {
+                exit_code,
+                stdout,
+                stderr,
+                timed_out,
+                session_lost,
+            }) => {
                 let mut output = String::new();
 
                 if !stdout.is_empty() {
-                    output.push_str(&maybe_truncate(&stdout, None));
+                    output.push_str(&self.clip(&stdout));
                 }
 
                 if !stderr.is_empty() {
                     if !output.is_empty() {
                         output.push('\n');
                     }
-                    output.push_str(&maybe_truncate(&stderr, None));
+                    output.push_str(&self.clip(&stderr));
+                }
+
+                let data = json!({
+                    "exit_code": exit_code,
+                    "stdout": stdout,
+                    "stderr": stderr,
+                    "timed_out": timed_out
+                });
+
+                if timed_out {
+                    let timeout = options.timeout.unwrap_or(self.timeout);
+                    let marker = if session_lost {
+                        format!(
+                            "<command timed out after {}ms and could not be stopped; restart the tool to use the shell again>",
+                            timeout.as_millis()
+                        )
+                    } else {
+                        format!(
+                            "<command timed out after {}ms and was stopped; the output above is partial>",
+                            timeout.as_millis()
+                        )
+                    };
+                    if !output.is_empty() {
+                        output.push('\n');
+                    }
+                    output.push_str(&marker);
+                    return Ok(ToolResult::error(&call.id, &output).with_data(data));
                 }
 
                 if output.is_empty() {
                     output = format!("Command completed with exit code: {}", exit_code);
                 }
 
-                Ok(ToolResult::success(&call.id, &output).with_data(json!({
-                    "exit_code": exit_code,
-                    "stdout": stdout,
-                    "stderr": stderr
-                })))
+                Ok(ToolResult::success(&call.id, &output).with_data(data))
             }
             Err(e) => Ok(ToolResult::error(
                 &call.id,
@@ -514,6 +955,27 @@ impl Tool for BashTool {
                     parameters: json!({"command": "echo 'restarting'", "restart": true}),
                     expected_result: "Session restarted message".to_string(),
                 },
+                ToolExample {
+                    description: "Run tests in a subdirectory with a longer timeout".to_string(),
+                    parameters: json!({
+                        "command": "cargo test",
+                        "cwd": "/repo/core",
+                        "env": {"RUST_BACKTRACE": "1"},
+                        "timeout_ms": 600000
+                    }),
+                    expected_result: "Test output, stdout and stderr with the exit code"
+                        .to_string(),
+                },
+                ToolExample {
+                    description: "Start a dev server in the background".to_string(),
+                    parameters: json!({"command": "npm run dev", "run_in_background": true}),
+                    expected_result: "Job id of the started server".to_string(),
+                },
+                ToolExample {
+                    description: "Read new output of a background job".to_string(),
+                    parameters: json!({"job_action": "output", "job_id": 1}),
+                    expected_result: "Job status and output since the last read".to_string(),
+                },
                 ToolExample {
                     description: "Run a command with persistent state".to_string(),
                     parameters: json!({"command": "export MY_VAR=hello && echo $MY_VAR"}),
@@ -530,13 +992,200 @@ impl Default for BashTool {
     }
 }
 
-impl_tool_factory!(
-    BashToolFactory,
-    BashTool,
-    "bash",
-    if cfg!(target_os = "windows") {
-        "Execute Windows commands using cmd.exe"
-    } else {
-        "Execute bash commands on Unix-like systems"
+/// Factory for creating BashTool instances
+#[derive(Default)]
+pub struct BashToolFactory {
+    timeout: Option<Duration>,
+    max_output_len: Option<usize>,
+}
+
+impl BashToolFactory {
+    /// Create tools with a command timeout and an output length limit
+    pub fn with_options(timeout: Option<Duration>, max_output_len: Option<usize>) -> Self {
+        Self {
+            timeout,
+            max_output_len,
+        }
+    }
+}
+
+impl ToolFactory for BashToolFactory {
+    fn create(&self) -> Box<dyn Tool> {
+        Box::new(BashTool::with_options(
+            self.timeout.unwrap_or(DEFAULT_COMMAND_TIMEOUT),
+            self.max_output_len,
+        ))
+    }
+
+    fn tool_name(&self) -> &str {
+        "bash"
+    }
+
+    fn tool_description(&self) -> &str {
+        if cfg!(target_os = "windows") {
+            "Execute Windows commands using cmd.exe"
+        } else {
+            "Execute bash commands on Unix-like systems"
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn bash_call(parameters: serde_json::Value) -> ToolCall {
+        ToolCall::new("bash", parameters)
     }
-);
+
+    #[tokio::test]
+    async fn test_timeout_stops_only_the_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("child.pid");
+        let tool = BashTool::new();
+        let result = tool
+            .execute(bash_call(json!({
+                "command": format!("echo started; sleep 30 & echo $! > {}; wait", pid_file.display()),
+                "timeout_ms": 500
+            })))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.content.starts_with("started\n"));
+        assert!(result.content.contains(
+            "<command timed out after 500ms and was stopped; the output above is partial>"
+        ));
+        assert_eq!(result.data.as_ref().unwrap()["timed_out"], true);
+
+        // Processes started by the command are stopped with it
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let child_alive = || {
+            std::process::Command::new("kill")
+                .args(["-0", pid.trim()])
+                .stderr(Stdio::null())
+                .status()
+                .unwrap()
+                .success()
+        };
+        for _ in 0..20 {
+            if !child_alive() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!child_alive());
+
+        // The session keeps running
+        let result = tool
+            .execute(bash_call(json!({"command": "echo alive"})))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.content, "alive");
+    }
+
+    #[tokio::test]
+    async fn test_output_data() {
+        let tool = BashTool::new();
+        let result = tool
+            .execute(bash_call(
+                json!({"command": "echo out; echo err >&2; exit 3"}),
+            ))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.content, "out\nerr");
+        let data = result.data.unwrap();
+        assert_eq!(data["stdout"], "out");
+        assert_eq!(data["stderr"], "err");
+        assert_eq!(data["exit_code"], 3);
+        assert_eq!(data["timed_out"], false);
+    }
+
+    #[tokio::test]
+    async fn test_stderr_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut session = ShellSession::new(DEFAULT_COMMAND_TIMEOUT);
+        session.start().await.unwrap();
+        let dir = session.stderr_dir.as_ref().unwrap().path().to_path_buf();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        let output = session
+            .run(
+                "echo oops >&2",
+                &RunOptions::default(),
+                &ToolProgress::disabled(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(output.stderr, "oops");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        session.stop();
+        assert!(!dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_cwd_and_env_apply_to_one_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path().canonicalize().unwrap();
+        let tool = BashTool::new();
+        let result = tool
+            .execute(bash_call(json!({
+                "command": "pwd; echo \"$GREETING\"",
+                "cwd": cwd,
+                "env": {"GREETING": "it's here"}
+            })))
+            .await
+            .unwrap();
+        assert_eq!(result.content, format!("{}\nit's here", cwd.display()));
+
+        let result = tool
+            .execute(bash_call(json!({"command": "pwd; echo \"[$GREETING]\""})))
+            .await
+            .unwrap();
+        assert!(!result.content.starts_with(&cwd.display().to_string()));
+        assert!(result.content.ends_with("[]"));
+    }
+
+    #[test]
+    fn test_run_options() {
+        let options = run_options(&bash_call(json!({
+            "command": "ls",
+            "timeout_ms": 1500,
+            "env": {"RUST_LOG": "debug"}
+        })))
+        .unwrap();
+        assert_eq!(options.timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(options.env["RUST_LOG"], "debug");
+
+        let invalid = [
+            (json!({"timeout_ms": 0}), "must be greater than 0"),
+            (json!({"cwd": "/does/not/exist"}), "is not a directory"),
+            (json!({"cwd": "src"}), "must be an absolute path"),
+            (
+                json!({"env": {"1BAD": "x"}}),
+                "Invalid environment variable name",
+            ),
+            (
+                json!({"env": {"A=B": "x"}}),
+                "Invalid environment variable name",
+            ),
+        ];
+        for (parameters, error) in invalid {
+            assert!(run_options(&bash_call(parameters))
+                .unwrap_err()
+                .contains(error));
+        }
+    }
+
+    #[test]
+    fn test_cmd_escape() {
+        assert_eq!(cmd_escape("plain text"), "plain text");
+        assert_eq!(cmd_escape("a&b|c\"d"), "a^&b^|c^\"d");
+        assert_eq!(cmd_escape("100%PATH%"), "100%^PATH%");
+        assert_eq!(cmd_escape("%%>"), "%^%^>");
+    }
+}
@@ -0,0 +1,487 @@
+//! Background jobs of the bash tool
+//!
+//! Commands run with `run_in_background` are started outside the shell
+//! session in their own process group, so the agent can keep working while a
+//! dev server, watcher or long build runs. Their combined stdout and stderr is
+//! buffered and read in increments. Jobs are killed when the tool that
+//! started them is dropped, which happens when its agent is dropped.
+
+use crate::tools::bash::ShellConfig;
+use coro_core::error::Result;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, Command};
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+/// Bytes of output kept per job, older output is discarded
+const MAX_JOB_OUTPUT: usize = 1024 * 1024;
+
+/// Time a job gets to exit after SIGTERM before it is killed
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// Time the output of an exited job is drained before its status changes
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Job tables of all live bash tools, for listing jobs in the UI
+static JOB_TABLES: Mutex<Vec<Weak<JobTable>>> = Mutex::new(Vec::new());
+
+/// State of a background job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Exited(i32),
+    Killed,
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobStatus::Running => write!(f, "running"),
+            JobStatus::Exited(code) => write!(f, "exited with code {}", code),
+            JobStatus::Killed => write!(f, "killed"),
+        }
+    }
+}
+
+/// Summary of a background job
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub id: u32,
+    pub command: String,
+    pub status: JobStatus,
+    /// Time since the job was started
+    pub elapsed: Duration,
+    /// Bytes of output not read by the agent yet
+    pub unread: usize,
+}
+
+/// Output of a job, with older output dropped past [`MAX_JOB_OUTPUT`]
+#[derive(Debug, Default)]
+struct JobOutput {
+    text: String,
+    /// Bytes dropped from the front of `text`
+    discarded: usize,
+}
+
+impl JobOutput {
+    fn push(&mut self, chunk: &str) {
+        self.text.push_str(chunk);
+        if self.text.len() > MAX_JOB_OUTPUT {
+            let mut cut = self.text.len() - MAX_JOB_OUTPUT;
+            while !self.text.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.text.drain(..cut);
+            self.discarded += cut;
+        }
+    }
+
+    /// Total bytes written by the job
+    fn len(&self) -> usize {
+        self.discarded + self.text.len()
+    }
+}
+
+/// A command running in the background
+struct Job {
+    command: String,
+    pid: Option<u32>,
+    started: Instant,
+    status: Arc<Mutex<JobStatus>>,
+    output: Arc<Mutex<JobOutput>>,
+    stdin: Arc<tokio::sync::Mutex<Option<ChildStdin>>>,
+    /// Position in the output up to which the agent has read
+    read_offset: usize,
+}
+
+impl Job {
+    fn status(&self) -> JobStatus {
+        *self.status.lock().expect("job status lock poisoned")
+    }
+
+    fn info(&self, id: u32) -> JobInfo {
+        JobInfo {
+            id,
+            command: self.command.clone(),
+            status: self.status(),
+            elapsed: self.started.elapsed(),
+            unread: self.output.lock().expect("job output lock poisoned").len() - self.read_offset,
+        }
+    }
+}
+
+/// Jobs started by one bash tool
+#[derive(Default)]
+struct JobTable {
+    jobs: Mutex<BTreeMap<u32, Job>>,
+    next_id: AtomicU32,
+}
+
+impl Drop for JobTable {
+    fn drop(&mut self) {
+        let jobs = self.jobs.get_mut().expect("job table lock poisoned");
+        for job in jobs.values() {
+            if job.status() == JobStatus::Running {
+                if let Some(pid) = job.pid {
+                    signal_process_group(pid, true);
+                }
+            }
+        }
+    }
+}
+
+/// New output of a job, as returned by [`BackgroundJobs::read_output`]
+#[derive(Debug, Clone)]
+pub struct JobOutputChunk {
+    pub text: String,
+    pub status: JobStatus,
+    /// Bytes of unread output that were discarded before they were read
+    pub missed: usize,
+}
+
+/// Background jobs of a bash tool, killed when it is dropped
+pub struct BackgroundJobs {
+    table: Arc<JobTable>,
+}
+
+impl BackgroundJobs {
+    pub fn new() -> Self {
+        let table = Arc::new(JobTable::default());
+        let mut tables = JOB_TABLES.lock().expect("job tables lock poisoned");
+        tables.retain(|table| table.strong_count() > 0);
+        tables.push(Arc::downgrade(&table));
+        Self { table }
+    }
+
+    /// Start a command in the background, returning its job id
+    pub fn start(
+        &self,
+        command: &str,
+        shell: &ShellConfig,
+        cwd: Option<&Path>,
+        env: &BTreeMap<String, String>,
+    ) -> Result<u32> {
+        let mut cmd = Command::new(shell.program());
+        if cfg!(target_os = "windows") {
+            cmd.arg("/C");
+        } else {
+            cmd.arg("-c");
+        }
+        cmd.arg(command)
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        if let Some(cwd) = cwd {
+            cmd.current_dir(cwd);
+        }
+
+        // A process group of its own lets the job be killed with its children
+        #[cfg(unix)]
+        {
+            #[allow(unused_imports)]
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+
+        let mut child = cmd.spawn()?;
+        let pid = child.id();
+        let stdin = child.stdin.take();
+        let output = Arc::new(Mutex::new(JobOutput::default()));
+        let status = Arc::new(Mutex::new(JobStatus::Running));
+
+        let readers = [
+            child
+                .stdout
+                .take()
+                .map(|out| collect_output(out, output.clone())),
+            child
+                .stderr
+                .take()
+                .map(|err| collect_output(err, output.clone())),
+        ];
+        let waiter_status = status.clone();
+        tokio::spawn(async move {
+            // Without an exit code the job was ended by a signal
+            let exit = match child.wait().await {
+                Ok(exit) => exit.code().map_or(JobStatus::Killed, JobStatus::Exited),
+                Err(_) => JobStatus::Exited(-1),
+            };
+            // Let the readers drain what the job wrote before it exited, unless
+            // a process it left behind keeps the pipes open
+            for reader in readers.into_iter().flatten() {
+                let _ = timeout(OUTPUT_DRAIN_TIMEOUT, reader).await;
+            }
+            let mut status = waiter_status.lock().expect("job status lock poisoned");
+            if *status == JobStatus::Running {
+                *status = exit;
+            }
+        });
+
+        let id = self.table.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = Job {
+            command: command.to_string(),
+            pid,
+            started: Instant::now(),
+            status,
+            output,
+            stdin: Arc::new(tokio::sync::Mutex::new(stdin)),
+            read_offset: 0,
+        };
+        self.lock().insert(id, job);
+        Ok(id)
+    }
+
+    /// Read the output written since the last read
+    pub fn read_output(&self, id: u32) -> Result<JobOutputChunk> {
+        let mut jobs = self.lock();
+        let job = jobs.get_mut(&id).ok_or_else(|| unknown_job(id))?;
+        let output = job.output.lock().expect("job output lock poisoned");
+
+        let missed = output.discarded.saturating_sub(job.read_offset);
+        let start = job.read_offset.max(output.discarded) - output.discarded;
+        let text = output.text[start..].to_string();
+        let end = output.len();
+        drop(output);
+
+        job.read_offset = end;
+        Ok(JobOutputChunk {
+            text,
+            status: job.status(),
+            missed,
+        })
+    }
+
+    /// Summary of a job
+    pub fn info(&self, id: u32) -> Result<JobInfo> {
+        let jobs = self.lock();
+        let job = jobs.get(&id).ok_or_else(|| unknown_job(id))?;
+        Ok(job.info(id))
+    }
+
+    /// Summaries of all jobs, oldest first
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.table.list()
+    }
+
+    /// Write text to the standard input of a running job
+    pub async fn send_input(&self, id: u32, input: &str) -> Result<()> {
+        let stdin = {
+            let jobs = self.lock();
+            let job = jobs.get(&id).ok_or_else(|| unknown_job(id))?;
+            if job.status() != JobStatus::Running {
+                return Err(format!("job {} is not running ({})", id, job.status()).into());
+            }
+            job.stdin.clone()
+        };
+
+        let mut stdin = stdin.lock().await;
+        let stdin = stdin
+            .as_mut()
+            .ok_or_else(|| format!("job {} has no standard input", id))?;
+        stdin.write_all(input.as_bytes()).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    /// Stop a job and its children, forcefully if it ignores SIGTERM
+    pub async fn kill(&self, id: u32) -> Result<JobStatus> {
+        let (pid, status) = {
+            let jobs = self.lock();
+            let job = jobs.get(&id).ok_or_else(|| unknown_job(id))?;
+            (job.pid, job.status.clone())
+        };
+        let current = *status.lock().expect("job status lock poisoned");
+        if current != JobStatus::Running {
+            return Ok(current);
+        }
+        let Some(pid) = pid else {
+            return Err(format!("job {} has no process id", id).into());
+        };
+
+        signal_process_group(pid, false);
+        let deadline = Instant::now() + KILL_GRACE_PERIOD;
+        let exited = loop {
+            if *status.lock().expect("job status lock poisoned") != JobStatus::Running {
+                break true;
+            }
+            if Instant::now() >= deadline {
+                break false;
+            }
+            sleep(Duration::from_millis(50)).await;
+        };
+        if !exited {
+            signal_process_group(pid, true);
+        }
+
+        // The job may have exited on its own before a signal reached it
+        let mut status = status.lock().expect("job status lock poisoned");
+        if *status == JobStatus::Running {
+            *status = JobStatus::Killed;
+        }
+        Ok(*status)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u32, Job>> {
+        self.table.jobs.lock().expect("job table lock poisoned")
+    }
+}
+
+impl Default for BackgroundJobs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobTable {
+    fn list(&self) -> Vec<JobInfo> {
+        let jobs = self.jobs.lock().expect("job table lock poisoned");
+        jobs.iter().map(|(id, job)| job.info(*id)).collect()
+    }
+}
+
+/// Jobs of every bash tool that is still alive
+pub fn all_jobs() -> Vec<JobInfo> {
+    let tables: Vec<Arc<JobTable>> = JOB_TABLES
+        .lock()
+        .expect("job tables lock poisoned")
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect();
+    tables.iter().flat_map(|table| table.list()).collect()
+}
+
+fn unknown_job(id: u32) -> coro_core::error::Error {
+    format!("no background job with id {}", id).into()
+}
+
+/// Append the lines of a job's output stream to its buffer
+fn collect_output(
+    stream: impl AsyncRead + Unpin + Send + 'static,
+    output: Arc<Mutex<JobOutput>>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(stream);
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => output
+                    .lock()
+                    .expect("job output lock poisoned")
+                    .push(&String::from_utf8_lossy(&buffer)),
+            }
+        }
+    })
+}
+
+/// Send SIGTERM, or SIGKILL when `force` is set, to a process group
+#[cfg(unix)]
+pub(crate) fn signal_process_group(pid: u32, force: bool) {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    // SAFETY: killpg only sends a signal, a stale pid makes it fail with ESRCH
+    unsafe {
+        libc::killpg(pid as libc::pid_t, signal);
+    }
+}
+
+/// Kill a process and its children, Windows has no SIGTERM to send first
+#[cfg(not(unix))]
+pub(crate) fn signal_process_group(pid: u32, _force: bool) {
+    // taskkill is not waited for, so callers in async code are not blocked
+    let _ = std::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    async fn wait_for_exit(jobs: &BackgroundJobs, id: u32) -> JobStatus {
+        for _ in 0..100 {
+            let status = jobs.info(id).unwrap().status;
+            if status != JobStatus::Running {
+                return status;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+        panic!("job {} did not exit", id);
+    }
+
+    #[tokio::test]
+    async fn test_job_input_and_incremental_output() {
+        let jobs = BackgroundJobs::new();
+        let id = jobs
+            .start(
+                "echo ready; read line; echo \"got $line\"",
+                &ShellConfig::new(),
+                None,
+                &BTreeMap::new(),
+            )
+            .unwrap();
+        assert!(all_jobs()
+            .iter()
+            .any(|job| job.command.starts_with("echo ready")));
+
+        jobs.send_input(id, "hello\n").await.unwrap();
+        assert_eq!(wait_for_exit(&jobs, id).await, JobStatus::Exited(0));
+
+        let chunk = jobs.read_output(id).unwrap();
+        assert_eq!(chunk.text, "ready\ngot hello\n");
+        // Output is only returned once
+        assert_eq!(jobs.read_output(id).unwrap().text, "");
+        assert!(jobs.send_input(id, "again\n").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_kill_job() {
+        let jobs = BackgroundJobs::new();
+        let id = jobs
+            .start("sleep 30", &ShellConfig::new(), None, &BTreeMap::new())
+            .unwrap();
+        assert_eq!(jobs.info(id).unwrap().status, JobStatus::Running);
+
+        assert_eq!(jobs.kill(id).await.unwrap(), JobStatus::Killed);
+        assert_eq!(jobs.info(id).unwrap().status, JobStatus::Killed);
+        assert!(jobs.read_output(id + 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_kill_keeps_the_exit_status_of_a_job_that_handles_sigterm() {
+        let jobs = BackgroundJobs::new();
+        let id = jobs
+            .start(
+                "trap 'exit 5' TERM; echo ready; while true; do sleep 0.1; done",
+                &ShellConfig::new(),
+                None,
+                &BTreeMap::new(),
+            )
+            .unwrap();
+        while jobs.read_output(id).unwrap().text.is_empty() {
+            sleep(Duration::from_millis(20)).await;
+        }
+
+        assert_eq!(jobs.kill(id).await.unwrap(), JobStatus::Exited(5));
+        assert_eq!(jobs.info(id).unwrap().status, JobStatus::Exited(5));
+    }
+
+    #[test]
+    fn test_output_keeps_the_latest_bytes() {
+        let mut output = JobOutput::default();
+        output.push(&"a".repeat(MAX_JOB_OUTPUT));
+        output.push("bc");
+        assert_eq!(output.discarded, 2);
+        assert_eq!(output.len(), MAX_JOB_OUTPUT + 2);
+        assert!(output.text.ends_with("abc"));
+    }
+}
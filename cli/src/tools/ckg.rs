@@ -2,13 +2,12 @@
 
 use async_trait::async_trait;
 use coro_core::error::Result;
-use coro_core::impl_tool_factory;
 use coro_core::tools::utils::validate_absolute_path;
-use coro_core::tools::{Tool, ToolCall, ToolExample, ToolResult};
+use coro_core::tools::{Tool, ToolCall, ToolExample, ToolFactory, ToolResult};
 use rusqlite::{params, Connection};
 use serde_json::json;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tree_sitter::{Language, Parser, Tree};
 use walkdir::WalkDir;
@@ -351,15 +350,36 @@ impl CkgDatabase {
     }
 }
 
+/// Database used when neither the call nor the config names one
+pub const DEFAULT_DB_PATH: &str = "./ckg.db";
+
+/// Number of symbols and of files in an existing database, opened read-only
+pub fn database_stats(db_path: &Path) -> Result<(i64, i64)> {
+    let conn = Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let stats = conn.query_row(
+        "SELECT COUNT(*), COUNT(DISTINCT file_path) FROM symbols",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    Ok(stats)
+}
+
 /// Tool for Code Knowledge Graph operations
 pub struct CkgTool {
     database: Arc<Mutex<Option<CkgDatabase>>>,
+    default_db_path: PathBuf,
 }
 
 impl CkgTool {
     pub fn new() -> Self {
+        Self::with_db_path(PathBuf::from(DEFAULT_DB_PATH))
+    }
+
+    /// Create a tool using `db_path` when a call does not name a database
+    pub fn with_db_path(db_path: PathBuf) -> Self {
         Self {
             database: Arc::new(Mutex::new(None)),
+            default_db_path: db_path,
         }
     }
 }
@@ -414,7 +434,7 @@ impl Tool for CkgTool {
                 },
                 "db_path": {
                     "type": "string",
-                    "description": "Path to the SQLite database file. Defaults to './ckg.db'"
+                    "description": "Path to the SQLite database file. Defaults to the configured database or './ckg.db'"
                 },
                 "recursive": {
                     "type": "boolean",
@@ -432,7 +452,10 @@ impl Tool for CkgTool {
 
     async fn execute(&self, call: ToolCall) -> Result<ToolResult> {
         let operation: String = call.get_parameter("operation")?;
-        let db_path: String = call.get_parameter_or("db_path", "./ckg.db".to_string());
+        let db_path: PathBuf = call
+            .get_parameter::<String>("db_path")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| self.default_db_path.clone());
 
         // Initialize database if needed
         {
@@ -441,7 +464,7 @@ impl Tool for CkgTool {
                 .lock()
                 .map_err(|_| "Failed to acquire database lock")?;
             if db_guard.is_none() {
-                *db_guard = Some(CkgDatabase::new(Path::new(&db_path))?);
+                *db_guard = Some(CkgDatabase::new(&db_path)?);
             }
         }
 
@@ -599,12 +622,9 @@ impl CkgTool {
 
         if !errors.is_empty() {
             result.push_str(&format!("\n\nErrors encountered ({}):\n", errors.len()));
-            for (i, error) in errors.iter().take(10).enumerate() {
+            for (i, error) in errors.iter().enumerate() {
                 result.push_str(&format!("{}. {}\n", i + 1, error));
             }
-            if errors.len() > 10 {
-                result.push_str(&format!("... and {} more errors\n", errors.len() - 10));
-            }
         }
 
         Ok(ToolResult::success(call_id, &result))
@@ -648,7 +668,7 @@ impl CkgTool {
             query
         );
 
-        for (i, symbol) in symbols.iter().take(50).enumerate() {
+        for (i, symbol) in symbols.iter().enumerate() {
             result.push_str(&format!(
                 "{}. {} ({})\n   File: {}:{}:{}\n   Type: {}\n",
                 i + 1,
@@ -666,10 +686,6 @@ impl CkgTool {
             result.push('\n');
         }
 
-        if symbols.len() > 50 {
-            result.push_str(&format!("... and {} more symbols\n", symbols.len() - 50));
-        }
-
         Ok(ToolResult::success(call_id, &result))
     }
 
@@ -714,15 +730,12 @@ impl CkgTool {
 
         for (symbol_type, symbols_of_type) in symbol_types {
             result.push_str(&format!("{}s ({}):\n", symbol_type, symbols_of_type.len()));
-            for symbol in symbols_of_type.iter().take(20) {
+            for symbol in symbols_of_type {
                 result.push_str(&format!(
                     "  - {} (lines {}-{})\n",
                     symbol.name, symbol.start_line, symbol.end_line
                 ));
             }
-            if symbols_of_type.len() > 20 {
-                result.push_str(&format!("  ... and {} more\n", symbols_of_type.len() - 20));
-            }
             result.push('\n');
         }
 
@@ -788,9 +801,32 @@ impl CkgTool {
     }
 }
 
-impl_tool_factory!(
-    CkgToolFactory,
-    CkgTool,
-    "ckg_tool",
-    "Code Knowledge Graph tool for analyzing and querying code structure"
-);
+/// Factory for creating CkgTool instances
+#[derive(Default)]
+pub struct CkgToolFactory {
+    db_path: Option<PathBuf>,
+}
+
+impl CkgToolFactory {
+    /// Create tools using `db_path` as the default database
+    pub fn with_db_path(db_path: Option<PathBuf>) -> Self {
+        Self { db_path }
+    }
+}
+
+impl ToolFactory for CkgToolFactory {
+    fn create(&self) -> Box<dyn Tool> {
+        match &self.db_path {
+            Some(db_path) => Box::new(CkgTool::with_db_path(db_path.clone())),
+            None => Box::new(CkgTool::new()),
+        }
+    }
+
+    fn tool_name(&self) -> &str {
+        "ckg_tool"
+    }
+
+    fn tool_description(&self) -> &str {
+        "Code Knowledge Graph tool for analyzing and querying code structure"
+    }
+}
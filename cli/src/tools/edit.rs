@@ -2,27 +2,186 @@
 
 use async_trait::async_trait;
 use coro_core::error::Result;
-use coro_core::impl_tool_factory;
 use coro_core::tools::utils::{
     check_file_exists, create_edit_snippet, expand_tabs, format_with_line_numbers, maybe_truncate,
     run_command, validate_absolute_path, validate_directory_operation,
 };
-use coro_core::tools::{Tool, ToolCall, ToolExample, ToolResult};
+use coro_core::tools::{Tool, ToolCall, ToolExample, ToolFactory, ToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::path::Path;
+use similar::TextDiff;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 /// Number of lines to show in snippets
 const SNIPPET_LINES: usize = 4;
 
 /// Available edit tool commands
-const EDIT_TOOL_COMMANDS: &[&str] = &["view", "create", "str_replace", "insert"];
+const EDIT_TOOL_COMMANDS: &[&str] = &[
+    "view",
+    "create",
+    "str_replace",
+    "insert",
+    "multi_edit",
+    "batch_edit",
+];
+
+/// Lines of context around each hunk of the diffs in edit results
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// One replacement of a `multi_edit` or `batch_edit` command
+#[derive(Debug, Clone, Deserialize)]
+struct Replacement {
+    old_str: String,
+    #[serde(default)]
+    new_str: String,
+    /// Replace every occurrence instead of requiring a unique match
+    #[serde(default)]
+    replace_all: bool,
+    /// Number of occurrences `old_str` must have, all of which are replaced
+    expected_occurrences: Option<usize>,
+}
+
+/// Replacements for one file of a `batch_edit` command
+#[derive(Debug, Deserialize)]
+struct FileEdits {
+    path: String,
+    edits: Vec<Replacement>,
+}
+
+/// A file with its replacements applied in memory, not yet written
+struct PreparedEdit {
+    path: PathBuf,
+    original: String,
+    updated: String,
+    replacements: usize,
+}
+
+/// Whether files must be viewed before `str_replace`, `insert`,
+/// `multi_edit` and `batch_edit` change them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadPolicy {
+    /// Files must have been viewed and not changed on disk since
+    #[default]
+    Strict,
+    /// Files that were viewed must not have changed on disk since, files
+    /// never viewed can be edited
+    Modified,
+    /// Files are edited without checks
+    Off,
+}
+
+/// A file as the tool last viewed or wrote it
+#[derive(Debug, Clone, PartialEq)]
+struct FileSnapshot {
+    hash: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileSnapshot {
+    fn new(path: &Path, content: &str) -> Self {
+        Self {
+            hash: content_hash(content),
+            modified: modified_time(path),
+        }
+    }
+}
+
+/// Files viewed or written, by canonical path
+///
+/// Clones share the same files, so a file shown by the `read` tool can be
+/// edited as if it had been viewed.
+#[derive(Debug, Clone, Default)]
+pub struct ReadTracker {
+    snapshots: Arc<Mutex<HashMap<PathBuf, FileSnapshot>>>,
+}
+
+impl ReadTracker {
+    /// Remember the content of a file the model has seen or a tool wrote
+    pub fn record(&self, path: &Path, content: &str) {
+        let snapshot = FileSnapshot::new(path, content);
+        self.snapshots
+            .lock()
+            .expect("snapshot lock poisoned")
+            .insert(snapshot_key(path), snapshot);
+    }
+
+    /// Forget a file a tool deleted or moved away
+    pub fn forget(&self, path: &Path) {
+        self.snapshots
+            .lock()
+            .expect("snapshot lock poisoned")
+            .remove(&snapshot_key(path));
+    }
+
+    /// Check that a file may be changed under `policy`
+    pub fn check(&self, path: &Path, policy: ReadPolicy) -> std::result::Result<(), String> {
+        if policy == ReadPolicy::Off {
+            return Ok(());
+        }
+
+        let key = snapshot_key(path);
+        let mut snapshots = self.snapshots.lock().expect("snapshot lock poisoned");
+        let Some(snapshot) = snapshots.get_mut(&key) else {
+            if policy == ReadPolicy::Strict {
+                return Err(format!(
+                    "{} has not been viewed yet. View it with the `view` command before editing it, so the edit is based on its current content.",
+                    path.display()
+                ));
+            }
+            return Ok(());
+        };
+
+        // An unchanged modification time saves reading the file again
+        let modified = modified_time(path);
+        if modified.is_some() && modified == snapshot.modified {
+            return Ok(());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Ran into {} while trying to read {}", e, path.display()))?;
+        if content_hash(&content) != snapshot.hash {
+            return Err(format!(
+                "{} has changed on disk since it was last viewed, possibly edited by the user or another program. View it again before editing it.",
+                path.display()
+            ));
+        }
+        // Touched but not changed
+        snapshot.modified = modified;
+        Ok(())
+    }
+}
 
 /// Tool for editing files with comprehensive functionality
-pub struct EditTool;
+pub struct EditTool {
+    max_output_len: Option<usize>,
+    read_policy: ReadPolicy,
+    tracker: ReadTracker,
+}
 
 impl EditTool {
     pub fn new() -> Self {
-        Self
+        Self::with_options(None, ReadPolicy::default(), ReadTracker::default())
+    }
+
+    /// Create a tool that truncates viewed files after `max_output_len` bytes
+    /// and checks edited files according to `read_policy` against the files
+    /// recorded by `tracker`
+    pub fn with_options(
+        max_output_len: Option<usize>,
+        read_policy: ReadPolicy,
+        tracker: ReadTracker,
+    ) -> Self {
+        Self {
+            max_output_len,
+            read_policy,
+            tracker,
+        }
     }
 }
 
@@ -47,7 +206,15 @@ impl Tool for EditTool {
          Notes for using the `str_replace` command:\n\
          * The `old_str` parameter should match EXACTLY one or more consecutive lines from the original file. Be mindful of whitespaces!\n\
          * If the `old_str` parameter is not unique in the file, the replacement will not be performed. Make sure to include enough context in `old_str` to make it unique\n\
-         * The `new_str` parameter should contain the edited lines that should replace the `old_str`"
+         * The `new_str` parameter should contain the edited lines that should replace the `old_str`\n\
+         \n\
+         Notes for using the `multi_edit` and `batch_edit` commands:\n\
+         * `multi_edit` applies the `edits` list to `path` in order, each edit seeing the result of the previous ones. If any edit fails, the file is left unchanged\n\
+         * `batch_edit` takes `files`, a list of `{path, edits}` objects, and checks every edit before writing. If any edit fails, no file is changed\n\
+         * An edit replaces a unique `old_str` unless `replace_all` is true or `expected_occurrences` gives the exact number of occurrences to replace\n\
+         * The result contains a unified diff of the changes\n\
+         \n\
+         A file must be viewed with `view` or the `read` tool before it is edited. If it changes on disk after that, for example because the user edited it, view it again before editing it."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -56,12 +223,12 @@ impl Tool for EditTool {
             "properties": {
                 "command": {
                     "type": "string",
-                    "enum": ["view", "create", "str_replace", "insert"],
-                    "description": "The commands to run. Allowed options are: view, create, str_replace, insert."
+                    "enum": ["view", "create", "str_replace", "insert", "multi_edit", "batch_edit"],
+                    "description": "The commands to run. Allowed options are: view, create, str_replace, insert, multi_edit, batch_edit."
                 },
                 "path": {
                     "type": "string",
-                    "description": "Absolute path to file or directory, e.g. `/repo/file.py` or `/repo`."
+                    "description": "Absolute path to file or directory, e.g. `/repo/file.py` or `/repo`. Not used by `batch_edit`."
                 },
                 "file_text": {
                     "type": "string",
@@ -83,14 +250,45 @@ impl Tool for EditTool {
                     "type": "array",
                     "items": {"type": "integer"},
                     "description": "Optional parameter of `view` command when `path` points to a file. If none is given, the full file is shown. If provided, the file will be shown in the indicated line number range, e.g. [11, 12] will show lines 11 and 12. Indexing at 1 to start. Setting `[start_line, -1]` shows all lines from `start_line` to the end of the file."
+                },
+                "edits": {
+                    "type": "array",
+                    "items": replacement_schema(),
+                    "description": "Required parameter of `multi_edit` command. Replacements applied to `path` in order."
+                },
+                "files": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to the file."
+                            },
+                            "edits": {
+                                "type": "array",
+                                "items": replacement_schema(),
+                                "description": "Replacements applied to the file in order."
+                            }
+                        },
+                        "required": ["path", "edits"]
+                    },
+                    "description": "Required parameter of `batch_edit` command. Files to edit, each listed once."
                 }
             },
-            "required": ["command", "path"]
+            "required": ["command"]
         })
     }
 
     async fn execute(&self, call: ToolCall) -> Result<ToolResult> {
         let command: String = call.get_parameter("command")?;
+        if command == "batch_edit" {
+            let files: Vec<FileEdits> = call.get_parameter("files").map_err(|_| {
+                "Parameter `files` is required and must be a list of {path, edits} objects for command: batch_edit"
+            })?;
+            return self.batch_edit_handler(&call.id, &files).await;
+        }
+
         let path_str: String = call.get_parameter("path")?;
         let path = Path::new(&path_str);
 
@@ -127,6 +325,12 @@ impl Tool for EditTool {
                 self.insert_handler(&call.id, path, insert_line, &new_str)
                     .await
             }
+            "multi_edit" => {
+                let edits: Vec<Replacement> = call.get_parameter("edits").map_err(|_| {
+                    "Parameter `edits` is required and must be a list of {old_str, new_str} objects for command: multi_edit"
+                })?;
+                self.multi_edit_handler(&call.id, path, &edits).await
+            }
             _ => Ok(ToolResult::error(
                 &call.id,
                 &format!(
@@ -180,6 +384,35 @@ impl Tool for EditTool {
                 }),
                 expected_result: "Text inserted with snippet showing changes".to_string(),
             },
+            ToolExample {
+                description: "Apply several replacements to a file at once".to_string(),
+                parameters: json!({
+                    "command": "multi_edit",
+                    "path": "/repo/src/main.rs",
+                    "edits": [
+                        {"old_str": "fn run()", "new_str": "fn run(verbose: bool)"},
+                        {"old_str": "run()", "new_str": "run(false)", "replace_all": true}
+                    ]
+                }),
+                expected_result: "All replacements applied with a unified diff".to_string(),
+            },
+            ToolExample {
+                description: "Rename a function across files".to_string(),
+                parameters: json!({
+                    "command": "batch_edit",
+                    "files": [
+                        {
+                            "path": "/repo/src/lib.rs",
+                            "edits": [{"old_str": "pub fn load(", "new_str": "pub fn load_config("}]
+                        },
+                        {
+                            "path": "/repo/src/main.rs",
+                            "edits": [{"old_str": "load(", "new_str": "load_config(", "expected_occurrences": 2}]
+                        }
+                    ]
+                }),
+                expected_result: "Both files edited, or neither if an edit fails".to_string(),
+            },
         ]
     }
 }
@@ -238,6 +471,7 @@ impl EditTool {
         view_range: Option<Vec<i32>>,
     ) -> Result<ToolResult> {
         let file_content = self.read_file(path)?;
+        self.record_snapshot(path, &file_content);
         let init_line = 1;
 
         let content_to_show = if let Some(range) = view_range {
@@ -301,6 +535,7 @@ impl EditTool {
         file_text: &str,
     ) -> Result<ToolResult> {
         self.write_file(path, file_text)?;
+        self.record_snapshot(path, file_text);
         Ok(ToolResult::success(
             call_id,
             &format!("File created successfully at: {}", path.display()),
@@ -315,6 +550,9 @@ impl EditTool {
         old_str: &str,
         new_str: Option<&str>,
     ) -> Result<ToolResult> {
+        if let Err(e) = self.check_snapshot(path) {
+            return Ok(ToolResult::error(call_id, &e));
+        }
         let file_content = expand_tabs(&self.read_file(path)?);
         let old_str_expanded = expand_tabs(old_str);
         let new_str_expanded = new_str.map(expand_tabs).unwrap_or_default();
@@ -352,6 +590,7 @@ impl EditTool {
         // Replace old_str with new_str
         let new_file_content = file_content.replace(&old_str_expanded, &new_str_expanded);
         self.write_file(path, &new_file_content)?;
+        self.record_snapshot(path, &new_file_content);
 
         // Create a snippet of the edited section
         let replacement_line = file_content
@@ -379,6 +618,9 @@ impl EditTool {
         insert_line: i32,
         new_str: &str,
     ) -> Result<ToolResult> {
+        if let Err(e) = self.check_snapshot(path) {
+            return Ok(ToolResult::error(call_id, &e));
+        }
         let file_text = expand_tabs(&self.read_file(path)?);
         let new_str_expanded = expand_tabs(new_str);
         let mut file_text_lines: Vec<&str> = file_text.lines().collect();
@@ -401,6 +643,7 @@ impl EditTool {
 
         let new_file_text = file_text_lines.join("\n");
         self.write_file(path, &new_file_text)?;
+        self.record_snapshot(path, &new_file_text);
 
         let snippet = create_edit_snippet(&new_file_text, insert_idx, SNIPPET_LINES);
         let success_msg = format!(
@@ -412,6 +655,162 @@ impl EditTool {
         Ok(ToolResult::success(call_id, &success_msg))
     }
 
+    /// Handle multi_edit command
+    async fn multi_edit_handler(
+        &self,
+        call_id: &str,
+        path: &Path,
+        edits: &[Replacement],
+    ) -> Result<ToolResult> {
+        let prepared = match self.prepare_edits(path, edits) {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                return Ok(ToolResult::error(
+                    call_id,
+                    &format!("No changes were made. {}", e),
+                ))
+            }
+        };
+        self.write_file(path, &prepared.updated)?;
+        self.record_snapshot(path, &prepared.updated);
+        Ok(self.edit_result(call_id, &[prepared]))
+    }
+
+    /// Handle batch_edit command
+    ///
+    /// Every file is read and edited in memory before the first one is
+    /// written, so a failing edit leaves all files unchanged.
+    async fn batch_edit_handler(&self, call_id: &str, files: &[FileEdits]) -> Result<ToolResult> {
+        if files.is_empty() {
+            return Ok(ToolResult::error(
+                call_id,
+                "Parameter `files` must list at least one file for command: batch_edit",
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        let mut prepared = Vec::new();
+        let mut errors = Vec::new();
+        for file in files {
+            let path = Path::new(&file.path);
+            if !seen.insert(path) {
+                errors.push(format!(
+                    "{} is listed more than once, combine its edits into one entry",
+                    file.path
+                ));
+                continue;
+            }
+            let result = self
+                .validate_path("batch_edit", path)
+                .map_err(|e| e.to_string())
+                .and_then(|_| self.prepare_edits(path, &file.edits));
+            match result {
+                Ok(edit) => prepared.push(edit),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Ok(ToolResult::error(
+                call_id,
+                &format!("No files were changed.\n{}", errors.join("\n")),
+            ));
+        }
+
+        for (written, edit) in prepared.iter().enumerate() {
+            if let Err(e) = self.write_file(&edit.path, &edit.updated) {
+                // Put back the files written so far
+                for done in &prepared[..written] {
+                    let _ = self.write_file(&done.path, &done.original);
+                }
+                return Ok(ToolResult::error(
+                    call_id,
+                    &format!("No files were changed. {}", e),
+                ));
+            }
+        }
+        for edit in &prepared {
+            self.record_snapshot(&edit.path, &edit.updated);
+        }
+
+        Ok(self.edit_result(call_id, &prepared))
+    }
+
+    /// Read a file and apply replacements to its content in memory
+    fn prepare_edits(
+        &self,
+        path: &Path,
+        edits: &[Replacement],
+    ) -> std::result::Result<PreparedEdit, String> {
+        self.check_snapshot(path)?;
+        let original = self.read_file(path).map_err(|e| e.to_string())?;
+        let (updated, replacements) = apply_replacements(&original, edits)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(PreparedEdit {
+            path: path.to_path_buf(),
+            original,
+            updated,
+            replacements,
+        })
+    }
+
+    /// Build the result of `multi_edit` and `batch_edit` with a diff per file
+    fn edit_result(&self, call_id: &str, edits: &[PreparedEdit]) -> ToolResult {
+        let diffs: Vec<String> = edits
+            .iter()
+            .map(|edit| {
+                let path = edit.path.display().to_string();
+                TextDiff::from_lines(&edit.original, &edit.updated)
+                    .unified_diff()
+                    .context_radius(DIFF_CONTEXT_LINES)
+                    .header(&path, &path)
+                    .to_string()
+            })
+            .collect();
+        let replacements: usize = edits.iter().map(|edit| edit.replacements).sum();
+
+        let summary = match edits {
+            [edit] => format!(
+                "The file {} has been edited with {} replacement(s).",
+                edit.path.display(),
+                replacements
+            ),
+            _ => format!(
+                "{} files have been edited with {} replacement(s).",
+                edits.len(),
+                replacements
+            ),
+        };
+        let content = format!(
+            "{}\n{}\nReview the changes and make sure they are as expected. Edit the files again if necessary.",
+            summary,
+            maybe_truncate(&diffs.concat(), self.max_output_len)
+        );
+
+        let files: Vec<_> = edits
+            .iter()
+            .zip(&diffs)
+            .map(|(edit, diff)| {
+                json!({
+                    "path": edit.path.display().to_string(),
+                    "replacements": edit.replacements,
+                    "diff": diff,
+                })
+            })
+            .collect();
+        ToolResult::success(call_id, &content).with_data(json!({ "files": files }))
+    }
+
+    /// Remember the content of a file the model has seen or the tool wrote
+    fn record_snapshot(&self, path: &Path, content: &str) {
+        self.tracker.record(path, content);
+    }
+
+    /// Check that a file may be edited under the read policy
+    fn check_snapshot(&self, path: &Path) -> std::result::Result<(), String> {
+        self.tracker.check(path, self.read_policy)
+    }
+
     /// Read file content
     fn read_file(&self, path: &Path) -> Result<String> {
         std::fs::read_to_string(path)
@@ -437,7 +836,7 @@ impl EditTool {
 
     /// Generate output for the CLI based on the content of a file
     fn make_output(&self, file_content: &str, file_descriptor: &str, init_line: i32) -> String {
-        let truncated_content = maybe_truncate(file_content, None);
+        let truncated_content = maybe_truncate(file_content, self.max_output_len);
         let formatted_content = format_with_line_numbers(&truncated_content, init_line as usize);
         format!(
             "Here's the result of running `cat -n` on {}:\n{}\n",
@@ -446,15 +845,274 @@ impl EditTool {
     }
 }
 
+/// Path under which the snapshot of a file is kept
+fn snapshot_key(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// JSON schema of a `multi_edit` or `batch_edit` replacement
+fn replacement_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "old_str": {
+                "type": "string",
+                "description": "The exact text to replace."
+            },
+            "new_str": {
+                "type": "string",
+                "description": "The replacement text, empty to delete `old_str`."
+            },
+            "replace_all": {
+                "type": "boolean",
+                "description": "Replace every occurrence of `old_str`. Defaults to false, which requires a unique match."
+            },
+            "expected_occurrences": {
+                "type": "integer",
+                "description": "Exact number of occurrences of `old_str`; all of them are replaced. The edit fails if the count differs."
+            }
+        },
+        "required": ["old_str", "new_str"]
+    })
+}
+
+/// Apply replacements in order, returning the new content and the number of
+/// replaced occurrences
+fn apply_replacements(
+    content: &str,
+    edits: &[Replacement],
+) -> std::result::Result<(String, usize), String> {
+    if edits.is_empty() {
+        return Err("no edits were given".to_string());
+    }
+
+    let mut content = content.to_string();
+    let mut replacements = 0;
+    for (index, edit) in edits.iter().enumerate() {
+        let number = index + 1;
+        if edit.old_str.is_empty() {
+            return Err(format!("edit {}: `old_str` must not be empty", number));
+        }
+
+        let occurrences = content.matches(&edit.old_str).count();
+        // Earlier edits have already been applied when a later one is matched
+        let after = if index > 0 {
+            " after applying the previous edits"
+        } else {
+            ""
+        };
+        if occurrences == 0 {
+            return Err(format!(
+                "edit {}: old_str `{}` did not appear verbatim{}",
+                number, edit.old_str, after
+            ));
+        }
+        match edit.expected_occurrences {
+            Some(expected) if expected != occurrences => {
+                return Err(format!(
+                    "edit {}: expected {} occurrence(s) of old_str `{}` but found {}{}",
+                    number, expected, edit.old_str, occurrences, after
+                ));
+            }
+            None if occurrences > 1 && !edit.replace_all => {
+                return Err(format!(
+                    "edit {}: old_str `{}` occurs {} times{}. Add context to make it unique, or set `replace_all` or `expected_occurrences`",
+                    number, edit.old_str, occurrences, after
+                ));
+            }
+            _ => {}
+        }
+
+        content = content.replace(&edit.old_str, &edit.new_str);
+        replacements += occurrences;
+    }
+    Ok((content, replacements))
+}
+
 impl Default for EditTool {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl_tool_factory!(
-    EditToolFactory,
-    EditTool,
-    "str_replace_based_edit_tool",
-    "Edit files by viewing, creating, or replacing text content"
-);
+/// Factory for creating EditTool instances
+#[derive(Default)]
+pub struct EditToolFactory {
+    max_output_len: Option<usize>,
+    read_policy: ReadPolicy,
+    tracker: ReadTracker,
+}
+
+impl EditToolFactory {
+    /// Create tools that truncate viewed files after `max_output_len` bytes
+    /// and check edited files according to `read_policy` against the files
+    /// recorded by `tracker`
+    pub fn with_options(
+        max_output_len: Option<usize>,
+        read_policy: ReadPolicy,
+        tracker: ReadTracker,
+    ) -> Self {
+        Self {
+            max_output_len,
+            read_policy,
+            tracker,
+        }
+    }
+}
+
+impl ToolFactory for EditToolFactory {
+    fn create(&self) -> Box<dyn Tool> {
+        Box::new(EditTool::with_options(
+            self.max_output_len,
+            self.read_policy,
+            self.tracker.clone(),
+        ))
+    }
+
+    fn tool_name(&self) -> &str {
+        "str_replace_based_edit_tool"
+    }
+
+    fn tool_description(&self) -> &str {
+        "Edit files by viewing, creating, or replacing text content"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn replacement(old_str: &str, new_str: &str) -> Replacement {
+        Replacement {
+            old_str: old_str.to_string(),
+            new_str: new_str.to_string(),
+            replace_all: false,
+            expected_occurrences: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_replacements() {
+        let content = "fn run() {}\nrun();\nrun();\n";
+        let edits = [
+            replacement("fn run() {}", "fn start() {}"),
+            Replacement {
+                expected_occurrences: Some(2),
+                ..replacement("run();", "start();")
+            },
+        ];
+        let (updated, count) = apply_replacements(content, &edits).unwrap();
+        assert_eq!(updated, "fn start() {}\nstart();\nstart();\n");
+        assert_eq!(count, 3);
+
+        let err = apply_replacements(content, &[replacement("run();", "start();")]).unwrap_err();
+        assert!(err.contains("occurs 2 times"));
+
+        // Later edits see the result of earlier ones
+        let edits = [
+            replacement("fn run", "fn start"),
+            replacement("fn run", "x"),
+        ];
+        let err = apply_replacements(content, &edits).unwrap_err();
+        assert!(err.starts_with("edit 2:"));
+        assert!(err.contains("after applying the previous edits"));
+
+        let edits = [Replacement {
+            expected_occurrences: Some(1),
+            ..replacement("run", "start")
+        }];
+        assert!(apply_replacements(content, &edits)
+            .unwrap_err()
+            .contains("expected 1 occurrence(s)"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_edit_is_all_or_nothing() {
+        let dir = TempDir::new().unwrap();
+        let lib = dir.path().join("lib.rs");
+        let main = dir.path().join("main.rs");
+        std::fs::write(&lib, "pub fn load() {}\n").unwrap();
+        std::fs::write(&main, "fn main() { load(); }\n").unwrap();
+        let tool = EditTool::new();
+        for path in [&lib, &main] {
+            let view = json!({"command": "view", "path": path});
+            tool.execute(ToolCall::new("str_replace_based_edit_tool", view))
+                .await
+                .unwrap();
+        }
+
+        let call = |main_old: &str| {
+            ToolCall::new(
+                "str_replace_based_edit_tool",
+                json!({
+                    "command": "batch_edit",
+                    "files": [
+                        {"path": lib, "edits": [{"old_str": "fn load(", "new_str": "fn load_config("}]},
+                        {"path": main, "edits": [{"old_str": main_old, "new_str": "load_config();"}]}
+                    ]
+                }),
+            )
+        };
+
+        let result = tool.execute(call("missing();")).await.unwrap();
+        assert!(!result.success);
+        assert!(result.content.contains("No files were changed"));
+        assert_eq!(std::fs::read_to_string(&lib).unwrap(), "pub fn load() {}\n");
+
+        let result = tool.execute(call("load();")).await.unwrap();
+        assert!(result.success, "{}", result.content);
+        assert_eq!(
+            std::fs::read_to_string(&lib).unwrap(),
+            "pub fn load_config() {}\n"
+        );
+        assert!(result.content.contains("+fn main() { load_config(); }"));
+        assert_eq!(result.data.unwrap()["files"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_edits_require_a_current_view() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "one\n").unwrap();
+        let tool = EditTool::new();
+        let call = |params: serde_json::Value| ToolCall::new("str_replace_based_edit_tool", params);
+        let replace = |old: &str, new: &str| {
+            call(json!({"command": "str_replace", "path": path, "old_str": old, "new_str": new}))
+        };
+
+        let result = tool.execute(replace("one", "two")).await.unwrap();
+        assert!(result.content.contains("has not been viewed yet"));
+
+        tool.execute(call(json!({"command": "view", "path": path})))
+            .await
+            .unwrap();
+        let result = tool.execute(replace("one", "two")).await.unwrap();
+        assert!(result.success, "{}", result.content);
+        // The tool's own write does not make the file stale
+        let result = tool.execute(replace("two", "three")).await.unwrap();
+        assert!(result.success, "{}", result.content);
+
+        std::fs::write(&path, "changed elsewhere\n").unwrap();
+        let result = tool.execute(replace("three", "four")).await.unwrap();
+        assert!(result.content.contains("has changed on disk"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "changed elsewhere\n"
+        );
+
+        let tool = EditTool::with_options(None, ReadPolicy::Off, ReadTracker::default());
+        let result = tool.execute(replace("elsewhere", "here")).await.unwrap();
+        assert!(result.success, "{}", result.content);
+    }
+}
@@ -0,0 +1,640 @@
+//! Content search tool backed by the `ignore` crate
+
+use async_trait::async_trait;
+use coro_core::error::Result;
+use coro_core::impl_tool_factory;
+use coro_core::tools::{Tool, ToolCall, ToolExample, ToolResult};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{WalkBuilder, WalkState};
+use regex::{Regex, RegexBuilder};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Results returned when the call sets no limit
+const DEFAULT_LIMIT: usize = 200;
+
+/// Matching lines collected before the search stops early
+const MAX_COLLECTED_MATCHES: usize = 20_000;
+
+/// Characters of a line shown before it is clipped
+const MAX_LINE_LEN: usize = 300;
+
+/// Bytes checked for NUL to detect binary files
+const BINARY_PROBE_LEN: usize = 8192;
+
+/// What the search returns
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputMode {
+    /// Matching lines with optional context
+    Content,
+    /// Paths of files with at least one match
+    FilesWithMatches,
+    /// Number of matching lines per file
+    Count,
+}
+
+impl OutputMode {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "content" => Some(Self::Content),
+            "files_with_matches" => Some(Self::FilesWithMatches),
+            "count" => Some(Self::Count),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Content => "content",
+            Self::FilesWithMatches => "files_with_matches",
+            Self::Count => "count",
+        }
+    }
+}
+
+/// Options of a search
+#[derive(Debug, Clone)]
+struct SearchConfig {
+    regex: Regex,
+    root: PathBuf,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    include_hidden: bool,
+    respect_gitignore: bool,
+    context_lines: usize,
+}
+
+/// A line of a file, either matching or surrounding a match
+#[derive(Debug, Clone)]
+struct Line {
+    line: usize,
+    text: String,
+    is_match: bool,
+}
+
+/// Matches found in one file
+#[derive(Debug, Clone)]
+struct FileMatches {
+    path: String,
+    count: usize,
+    lines: Vec<Line>,
+}
+
+/// Regex search over file contents
+pub struct GrepTool;
+
+impl GrepTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for GrepTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for GrepTool {
+    fn name(&self) -> &str {
+        "grep"
+    }
+
+    fn description(&self) -> &str {
+        "Search file contents with a regular expression\n\
+         * Respects .gitignore and skips hidden and binary files by default\n\
+         * Filter files with include/exclude globs, e.g. `*.rs` or `src/**/*.ts`\n\
+         * output_mode `content` shows matching lines as `path:line:text` (context lines use `-`),\n\
+           `files_with_matches` lists matching files, `count` shows matches per file\n\
+         * Results are limited (default 200); use offset to page through more\n\
+         * Prefer this tool over running grep or rg through bash"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Regular expression to search for (Rust regex syntax)."
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Absolute file or directory to search in (default: current directory)."
+                },
+                "include": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only search files matching these globs, e.g. [\"*.rs\", \"*.toml\"]."
+                },
+                "exclude": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Skip files matching these globs, e.g. [\"*_test.go\", \"vendor/**\"]."
+                },
+                "case_sensitive": {
+                    "type": "boolean",
+                    "description": "Whether matching is case sensitive (default: true).",
+                    "default": true
+                },
+                "context_lines": {
+                    "type": "integer",
+                    "description": "Lines of context shown before and after each match in content mode (default: 0).",
+                    "minimum": 0
+                },
+                "output_mode": {
+                    "type": "string",
+                    "enum": ["content", "files_with_matches", "count"],
+                    "description": "What to return (default: content).",
+                    "default": "content"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of matching lines (content) or files (other modes) to return (default: 200).",
+                    "minimum": 1
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Number of results to skip, for paging (default: 0).",
+                    "minimum": 0
+                },
+                "include_hidden": {
+                    "type": "boolean",
+                    "description": "Search hidden files and directories (default: false).",
+                    "default": false
+                },
+                "respect_gitignore": {
+                    "type": "boolean",
+                    "description": "Skip files ignored by .gitignore (default: true).",
+                    "default": true
+                }
+            },
+            "required": ["pattern"]
+        })
+    }
+
+    async fn execute(&self, call: ToolCall) -> Result<ToolResult> {
+        let pattern: String = call.get_parameter("pattern")?;
+        let path: String = call.get_parameter_or("path", ".".to_string());
+        let case_sensitive: bool = call.get_parameter_or("case_sensitive", true);
+        let output_mode: String = call.get_parameter_or("output_mode", "content".to_string());
+        let limit: usize = call.get_parameter_or("limit", DEFAULT_LIMIT).max(1);
+        let offset: usize = call.get_parameter_or("offset", 0);
+
+        let Some(output_mode) = OutputMode::parse(&output_mode) else {
+            return Ok(ToolResult::error(
+                &call.id,
+                &format!(
+                    "Invalid output_mode '{}', expected content, files_with_matches or count",
+                    output_mode
+                ),
+            ));
+        };
+
+        let regex = match RegexBuilder::new(&pattern)
+            .case_insensitive(!case_sensitive)
+            .build()
+        {
+            Ok(regex) => regex,
+            Err(e) => {
+                return Ok(ToolResult::error(
+                    &call.id,
+                    &format!("Invalid regular expression: {}", e),
+                ))
+            }
+        };
+
+        let root = Path::new(&path);
+        if !root.exists() {
+            return Ok(ToolResult::error(
+                &call.id,
+                &format!("Path does not exist: {}", root.display()),
+            ));
+        }
+        let root = if root.is_relative() {
+            std::env::current_dir()
+                .map_err(|e| format!("Cannot get current directory: {}", e))?
+                .join(root)
+        } else {
+            root.to_path_buf()
+        };
+
+        let config = SearchConfig {
+            regex,
+            root: root.clone(),
+            include: call.get_parameter_or("include", Vec::new()),
+            exclude: call.get_parameter_or("exclude", Vec::new()),
+            include_hidden: call.get_parameter_or("include_hidden", false),
+            respect_gitignore: call.get_parameter_or("respect_gitignore", true),
+            context_lines: if output_mode == OutputMode::Content {
+                call.get_parameter_or("context_lines", 0)
+            } else {
+                0
+            },
+        };
+
+        let search = tokio::task::spawn_blocking(move || search(&config))
+            .await
+            .map_err(|e| format!("Search task failed: {}", e))?;
+        let (files, complete) = match search {
+            Ok(result) => result,
+            Err(e) => return Ok(ToolResult::error(&call.id, &e)),
+        };
+
+        let total_matches: usize = files.iter().map(|file| file.count).sum();
+        let total_files = files.len();
+        if files.is_empty() {
+            return Ok(ToolResult::success(
+                &call.id,
+                &format!("No matches for '{}' in {}", pattern, root.display()),
+            )
+            .with_data(json!({
+                "pattern": pattern,
+                "path": root.display().to_string(),
+                "output_mode": output_mode.as_str(),
+                "total_matches": 0,
+                "total_files": 0,
+                "matches": []
+            })));
+        }
+
+        let (body, returned, data) = match output_mode {
+            OutputMode::Content => render_content(&files, offset, limit),
+            OutputMode::FilesWithMatches => {
+                let page: Vec<&FileMatches> = files.iter().skip(offset).take(limit).collect();
+                let paths: Vec<&str> = page.iter().map(|file| file.path.as_str()).collect();
+                (paths.join("\n"), page.len(), json!(paths))
+            }
+            OutputMode::Count => {
+                let page: Vec<&FileMatches> = files.iter().skip(offset).take(limit).collect();
+                let lines: Vec<String> = page
+                    .iter()
+                    .map(|file| format!("{}:{}", file.path, file.count))
+                    .collect();
+                let counts: Vec<_> = page
+                    .iter()
+                    .map(|file| json!({ "path": file.path, "count": file.count }))
+                    .collect();
+                (lines.join("\n"), page.len(), json!(counts))
+            }
+        };
+
+        let total_results = match output_mode {
+            OutputMode::Content => total_matches,
+            _ => total_files,
+        };
+        let truncated = offset + returned < total_results;
+
+        let mut content = format!(
+            "Found {} matches in {} files for '{}'{}:\n\n{}",
+            total_matches,
+            total_files,
+            pattern,
+            if complete {
+                ""
+            } else {
+                " (search stopped early)"
+            },
+            body
+        );
+        if truncated {
+            content.push_str(&format!(
+                "\n\n[Showing results {}-{} of {}. Use offset {} to see more, or narrow the search.]",
+                offset + 1,
+                offset + returned,
+                total_results,
+                offset + returned
+            ));
+        } else if returned == 0 {
+            content.push_str(&format!(
+                "No results at offset {} (total {})",
+                offset, total_results
+            ));
+        }
+
+        Ok(ToolResult::success(&call.id, &content).with_data(json!({
+            "pattern": pattern,
+            "path": root.display().to_string(),
+            "output_mode": output_mode.as_str(),
+            "total_matches": total_matches,
+            "total_files": total_files,
+            "offset": offset,
+            "returned": returned,
+            "truncated": truncated,
+            "complete": complete,
+            "matches": data
+        })))
+    }
+
+    fn examples(&self) -> Vec<ToolExample> {
+        vec![
+            ToolExample {
+                description: "Find where a function is defined in Rust files".to_string(),
+                parameters: json!({
+                    "pattern": "fn\\s+parse_config",
+                    "path": "/project",
+                    "include": ["*.rs"]
+                }),
+                expected_result: "Matching lines as path:line:text".to_string(),
+            },
+            ToolExample {
+                description: "List files mentioning TODO, ignoring tests".to_string(),
+                parameters: json!({
+                    "pattern": "TODO",
+                    "path": "/project",
+                    "exclude": ["tests/**"],
+                    "output_mode": "files_with_matches"
+                }),
+                expected_result: "Paths of the matching files".to_string(),
+            },
+            ToolExample {
+                description: "Show usages with two lines of context".to_string(),
+                parameters: json!({
+                    "pattern": "load_settings\\(",
+                    "path": "/project/src",
+                    "context_lines": 2
+                }),
+                expected_result: "Matching lines with surrounding context".to_string(),
+            },
+        ]
+    }
+}
+
+/// Search all files below the root in parallel, sorted by path
+///
+/// Returns whether the search covered every file; it stops once
+/// [`MAX_COLLECTED_MATCHES`] lines matched.
+fn search(config: &SearchConfig) -> std::result::Result<(Vec<FileMatches>, bool), String> {
+    // Include globs are matched separately, as whitelisting them in the walker
+    // would also bring back files ignored by .gitignore
+    let include = build_globs(&config.root, &config.include, "", "include")?;
+    let exclude = build_globs(&config.root, &config.exclude, "!", "exclude")?;
+
+    let walker = WalkBuilder::new(&config.root)
+        .hidden(!config.include_hidden)
+        .git_ignore(config.respect_gitignore)
+        .git_global(config.respect_gitignore)
+        .git_exclude(config.respect_gitignore)
+        .ignore(config.respect_gitignore)
+        .parents(config.respect_gitignore)
+        .require_git(false)
+        .overrides(exclude)
+        .build_parallel();
+
+    let results = Mutex::new(Vec::new());
+    let collected = AtomicUsize::new(0);
+    let stopped = AtomicBool::new(false);
+
+    walker.run(|| {
+        let results = &results;
+        let collected = &collected;
+        let stopped = &stopped;
+        let include = &include;
+        Box::new(move |entry| {
+            if stopped.load(Ordering::Relaxed) {
+                return WalkState::Quit;
+            }
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file())
+                || (!config.include.is_empty()
+                    && !include.matched(entry.path(), false).is_whitelist())
+            {
+                return WalkState::Continue;
+            }
+
+            if let Some(file) = search_file(entry.path(), config) {
+                let total = collected.fetch_add(file.count, Ordering::Relaxed) + file.count;
+                results.lock().unwrap_or_else(|e| e.into_inner()).push(file);
+                if total >= MAX_COLLECTED_MATCHES {
+                    stopped.store(true, Ordering::Relaxed);
+                    return WalkState::Quit;
+                }
+            }
+            WalkState::Continue
+        })
+    });
+
+    let mut files = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((files, !stopped.load(Ordering::Relaxed)))
+}
+
+/// Build a glob matcher, prefixing every glob with `prefix`
+fn build_globs(
+    root: &Path,
+    globs: &[String],
+    prefix: &str,
+    kind: &str,
+) -> std::result::Result<Override, String> {
+    let mut builder = OverrideBuilder::new(root);
+    for glob in globs {
+        builder
+            .add(&format!("{}{}", prefix, glob))
+            .map_err(|e| format!("Invalid {} glob '{}': {}", kind, glob, e))?;
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Invalid {} globs: {}", kind, e))
+}
+
+/// Search one file, skipping binary and unreadable files
+fn search_file(path: &Path, config: &SearchConfig) -> Option<FileMatches> {
+    let bytes = std::fs::read(path).ok()?;
+    if bytes[..bytes.len().min(BINARY_PROBE_LEN)].contains(&0) {
+        return None;
+    }
+    let content = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = content.lines().collect();
+
+    let matching: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| config.regex.is_match(line))
+        .map(|(index, _)| index)
+        .collect();
+    if matching.is_empty() {
+        return None;
+    }
+
+    // Matching lines plus their context, each line once
+    let mut shown = Vec::new();
+    for &index in &matching {
+        let start = index.saturating_sub(config.context_lines);
+        let end = (index + config.context_lines).min(lines.len() - 1);
+        for line in start..=end {
+            if shown.last().is_none_or(|&last| last < line) {
+                shown.push(line);
+            }
+        }
+    }
+
+    Some(FileMatches {
+        path: path.display().to_string(),
+        count: matching.len(),
+        lines: shown
+            .into_iter()
+            .map(|index| Line {
+                line: index + 1,
+                text: clip_line(lines[index]),
+                is_match: matching.binary_search(&index).is_ok(),
+            })
+            .collect(),
+    })
+}
+
+fn clip_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_LEN) {
+        Some((end, _)) => format!("{}… [line clipped]", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Render a page of matching lines with their context
+fn render_content(
+    files: &[FileMatches],
+    offset: usize,
+    limit: usize,
+) -> (String, usize, serde_json::Value) {
+    let mut output = Vec::new();
+    let mut data = Vec::new();
+    let mut seen = 0;
+    let mut returned = 0;
+
+    for file in files {
+        if returned >= limit {
+            break;
+        }
+        // Index in `file.lines` of every match, and the ones on this page
+        let matches: Vec<usize> = file
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.is_match)
+            .map(|(index, _)| index)
+            .collect();
+        let skip = offset.saturating_sub(seen).min(matches.len());
+        let take = (limit - returned).min(matches.len() - skip);
+        seen += matches.len();
+        if take == 0 {
+            continue;
+        }
+        returned += take;
+        let (first, last) = (matches[skip], matches[skip + take - 1]);
+
+        // Extend the page to the context around its first and last match
+        let start = file.lines[..first]
+            .iter()
+            .rposition(|line| line.is_match)
+            .map_or(0, |index| index + 1);
+        let end = file.lines[last + 1..]
+            .iter()
+            .position(|line| line.is_match)
+            .map_or(file.lines.len(), |index| last + 1 + index);
+
+        let mut previous: Option<usize> = None;
+        for line in &file.lines[start..end] {
+            if previous.is_some_and(|previous| line.line > previous + 1) {
+                output.push("--".to_string());
+            }
+            previous = Some(line.line);
+            let separator = if line.is_match { ':' } else { '-' };
+            output.push(format!(
+                "{}{}{}{}{}",
+                file.path, separator, line.line, separator, line.text
+            ));
+            if line.is_match {
+                data.push(json!({ "path": file.path, "line": line.line, "text": line.text }));
+            }
+        }
+    }
+
+    (output.join("\n"), returned, json!(data))
+}
+
+impl_tool_factory!(
+    GrepToolFactory,
+    GrepTool,
+    "grep",
+    "Search file contents with regular expressions, respecting .gitignore"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn search_dir(dir: &Path, parameters: serde_json::Value) -> ToolResult {
+        let mut parameters = parameters;
+        parameters["path"] = json!(dir.display().to_string());
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(GrepTool::new().execute(ToolCall::new("grep", parameters)))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_grep_modes_and_filters() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join(".gitignore"), "generated.rs\n").unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "use std::io;\n\nfn load() {}\nfn Load_all() {}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/generated.rs"), "fn load() {}\n").unwrap();
+        std::fs::write(root.join("notes.md"), "load the data\n").unwrap();
+        std::fs::write(root.join("blob.bin"), b"load\0\0").unwrap();
+
+        let result = search_dir(root, json!({ "pattern": "fn load", "context_lines": 1 }));
+        assert!(result.success);
+        let lib = root.join("src/lib.rs").display().to_string();
+        assert!(result
+            .content
+            .contains(&format!("{0}-2-\n{0}:3:fn load() {{}}\n{0}-4-", lib)));
+        assert!(!result.content.contains("generated.rs"));
+        assert_eq!(result.data.as_ref().unwrap()["total_matches"], 1);
+
+        let result = search_dir(
+            root,
+            json!({ "pattern": "LOAD", "case_sensitive": false, "output_mode": "count" }),
+        );
+        let data = result.data.unwrap();
+        assert_eq!(
+            data["matches"],
+            json!([
+                { "path": root.join("notes.md").display().to_string(), "count": 1 },
+                { "path": lib, "count": 2 }
+            ])
+        );
+
+        let result = search_dir(
+            root,
+            json!({ "pattern": "load", "include": ["*.rs"], "output_mode": "files_with_matches" }),
+        );
+        assert_eq!(result.data.unwrap()["matches"], json!([lib]));
+    }
+
+    #[test]
+    fn test_grep_paging() {
+        let dir = TempDir::new().unwrap();
+        let content: String = (1..=10).map(|i| format!("match {}\n", i)).collect();
+        std::fs::write(dir.path().join("a.txt"), content).unwrap();
+
+        let result = search_dir(
+            dir.path(),
+            json!({ "pattern": "match", "limit": 3, "offset": 4 }),
+        );
+        let data = result.data.unwrap();
+        assert_eq!(data["returned"], 3);
+        assert_eq!(data["truncated"], true);
+        assert_eq!(data["matches"][0]["line"], 5);
+        assert!(result.content.contains("Use offset 7"));
+    }
+}
@@ -1,21 +1,35 @@
 //! JSON editing tool
 
+use crate::tools::edit::ReadTracker;
 use async_trait::async_trait;
 use coro_core::error::Result;
-use coro_core::impl_tool_factory;
 use coro_core::tools::utils::validate_absolute_path;
-use coro_core::tools::{Tool, ToolCall, ToolExample, ToolResult};
+use coro_core::tools::{Tool, ToolCall, ToolExample, ToolFactory, ToolResult};
 use jsonpath_rust::JsonPathQuery;
 use serde_json::{json, Value};
 use std::path::Path;
 use tokio::fs;
 
 /// Tool for editing JSON files using JSONPath expressions
-pub struct JsonEditTool;
+pub struct JsonEditTool {
+    tracker: ReadTracker,
+}
 
 impl JsonEditTool {
     pub fn new() -> Self {
-        Self
+        Self::with_tracker(ReadTracker::default())
+    }
+
+    /// Create a tool that records the files it writes in `tracker`, so they
+    /// can be edited without viewing them again
+    pub fn with_tracker(tracker: ReadTracker) -> Self {
+        Self { tracker }
+    }
+}
+
+impl Default for JsonEditTool {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -204,9 +218,11 @@ impl JsonEditTool {
             serde_json::to_string(data)?
         };
 
-        fs::write(file_path, content)
+        fs::write(file_path, &content)
             .await
-            .map_err(|e| format!("Error writing to file {}: {}", file_path.display(), e).into())
+            .map_err(|e| format!("Error writing to file {}: {}", file_path.display(), e))?;
+        self.tracker.record(file_path, &content);
+        Ok(())
     }
 
     /// View JSON file content or specific paths
@@ -422,9 +438,29 @@ impl JsonEditTool {
     }
 }
 
-impl_tool_factory!(
-    JsonEditToolFactory,
-    JsonEditTool,
-    "json_edit_tool",
-    "Tool for editing JSON files with JSONPath expressions"
-);
+/// Factory for creating JSON edit tools that share a read tracker with the
+/// edit tool
+pub struct JsonEditToolFactory {
+    tracker: ReadTracker,
+}
+
+impl JsonEditToolFactory {
+    /// Create tools that record the files they write in `tracker`
+    pub fn with_tracker(tracker: ReadTracker) -> Self {
+        Self { tracker }
+    }
+}
+
+impl ToolFactory for JsonEditToolFactory {
+    fn create(&self) -> Box<dyn Tool> {
+        Box::new(JsonEditTool::with_tracker(self.tracker.clone()))
+    }
+
+    fn tool_name(&self) -> &str {
+        "json_edit_tool"
+    }
+
+    fn tool_description(&self) -> &str {
+        "Tool for editing JSON files with JSONPath expressions"
+    }
+}
@@ -1,17 +1,27 @@
 //! CLI-specific tools for interactive mode
 
+pub mod apply_patch;
 pub mod bash;
+pub mod bash_jobs;
 pub mod ckg;
 pub mod edit;
 pub mod glob;
+pub mod grep;
 pub mod json_edit;
+pub mod read;
 pub mod registry;
 pub mod status_report;
 
+pub use apply_patch::ApplyPatchToolFactory;
 pub use bash::BashToolFactory;
 pub use ckg::CkgToolFactory;
 pub use edit::EditToolFactory;
 pub use glob::GlobToolFactory;
+pub use grep::GrepToolFactory;
 pub use json_edit::JsonEditToolFactory;
-pub use registry::{create_cli_tool_registry, get_default_cli_tools};
+pub use read::ReadToolFactory;
+pub use registry::{
+    create_cli_tool_registry, create_configured_tool_registry, get_default_cli_tools,
+    get_interactive_cli_tools,
+};
 pub use status_report::StatusReportToolFactory;
@@ -0,0 +1,752 @@
+//! Native file reading tool for text, images, PDFs and notebooks
+
+use crate::tools::edit::ReadTracker;
+use async_trait::async_trait;
+use base64::Engine;
+use coro_core::error::Result;
+use coro_core::tools::utils::{format_with_line_numbers, validate_absolute_path};
+use coro_core::tools::{Tool, ToolCall, ToolExample, ToolFactory, ToolResult};
+use encoding_rs::Encoding;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Lines returned when the call sets no limit
+const DEFAULT_LIMIT: usize = 2000;
+
+/// Characters of a line shown before it is clipped
+const MAX_LINE_LEN: usize = 2000;
+
+/// Bytes checked for NUL to detect binary files
+const BINARY_PROBE_LEN: usize = 8192;
+
+/// Largest image sent to the model
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Bytes of all images attached from one notebook
+const MAX_NOTEBOOK_IMAGE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Entries listed for a directory
+const MAX_DIR_ENTRIES: usize = 1000;
+
+/// MIME type of an image file, by extension
+fn image_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+/// Decode file bytes into text and the name of their encoding, or `None`
+/// for binary files
+///
+/// A byte order mark wins, then UTF-8, then the encoding guessed from the
+/// content.
+fn decode(bytes: &[u8]) -> Option<(String, &'static str)> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return Some((text.into_owned(), encoding.name()));
+    }
+    if bytes[..bytes.len().min(BINARY_PROBE_LEN)].contains(&0) {
+        return None;
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Some((text.to_string(), encoding_rs::UTF_8.name()));
+    }
+
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, true);
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    Some((text.into_owned(), encoding.name()))
+}
+
+/// Clip a line to `MAX_LINE_LEN` characters
+fn clip_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_LEN) {
+        Some((index, _)) => format!(
+            "{}... [line clipped, {} more characters]",
+            &line[..index],
+            line[index..].chars().count()
+        ),
+        None => line.to_string(),
+    }
+}
+
+/// Parse a page selection such as `3` or `2-5` into a 1-based inclusive range
+fn parse_pages(pages: &str) -> Option<(usize, usize)> {
+    let (first, last) = match pages.split_once('-') {
+        Some((first, last)) => (first.trim().parse().ok()?, last.trim().parse().ok()?),
+        None => {
+            let page = pages.trim().parse().ok()?;
+            (page, page)
+        }
+    };
+    (first >= 1 && first <= last).then_some((first, last))
+}
+
+/// Text of a notebook `source` or output field, stored as a string or a list
+/// of lines
+fn notebook_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(|line| line.as_str()).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Tool that reads files natively, without a shell
+pub struct ReadTool {
+    tracker: ReadTracker,
+}
+
+impl ReadTool {
+    pub fn new() -> Self {
+        Self::with_tracker(ReadTracker::default())
+    }
+
+    /// Create a tool that records the text files it shows in `tracker`, so
+    /// they can be edited afterwards
+    pub fn with_tracker(tracker: ReadTracker) -> Self {
+        Self { tracker }
+    }
+}
+
+impl Default for ReadTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for ReadTool {
+    fn name(&self) -> &str {
+        "read"
+    }
+
+    fn description(&self) -> &str {
+        "Read a file from the local file system\n\
+         * `path` must be an absolute path\n\
+         * Text files are shown with line numbers, up to 2000 lines from `offset` (1-based). Use `offset` and `limit` to read other parts of long files\n\
+         * Lines longer than 2000 characters are clipped\n\
+         * The encoding of text files is detected, so files that are not UTF-8 can be read as well\n\
+         * Images (PNG, JPEG, GIF, WebP) are returned as images you can see\n\
+         * The text of PDFs is extracted page by page, `pages` selects pages such as `3` or `1-5`\n\
+         * Jupyter notebooks (.ipynb) are shown cell by cell with their outputs\n\
+         * Directories are listed one level deep\n\
+         \n\
+         A text file read with this tool counts as viewed for the edit tool."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Absolute path to the file or directory, e.g. `/repo/src/main.rs`."
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Line to start reading text files from, counting from 1. Defaults to 1."
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of lines to read. Defaults to 2000."
+                },
+                "pages": {
+                    "type": "string",
+                    "description": "Pages of a PDF to read, e.g. `3` or `1-5`. Defaults to all pages."
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, call: ToolCall) -> Result<ToolResult> {
+        let path_str: String = call.get_parameter("path")?;
+        let path = PathBuf::from(&path_str);
+
+        if let Err(e) = validate_absolute_path(&path) {
+            return Ok(ToolResult::error(&call.id, &e.to_string()));
+        }
+        if !path.exists() {
+            return Ok(ToolResult::error(
+                &call.id,
+                &format!("The path {} does not exist", path.display()),
+            ));
+        }
+
+        if path.is_dir() {
+            return Ok(self.read_directory(&call.id, &path));
+        }
+        if let Some(mime_type) = image_mime_type(&path) {
+            return Ok(self.read_image(&call.id, &path, mime_type));
+        }
+        if has_extension(&path, "pdf") {
+            let pages: Option<String> = call.get_parameter("pages").ok();
+            return Ok(self.read_pdf(&call.id, &path, pages.as_deref()).await);
+        }
+        if has_extension(&path, "ipynb") {
+            return Ok(self.read_notebook(&call.id, &path));
+        }
+
+        let offset: usize = call.get_parameter_or("offset", 1).max(1);
+        let limit: usize = call.get_parameter_or("limit", DEFAULT_LIMIT).max(1);
+        Ok(self.read_text(&call.id, &path, offset, limit))
+    }
+
+    fn examples(&self) -> Vec<ToolExample> {
+        vec![
+            ToolExample {
+                description: "Read a source file".to_string(),
+                parameters: json!({"path": "/repo/src/main.rs"}),
+                expected_result: "The file with line numbers".to_string(),
+            },
+            ToolExample {
+                description: "Read part of a long log".to_string(),
+                parameters: json!({"path": "/repo/build.log", "offset": 5000, "limit": 200}),
+                expected_result: "Lines 5000 to 5199 of the log".to_string(),
+            },
+            ToolExample {
+                description: "Look at a screenshot".to_string(),
+                parameters: json!({"path": "/tmp/screenshot.png"}),
+                expected_result: "The image".to_string(),
+            },
+            ToolExample {
+                description: "Read the first pages of a PDF".to_string(),
+                parameters: json!({"path": "/repo/docs/spec.pdf", "pages": "1-3"}),
+                expected_result: "Text of pages 1 to 3".to_string(),
+            },
+        ]
+    }
+}
+
+impl ReadTool {
+    /// Show lines of a text file
+    fn read_text(&self, call_id: &str, path: &Path, offset: usize, limit: usize) -> ToolResult {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return ToolResult::error(
+                    call_id,
+                    &format!("Cannot read {}: {}", path.display(), e),
+                )
+            }
+        };
+        let Some((text, encoding)) = decode(&bytes) else {
+            return ToolResult::error(
+                call_id,
+                &format!(
+                    "{} is a binary file ({} bytes) and cannot be shown as text",
+                    path.display(),
+                    bytes.len()
+                ),
+            );
+        };
+
+        // The edit tool reads files as UTF-8, so only those count as viewed
+        if let Ok(content) = std::str::from_utf8(&bytes) {
+            self.tracker.record(path, content);
+        }
+
+        let lines: Vec<&str> = text.lines().collect();
+        if lines.is_empty() {
+            return ToolResult::success(call_id, &format!("{} is empty", path.display()));
+        }
+        if offset > lines.len() {
+            return ToolResult::error(
+                call_id,
+                &format!(
+                    "`offset` {} is past the end of {}, which has {} lines",
+                    offset,
+                    path.display(),
+                    lines.len()
+                ),
+            );
+        }
+
+        let end = (offset - 1 + limit).min(lines.len());
+        let shown: Vec<String> = lines[offset - 1..end]
+            .iter()
+            .map(|line| clip_line(line))
+            .collect();
+        let mut output = format_with_line_numbers(&shown.join("\n"), offset);
+        output.push('\n');
+        if offset > 1 || end < lines.len() {
+            output.push_str(&format!(
+                "(Showing lines {}-{} of {}. Use `offset` and `limit` to read other lines.)\n",
+                offset,
+                end,
+                lines.len()
+            ));
+        }
+        if encoding != encoding_rs::UTF_8.name() {
+            output.push_str(&format!("(Decoded from {})\n", encoding));
+        }
+
+        ToolResult::success(call_id, &output).with_data(json!({
+            "total_lines": lines.len(),
+            "first_line": offset,
+            "last_line": end,
+            "encoding": encoding,
+        }))
+    }
+
+    /// List the entries of a directory
+    fn read_directory(&self, call_id: &str, path: &Path) -> ToolResult {
+        let entries = match std::fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                return ToolResult::error(
+                    call_id,
+                    &format!("Cannot list {}: {}", path.display(), e),
+                )
+            }
+        };
+
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    format!("{}/", name)
+                } else {
+                    name
+                }
+            })
+            .collect();
+        names.sort();
+
+        let total = names.len();
+        let mut output = format!(
+            "{} is a directory with {} entries:\n",
+            path.display(),
+            total
+        );
+        for name in names.iter().take(MAX_DIR_ENTRIES) {
+            output.push_str(name);
+            output.push('\n');
+        }
+        if total > MAX_DIR_ENTRIES {
+            output.push_str(&format!(
+                "({} more entries not shown, use the glob tool to find files)\n",
+                total - MAX_DIR_ENTRIES
+            ));
+        }
+        ToolResult::success(call_id, &output)
+    }
+
+    /// Return an image for the model to look at
+    fn read_image(&self, call_id: &str, path: &Path, mime_type: &str) -> ToolResult {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return ToolResult::error(
+                    call_id,
+                    &format!("Cannot read {}: {}", path.display(), e),
+                )
+            }
+        };
+        if bytes.len() as u64 > MAX_IMAGE_BYTES {
+            return ToolResult::error(
+                call_id,
+                &format!(
+                    "{} is {} KB, images over {} KB cannot be sent to the model. Scale it down first",
+                    path.display(),
+                    bytes.len() / 1024,
+                    MAX_IMAGE_BYTES / 1024
+                ),
+            );
+        }
+
+        let data = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        ToolResult::success(
+            call_id.to_string(),
+            format!(
+                "Image {} ({}, {} KB)",
+                path.display(),
+                mime_type,
+                bytes.len().div_ceil(1024)
+            ),
+        )
+        .with_image(mime_type.to_string(), data)
+    }
+
+    /// Extract the text of the pages of a PDF
+    async fn read_pdf(&self, call_id: &str, path: &Path, pages: Option<&str>) -> ToolResult {
+        let range = match pages.map(parse_pages) {
+            Some(None) => {
+                return ToolResult::error(
+                    call_id,
+                    "Invalid `pages`. Use a page number such as `3` or a range such as `1-5`",
+                )
+            }
+            Some(range) => range,
+            None => None,
+        };
+
+        let file = path.to_path_buf();
+        let extracted =
+            tokio::task::spawn_blocking(move || pdf_extract::extract_text_by_pages(file)).await;
+        let texts = match extracted {
+            Ok(Ok(texts)) => texts,
+            Ok(Err(e)) => {
+                return ToolResult::error(
+                    call_id,
+                    &format!("Cannot extract the text of {}: {}", path.display(), e),
+                )
+            }
+            Err(_) => {
+                return ToolResult::error(
+                    call_id,
+                    &format!("Cannot parse {} as a PDF", path.display()),
+                )
+            }
+        };
+
+        let total = texts.len();
+        let (first, last) = range.unwrap_or((1, total.max(1)));
+        if first > total {
+            return ToolResult::error(
+                call_id,
+                &format!(
+                    "Page {} is past the end of {}, which has {} pages",
+                    first,
+                    path.display(),
+                    total
+                ),
+            );
+        }
+        let last = last.min(total);
+
+        let mut output = format!(
+            "{} has {} pages, showing {}-{}\n",
+            path.display(),
+            total,
+            first,
+            last
+        );
+        let mut has_text = false;
+        for (index, text) in texts.iter().enumerate().take(last).skip(first - 1) {
+            let text = text.trim();
+            has_text |= !text.is_empty();
+            output.push_str(&format!("\n--- Page {} ---\n", index + 1));
+            for line in text.lines() {
+                output.push_str(&clip_line(line));
+                output.push('\n');
+            }
+        }
+        if !has_text {
+            output.push_str(
+                "\n(No text was found on these pages, they are probably scanned images)\n",
+            );
+        }
+        ToolResult::success(call_id, &output)
+    }
+
+    /// Show the cells of a Jupyter notebook with their outputs
+    fn read_notebook(&self, call_id: &str, path: &Path) -> ToolResult {
+        let notebook: Value = match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+        {
+            Ok(notebook) => notebook,
+            Err(e) => {
+                return ToolResult::error(
+                    call_id,
+                    &format!("Cannot read {} as a notebook: {}", path.display(), e),
+                )
+            }
+        };
+        let Some(cells) = notebook["cells"].as_array() else {
+            return ToolResult::error(call_id, &format!("{} has no cells", path.display()));
+        };
+
+        let mut output = format!("{} has {} cells\n", path.display(), cells.len());
+        let mut images = Vec::new();
+        for (index, cell) in cells.iter().enumerate() {
+            let cell_type = cell["cell_type"].as_str().unwrap_or("unknown");
+            output.push_str(&format!("\nCell {} [{}]\n", index + 1, cell_type));
+            for line in notebook_text(&cell["source"]).lines() {
+                output.push_str(&clip_line(line));
+                output.push('\n');
+            }
+
+            let outputs = cell["outputs"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+            if outputs.is_empty() {
+                continue;
+            }
+            output.push_str("Output:\n");
+            for cell_output in outputs {
+                let text = match cell_output["output_type"].as_str() {
+                    Some("stream") => notebook_text(&cell_output["text"]),
+                    Some("error") => format!(
+                        "{}: {}",
+                        cell_output["ename"].as_str().unwrap_or("Error"),
+                        cell_output["evalue"].as_str().unwrap_or("")
+                    ),
+                    _ => {
+                        let data = &cell_output["data"];
+                        let image = ["image/png", "image/jpeg"]
+                            .into_iter()
+                            .find(|mime_type| !data[*mime_type].is_null());
+                        match image {
+                            Some(mime_type) => {
+                                // Notebooks may wrap base64 data across lines
+                                let encoded: String = notebook_text(&data[mime_type])
+                                    .chars()
+                                    .filter(|ch| !ch.is_whitespace())
+                                    .collect();
+                                attach_notebook_image(&mut images, mime_type, encoded)
+                            }
+                            None => notebook_text(&data["text/plain"]),
+                        }
+                    }
+                };
+                for line in text.lines() {
+                    output.push_str(&clip_line(line));
+                    output.push('\n');
+                }
+            }
+        }
+
+        images.into_iter().fold(
+            ToolResult::success(call_id, &output),
+            |result, (mime_type, data)| result.with_image(mime_type.to_string(), data),
+        )
+    }
+}
+
+/// Decoded size of base64 `data`
+fn decoded_len(data: &str) -> u64 {
+    (data.len() / 4 * 3) as u64
+}
+
+/// Attach an image output of a notebook, returning the line shown in its place
+///
+/// Images over [`MAX_IMAGE_BYTES`], or past [`MAX_NOTEBOOK_IMAGE_BYTES`] for
+/// the whole notebook, are left out with a note.
+fn attach_notebook_image(
+    images: &mut Vec<(&'static str, String)>,
+    mime_type: &'static str,
+    encoded: String,
+) -> String {
+    let size = decoded_len(&encoded);
+    let attached: u64 = images.iter().map(|(_, data)| decoded_len(data)).sum();
+    if size > MAX_IMAGE_BYTES {
+        return format!(
+            "[{} output of {} KB, not attached: images over {} KB cannot be sent to the model]",
+            mime_type,
+            size / 1024,
+            MAX_IMAGE_BYTES / 1024
+        );
+    }
+    if attached + size > MAX_NOTEBOOK_IMAGE_BYTES {
+        return format!(
+            "[{} output of {} KB, not attached: the images of a notebook are limited to {} KB]",
+            mime_type,
+            size / 1024,
+            MAX_NOTEBOOK_IMAGE_BYTES / 1024
+        );
+    }
+    images.push((mime_type, encoded));
+    format!("[{} output, attached as image {}]", mime_type, images.len())
+}
+
+/// Factory for creating read tools that share a read tracker with the edit tool
+pub struct ReadToolFactory {
+    tracker: ReadTracker,
+}
+
+impl ReadToolFactory {
+    /// Create tools that record the text files they show in `tracker`
+    pub fn with_tracker(tracker: ReadTracker) -> Self {
+        Self { tracker }
+    }
+}
+
+impl ToolFactory for ReadToolFactory {
+    fn create(&self) -> Box<dyn Tool> {
+        Box::new(ReadTool::with_tracker(self.tracker.clone()))
+    }
+
+    fn tool_name(&self) -> &str {
+        "read"
+    }
+
+    fn tool_description(&self) -> &str {
+        "Read text files, images, PDFs and notebooks"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::edit::{EditTool, ReadPolicy};
+    use coro_core::llm::ToolResultBlock;
+    use tempfile::TempDir;
+
+    async fn read(tool: &ReadTool, parameters: Value) -> ToolResult {
+        tool.execute(ToolCall::new("read", parameters))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_text_ranges() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("log.txt");
+        let mut content: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+        content.push_str(&"x".repeat(MAX_LINE_LEN + 5));
+        std::fs::write(&path, content).unwrap();
+        let tool = ReadTool::new();
+
+        let result = read(&tool, json!({"path": path, "offset": 3, "limit": 2})).await;
+        assert!(result.success, "{}", result.content);
+        assert!(result
+            .content
+            .starts_with("     3\tline 3\n     4\tline 4\n"));
+        assert!(result.content.contains("Showing lines 3-4 of 11"));
+
+        let result = read(&tool, json!({"path": path, "offset": 11})).await;
+        assert!(result.content.contains("[line clipped, 5 more characters]"));
+
+        let result = read(&tool, json!({"path": path, "offset": 12})).await;
+        assert!(!result.success);
+        assert!(result.content.contains("has 11 lines"));
+
+        let result = read(&tool, json!({"path": "relative.txt"})).await;
+        assert!(result.content.contains("not an absolute path"));
+    }
+
+    #[tokio::test]
+    async fn test_read_detects_encodings_and_binaries() {
+        let dir = TempDir::new().unwrap();
+        let tool = ReadTool::new();
+
+        let path = dir.path().join("utf16.txt");
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("héllo\n".encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        std::fs::write(&path, bytes).unwrap();
+        let result = read(&tool, json!({"path": path})).await;
+        assert!(result.content.starts_with("     1\théllo\n"));
+        assert!(result.content.contains("Decoded from UTF-16LE"));
+
+        let path = dir.path().join("gbk.txt");
+        let text = "这是一个使用简体中文编写的文本文件，用来检查编码是否能够被正确识别。\n";
+        let content = text.repeat(4);
+        let (bytes, _, _) = encoding_rs::GBK.encode(&content);
+        std::fs::write(&path, bytes).unwrap();
+        let result = read(&tool, json!({"path": path})).await;
+        assert!(result.content.contains(text.trim_end()));
+        assert!(result.content.contains("Decoded from GBK"));
+
+        let path = dir.path().join("blob.bin");
+        std::fs::write(&path, b"ELF\0\0\x01").unwrap();
+        let result = read(&tool, json!({"path": path})).await;
+        assert!(!result.success);
+        assert!(result.content.contains("is a binary file"));
+    }
+
+    #[tokio::test]
+    async fn test_read_images_and_notebooks() {
+        let dir = TempDir::new().unwrap();
+        let tool = ReadTool::new();
+
+        let path = dir.path().join("shot.png");
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n").unwrap();
+        let result = read(&tool, json!({"path": path})).await;
+        assert!(result.success, "{}", result.content);
+        assert!(matches!(
+            &result.blocks[..],
+            [ToolResultBlock::Image { mime_type, data }] if mime_type == "image/png" && data == "iVBORw0KGgo="
+        ));
+
+        let path = dir.path().join("analysis.ipynb");
+        let notebook = json!({
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Analysis\n", "Loads the data"]},
+                {
+                    "cell_type": "code",
+                    "source": "print(1)\nplot()",
+                    "outputs": [
+                        {"output_type": "stream", "name": "stdout", "text": ["1\n"]},
+                        {"output_type": "display_data", "data": {"image/png": "iVBORw0K\nGgo=\n", "text/plain": "<Figure>"}},
+                        {"output_type": "error", "ename": "NameError", "evalue": "name 'plot' is not defined", "traceback": []}
+                    ]
+                }
+            ]
+        });
+        std::fs::write(&path, notebook.to_string()).unwrap();
+        let result = read(&tool, json!({"path": path})).await;
+        assert!(result.success, "{}", result.content);
+        assert!(result.content.contains(
+            "Cell 1 [markdown]\n# Analysis\nLoads the data\n\nCell 2 [code]\nprint(1)\nplot()\nOutput:\n1\n[image/png output, attached as image 1]\nNameError: name 'plot' is not defined\n"
+        ));
+        assert!(matches!(
+            &result.blocks[..],
+            [ToolResultBlock::Image { data, .. }] if data == "iVBORw0KGgo="
+        ));
+    }
+
+    #[test]
+    fn test_notebook_images_are_limited() {
+        let mut images = Vec::new();
+        // 4.5 MB each once decoded, two fit in the notebook limit
+        let image = "A".repeat(6 * 1024 * 1024);
+
+        let note = attach_notebook_image(&mut images, "image/png", "A".repeat(7 * 1024 * 1024));
+        assert!(
+            note.contains("not attached: images over 5120 KB"),
+            "{}",
+            note
+        );
+        assert_eq!(
+            attach_notebook_image(&mut images, "image/png", image.clone()),
+            "[image/png output, attached as image 1]"
+        );
+        assert_eq!(
+            attach_notebook_image(&mut images, "image/png", image.clone()),
+            "[image/png output, attached as image 2]"
+        );
+        let note = attach_notebook_image(&mut images, "image/png", image);
+        assert!(note.contains("limited to 10240 KB"), "{}", note);
+        assert_eq!(images.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_read_counts_as_a_view_for_edits() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "one\n").unwrap();
+        let tracker = ReadTracker::default();
+        let edit = EditTool::with_options(None, ReadPolicy::Strict, tracker.clone());
+        let replace = ToolCall::new(
+            "str_replace_based_edit_tool",
+            json!({"command": "str_replace", "path": path, "old_str": "one", "new_str": "two"}),
+        );
+
+        read(&ReadTool::with_tracker(tracker), json!({"path": path})).await;
+        let result = edit.execute(replace).await.unwrap();
+        assert!(result.success, "{}", result.content);
+    }
+
+    #[test]
+    fn test_parse_pages() {
+        assert_eq!(parse_pages("3"), Some((3, 3)));
+        assert_eq!(parse_pages("2-5"), Some((2, 5)));
+        assert_eq!(parse_pages("0"), None);
+        assert_eq!(parse_pages("5-2"), None);
+        assert_eq!(parse_pages("all"), None);
+    }
+}
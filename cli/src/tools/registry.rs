@@ -1,18 +1,54 @@
 //! CLI tool registry with extended tools
 
+use crate::config::ToolSettings;
+use crate::tools::edit::ReadTracker;
+use crate::tools::{BashToolFactory, CkgToolFactory, EditToolFactory, ReadToolFactory};
+use coro_core::tools::builtin::McpToolFactory;
 use coro_core::tools::{ToolExecutor, ToolRegistry};
+use std::time::Duration;
 
 /// Create a CLI-specific tool registry with all available tools
 pub fn create_cli_tool_registry() -> ToolRegistry {
+    create_configured_tool_registry(&ToolSettings::default())
+}
+
+/// Create a CLI tool registry whose tools use the options of the config file
+pub fn create_configured_tool_registry(settings: &ToolSettings) -> ToolRegistry {
     let mut registry = ToolRegistry::default(); // This gets core tools (thinking, task_done, mcp)
 
     // Register CLI-specific tools
-    registry.register_factory(Box::new(crate::tools::BashToolFactory));
-    registry.register_factory(Box::new(crate::tools::EditToolFactory));
+    registry.register_factory(Box::new(BashToolFactory::with_options(
+        settings.bash.timeout_secs.map(Duration::from_secs),
+        settings.max_output_len,
+    )));
+    // Files shown by the read tool count as viewed by the edit tool, and files
+    // written by any file tool are not stale for the others
+    let read_policy = settings.edit.read_policy.unwrap_or_default();
+    let read_tracker = ReadTracker::default();
+    registry.register_factory(Box::new(EditToolFactory::with_options(
+        settings.max_output_len,
+        read_policy,
+        read_tracker.clone(),
+    )));
+    registry.register_factory(Box::new(ReadToolFactory::with_tracker(
+        read_tracker.clone(),
+    )));
+    registry.register_factory(Box::new(crate::tools::ApplyPatchToolFactory::with_options(
+        read_policy,
+        read_tracker.clone(),
+    )));
     registry.register_factory(Box::new(crate::tools::GlobToolFactory));
-    registry.register_factory(Box::new(crate::tools::JsonEditToolFactory));
-    registry.register_factory(Box::new(crate::tools::CkgToolFactory));
+    registry.register_factory(Box::new(crate::tools::GrepToolFactory));
+    registry.register_factory(Box::new(crate::tools::JsonEditToolFactory::with_tracker(
+        read_tracker,
+    )));
+    registry.register_factory(Box::new(CkgToolFactory::with_db_path(
+        settings.ckg.db_path.clone(),
+    )));
     registry.register_factory(Box::new(crate::tools::StatusReportToolFactory::new()));
+    registry.register_factory(Box::new(McpToolFactory::with_servers(
+        settings.mcp.server_configs(),
+    )));
 
     registry
 }
@@ -33,8 +69,11 @@ pub fn create_cli_tool_executor_with_all() -> ToolExecutor {
 pub fn get_default_cli_tools() -> Vec<String> {
     vec![
         "bash".to_string(),
+        "read".to_string(),
         "str_replace_based_edit_tool".to_string(),
+        "apply_patch".to_string(),
         "glob".to_string(),
+        "grep".to_string(),
         "sequentialthinking".to_string(),
         "task_done".to_string(),
         "json_edit_tool".to_string(),
@@ -42,6 +81,15 @@ pub fn get_default_cli_tools() -> Vec<String> {
     ]
 }
 
+/// Get the CLI tool names used in interactive mode (defaults plus status reporting)
+pub fn get_interactive_cli_tools() -> Vec<String> {
+    let mut tools = get_default_cli_tools();
+    if !tools.contains(&"status_report".to_string()) {
+        tools.push("status_report".to_string());
+    }
+    tools
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -54,8 +102,11 @@ mod tests {
         // Expected tools including CLI-specific ones
         let expected_tools = vec![
             "bash",
+            "read",
             "str_replace_based_edit_tool",
+            "apply_patch",
             "glob",
+            "grep",
             "sequentialthinking",
             "task_done",
             "json_edit_tool",
@@ -83,8 +134,11 @@ mod tests {
         // Test creating each CLI tool
         let tools_to_test = vec![
             "bash",
+            "read",
             "str_replace_based_edit_tool",
+            "apply_patch",
             "glob",
+            "grep",
             "sequentialthinking",
             "task_done",
             "json_edit_tool",
@@ -88,6 +88,7 @@ system_prompt = "You are a helpful coding assistant specialized in web developme
         ],
         output_mode: OutputMode::Normal,
         system_prompt: Some("You are a specialized DevOps assistant.".to_string()),
+        ..Default::default()
     };
 
     let json = serde_json::to_string_pretty(&example_config)?;
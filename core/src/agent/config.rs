@@ -1,13 +1,19 @@
 //! Agent configuration structures
 
+use super::templates::PromptMode;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Output mode for the agent
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputMode {
     /// Debug mode with detailed logging and verbose output
+    #[serde(alias = "Debug")]
     Debug,
     /// Normal mode with clean, user-friendly output
+    #[serde(alias = "Normal")]
     Normal,
 }
 
@@ -37,6 +43,39 @@ pub struct AgentConfig {
     /// If not provided, the default system prompt will be used
     #[serde(default)]
     pub system_prompt: Option<String>,
+
+    /// Whether CORO.md and AGENTS.md instruction files are added to the system prompt
+    #[serde(default = "default_load_instructions")]
+    pub load_instructions: bool,
+
+    /// Bytes of instruction files kept in the system prompt
+    #[serde(default = "default_max_instructions_len")]
+    pub max_instructions_len: usize,
+
+    /// Kind of task, selecting the system prompt template
+    #[serde(default)]
+    pub mode: PromptMode,
+
+    /// Tokens of output kept in a tool result
+    #[serde(default = "default_max_output_tokens")]
+    pub max_output_tokens: usize,
+
+    /// Directory the full output of clipped tool results is saved to, not
+    /// saved when unset
+    #[serde(default)]
+    pub tool_output_dir: Option<PathBuf>,
+}
+
+fn default_load_instructions() -> bool {
+    true
+}
+
+fn default_max_instructions_len() -> usize {
+    super::instructions::DEFAULT_MAX_INSTRUCTIONS_LEN
+}
+
+fn default_max_output_tokens() -> usize {
+    crate::tools::output_budget::DEFAULT_MAX_OUTPUT_TOKENS
 }
 
 impl Default for AgentConfig {
@@ -52,6 +91,11 @@ impl Default for AgentConfig {
             ],
             output_mode: OutputMode::default(),
             system_prompt: None,
+            load_instructions: default_load_instructions(),
+            max_instructions_len: default_max_instructions_len(),
+            mode: PromptMode::default(),
+            max_output_tokens: default_max_output_tokens(),
+            tool_output_dir: None,
         }
     }
 }
@@ -95,6 +139,12 @@ impl AgentBuilder {
         self
     }
 
+    /// Set the prompt mode
+    pub fn with_mode(mut self, mode: PromptMode) -> Self {
+        self.agent_config.mode = mode;
+        self
+    }
+
     /// Set system prompt
     pub fn with_system_prompt(mut self, system_prompt: Option<String>) -> Self {
         self.agent_config.system_prompt = system_prompt;
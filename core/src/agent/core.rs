@@ -1,20 +1,26 @@
 //! AgentCore implementation
 
 use super::config::AgentConfig;
-use crate::agent::prompt::{build_system_prompt_with_context, build_user_message};
+use crate::agent::environment::ProjectEnvironment;
+use crate::agent::instructions::{build_instructions_section, discover_instructions};
+use crate::agent::prompt::{
+    build_compacted_history_message, build_user_message_with_context, COMPACT_CONVERSATION_PROMPT,
+};
+use crate::agent::templates::{render_system_prompt, PromptContext};
 use crate::agent::{Agent, AgentExecution, AgentResult};
 use crate::error::{AgentError, Result};
-use crate::llm::{ChatOptions, LlmClient, LlmMessage};
+use crate::llm::{create_llm_client, flatten_tool_calls, ChatOptions, LlmClient, LlmMessage};
 use crate::output::{
     AgentEvent, AgentExecutionContext, AgentOutput, TokenUsage, ToolExecutionInfo,
     ToolExecutionInfoBuilder, ToolExecutionStatus,
 };
-use crate::tools::{ToolExecutor, ToolRegistry};
+use crate::tools::{OutputBudget, ToolCall, ToolExecutor, ToolProgress, ToolRegistry, ToolResult};
 use crate::trajectory::{TrajectoryEntry, TrajectoryRecorder};
 use async_trait::async_trait;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Instant;
+use tokio::sync::mpsc;
 
 /// TraeAgent - the main agent implementation
 pub struct AgentCore {
@@ -26,6 +32,19 @@ pub struct AgentCore {
     output: Box<dyn AgentOutput>,
     current_task_displayed: bool,
     execution_context: Option<AgentExecutionContext>,
+    /// Extra context (e.g. piped stdin) attached to the next task's user message
+    initial_context: Option<String>,
+    /// Project environment, detected when the first system prompt is built
+    environment: OnceLock<ProjectEnvironment>,
+}
+
+/// Output budget of the tool results of an agent
+fn output_budget(config: &AgentConfig) -> OutputBudget {
+    let budget = OutputBudget::new(config.max_output_tokens);
+    match &config.tool_output_dir {
+        Some(dir) => budget.with_spill_dir(dir),
+        None => budget,
+    }
 }
 
 impl AgentCore {
@@ -36,28 +55,12 @@ impl AgentCore {
         output: Box<dyn AgentOutput>,
     ) -> Result<Self> {
         // Create LLM client based on protocol
-        let llm_client: Arc<dyn LlmClient> = match llm_config.protocol {
-            crate::config::Protocol::OpenAICompat => {
-                Arc::new(crate::llm::OpenAiClient::new(&llm_config)?)
-            }
-            crate::config::Protocol::Anthropic => {
-                Arc::new(crate::llm::AnthropicClient::new(&llm_config)?)
-            }
-            crate::config::Protocol::GoogleAI => {
-                return Err(AgentError::NotInitialized.into()); // TODO: Implement GoogleAI client
-            }
-            crate::config::Protocol::AzureOpenAI => {
-                // Azure OpenAI uses the same client as OpenAI
-                Arc::new(crate::llm::OpenAiClient::new(&llm_config)?)
-            }
-            crate::config::Protocol::Custom(_) => {
-                return Err(AgentError::NotInitialized.into()); // TODO: Implement custom protocol support
-            }
-        };
+        let llm_client = create_llm_client(&llm_config)?;
 
         // Create tool executor
         let tool_registry = crate::tools::ToolRegistry::default();
-        let tool_executor = tool_registry.create_executor(&agent_config.tools);
+        let mut tool_executor = tool_registry.create_executor(&agent_config.tools);
+        tool_executor.set_output_budget(output_budget(&agent_config));
 
         Ok(Self {
             config: agent_config,
@@ -68,6 +71,8 @@ impl AgentCore {
             output,
             current_task_displayed: false,
             execution_context: None,
+            initial_context: None,
+            environment: OnceLock::new(),
         })
     }
 
@@ -84,27 +89,11 @@ impl AgentCore {
         tool_registry: ToolRegistry,
     ) -> Result<Self> {
         // Create LLM client based on protocol
-        let llm_client: Arc<dyn LlmClient> = match llm_config.protocol {
-            crate::config::Protocol::OpenAICompat => {
-                Arc::new(crate::llm::OpenAiClient::new(&llm_config)?)
-            }
-            crate::config::Protocol::Anthropic => {
-                Arc::new(crate::llm::AnthropicClient::new(&llm_config)?)
-            }
-            crate::config::Protocol::GoogleAI => {
-                return Err(AgentError::NotInitialized.into()); // TODO: Implement GoogleAI client
-            }
-            crate::config::Protocol::AzureOpenAI => {
-                // Azure OpenAI uses the same client as OpenAI
-                Arc::new(crate::llm::OpenAiClient::new(&llm_config)?)
-            }
-            crate::config::Protocol::Custom(_) => {
-                return Err(AgentError::NotInitialized.into()); // TODO: Implement custom protocol support
-            }
-        };
+        let llm_client = create_llm_client(&llm_config)?;
 
         // Create tool executor with custom registry
-        let tool_executor = tool_registry.create_executor(&agent_config.tools);
+        let mut tool_executor = tool_registry.create_executor(&agent_config.tools);
+        tool_executor.set_output_budget(output_budget(&agent_config));
 
         Ok(Self {
             config: agent_config,
@@ -115,6 +104,8 @@ impl AgentCore {
             output,
             current_task_displayed: false,
             execution_context: None,
+            initial_context: None,
+            environment: OnceLock::new(),
         })
     }
 
@@ -133,6 +124,150 @@ impl AgentCore {
         self.config.system_prompt = system_prompt;
     }
 
+    /// Attach extra context (such as piped stdin) to the next task's user message
+    /// The context is consumed by the next call to execute or continue a task
+    pub fn set_initial_context(&mut self, context: Option<String>) {
+        self.initial_context = context;
+    }
+
+    /// Get the conversation history of the agent
+    pub fn conversation_history(&self) -> &[LlmMessage] {
+        &self.conversation_history
+    }
+
+    /// Replace the conversation history, e.g. when resuming a persisted session
+    /// Subsequent calls to `continue_conversation` build on the imported messages
+    pub fn import_conversation_history(&mut self, history: Vec<LlmMessage>) {
+        self.conversation_history = history;
+    }
+
+    /// Get the model name of the current LLM client
+    pub fn model_name(&self) -> &str {
+        self.llm_client.model_name()
+    }
+
+    /// Switch to a different LLM client, keeping the conversation history
+    /// Tool call ids and message formats are provider-specific, so when the
+    /// provider changes, tool calls and results in the history are rewritten
+    /// as plain text.
+    pub fn set_llm_client(&mut self, llm_client: Arc<dyn LlmClient>) {
+        if llm_client.provider_name() != self.llm_client.provider_name() {
+            self.conversation_history = flatten_tool_calls(&self.conversation_history);
+        }
+        self.llm_client = llm_client;
+    }
+
+    /// Get the token usage accumulated by this agent so far
+    pub fn token_usage(&self) -> TokenUsage {
+        self.execution_context
+            .as_ref()
+            .map(|context| context.token_usage.clone())
+            .unwrap_or_default()
+    }
+
+    /// Summarize the conversation and replace the history with the summary
+    /// The system prompt is kept so later tasks continue from the summary.
+    /// Returns the generated summary.
+    pub async fn compact_conversation(&mut self, instructions: Option<&str>) -> Result<String> {
+        let is_system =
+            |message: &LlmMessage| matches!(message.role, crate::llm::MessageRole::System);
+
+        if self.conversation_history.iter().all(is_system) {
+            return Err(AgentError::InvalidTask {
+                message: "there is no conversation to compact".to_string(),
+            }
+            .into());
+        }
+
+        let mut prompt = COMPACT_CONVERSATION_PROMPT.to_string();
+        if let Some(instructions) = instructions.filter(|i| !i.trim().is_empty()) {
+            prompt.push_str(&format!(
+                "\n\nAdditional instructions: {}",
+                instructions.trim()
+            ));
+        }
+
+        // No tools are sent, so tool calls are summarized as plain text
+        let mut messages = flatten_tool_calls(&self.conversation_history);
+        messages.push(LlmMessage::user(prompt));
+
+        let response = self
+            .llm_client
+            .chat_completion(messages, None, Some(ChatOptions::default()))
+            .await?;
+
+        if let (Some(usage), Some(context)) = (&response.usage, &mut self.execution_context) {
+            context.token_usage.input_tokens += usage.prompt_tokens;
+            context.token_usage.output_tokens += usage.completion_tokens;
+            context.token_usage.total_tokens += usage.total_tokens;
+        }
+
+        let summary = response.message.get_text().unwrap_or_default();
+        if summary.trim().is_empty() {
+            return Err(AgentError::TaskFailed {
+                message: "the model returned an empty summary".to_string(),
+            }
+            .into());
+        }
+
+        let mut history: Vec<LlmMessage> = self
+            .conversation_history
+            .first()
+            .filter(|message| is_system(message))
+            .cloned()
+            .into_iter()
+            .collect();
+        history.push(LlmMessage::user(build_compacted_history_message(&summary)));
+        history.push(LlmMessage::assistant(
+            "Understood. I will continue from this summary.",
+        ));
+        self.conversation_history = history;
+
+        Ok(summary)
+    }
+
+    /// Execute a tool call, forwarding its output to realtime outputs while it runs
+    async fn execute_tool(
+        &self,
+        tool_call: &ToolCall,
+        tool_info: &ToolExecutionInfo,
+    ) -> Result<ToolResult> {
+        if !self.output.supports_realtime_updates() {
+            return self.tool_executor.execute(tool_call.clone()).await;
+        }
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let execution = self
+            .tool_executor
+            .execute_with_progress(tool_call.clone(), ToolProgress::new(sender));
+        tokio::pin!(execution);
+
+        loop {
+            tokio::select! {
+                result = &mut execution => return result,
+                Some(chunk) = receiver.recv() => {
+                    // Coalesce chunks that queued up while the last event was emitted
+                    let mut output_chunk = chunk;
+                    while let Ok(chunk) = receiver.try_recv() {
+                        output_chunk.push_str(&chunk);
+                    }
+                    self.output
+                        .emit_event(AgentEvent::ToolExecutionUpdated {
+                            tool_info: tool_info.clone(),
+                            output_chunk,
+                        })
+                        .await
+                        .unwrap_or_else(|e| {
+                            let _ = futures::executor::block_on(self.output.debug(&format!(
+                                "Failed to emit tool execution updated event: {}",
+                                e
+                            )));
+                        });
+                }
+            }
+        }
+    }
+
     /// Get the current system prompt from configuration
     pub fn get_configured_system_prompt(&self) -> Option<&String> {
         self.config.system_prompt.as_ref()
@@ -140,28 +275,64 @@ impl AgentCore {
 
     /// Get the system prompt for the agent with project context
     fn get_system_prompt(&self, project_path: &Path) -> String {
-        // Use custom system prompt if provided, otherwise use default
-        let base_prompt = if let Some(custom_prompt) = &self.config.system_prompt {
-            // If custom prompt is provided, use it as-is with minimal generic context
-            let system_context = crate::agent::prompt::build_system_context();
-
-            format!(
-                "{}\n\n\
-                     [System Context]:\n{}",
-                custom_prompt, system_context
-            )
-        } else {
-            // Use default system prompt with full environment context from prompt.rs
-            build_system_prompt_with_context(project_path)
+        let tool_names: Vec<String> = self
+            .tool_executor
+            .list_tools()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let instructions = self.instructions_section(project_path);
+
+        // Without a custom system prompt, render the template of the mode
+        let Some(custom_prompt) = &self.config.system_prompt else {
+            let environment = self
+                .environment
+                .get_or_init(|| ProjectEnvironment::detect(project_path));
+            let context = PromptContext::new(
+                self.config.mode,
+                project_path,
+                environment,
+                tool_names,
+                instructions,
+            );
+            return render_system_prompt(&context, project_path);
         };
 
-        format!(
-            "{}\n\nAvailable tools: {}",
-            base_prompt,
-            self.tool_executor.list_tools().join(", ")
+        // A custom prompt is used as-is with minimal generic context
+        let mut prompt = format!(
+            "{}\n\n[System Context]:\n{}",
+            custom_prompt,
+            crate::agent::prompt::build_system_context()
+        );
+        if let Some(instructions) = instructions {
+            prompt.push_str(&format!("\n\n{}", instructions));
+        }
+        format!("{}\n\nAvailable tools: {}", prompt, tool_names.join(", "))
+    }
+
+    /// Instruction files of the project, unless disabled in the configuration
+    fn instructions_section(&self, project_path: &Path) -> Option<String> {
+        if !self.config.load_instructions {
+            return None;
+        }
+        build_instructions_section(
+            &discover_instructions(project_path),
+            self.config.max_instructions_len,
         )
     }
 
+    /// Rebuild the system prompt of the conversation, e.g. after instruction files changed
+    pub fn refresh_system_prompt(&mut self, project_path: &Path) {
+        let system_prompt = self.get_system_prompt(project_path);
+        if let Some(message) = self
+            .conversation_history
+            .first_mut()
+            .filter(|message| matches!(message.role, crate::llm::MessageRole::System))
+        {
+            *message = LlmMessage::system(system_prompt);
+        }
+    }
+
     /// Execute a single step of the agent
     async fn execute_step(&mut self, step: usize, project_path: &Path) -> Result<bool> {
         // Prepare messages - only add system prompt if conversation history doesn't start with one
@@ -293,7 +464,7 @@ impl AgentCore {
                     }
 
                     // Execute tool
-                    let tool_result = self.tool_executor.execute(tool_call.clone()).await?;
+                    let tool_result = self.execute_tool(&tool_call, &tool_info).await?;
 
                     // Create completed tool execution info and emit completed event
                     let completed_tool_info = ToolExecutionInfo::create_tool_execution_info(
@@ -380,7 +551,7 @@ impl AgentCore {
                             crate::llm::ContentBlock::ToolResult {
                                 tool_use_id: id.clone(),
                                 is_error: Some(!tool_result.success),
-                                content: tool_result.content,
+                                content: tool_result.content_blocks(),
                             },
                         ]),
                         metadata: None,
@@ -535,7 +706,8 @@ impl AgentCore {
         self.trim_conversation_history(50);
 
         // Add user message with task
-        let user_message = build_user_message(task);
+        let user_message =
+            build_user_message_with_context(task, self.initial_context.take().as_deref());
         self.conversation_history
             .push(LlmMessage::user(&user_message));
 
@@ -703,7 +875,8 @@ impl AgentCore {
             .push(LlmMessage::system(self.get_system_prompt(project_path)));
 
         // Add user message with task only (environment context is now in system prompt)
-        let user_message = build_user_message(task);
+        let user_message =
+            build_user_message_with_context(task, self.initial_context.take().as_deref());
         self.conversation_history
             .push(LlmMessage::user(&user_message));
 
@@ -822,7 +995,7 @@ mod tests {
     use crate::error::Result;
     use crate::llm::{
         ChatOptions, LlmClient, LlmMessage, LlmResponse, MessageContent, MessageRole,
-        ToolDefinition,
+        ToolDefinition, ToolResultBlock,
     };
     use crate::AgentConfig;
     use async_trait::async_trait;
@@ -934,6 +1107,8 @@ mod tests {
             output: Box::new(NullOutput),
             current_task_displayed: false,
             execution_context: None,
+            initial_context: None,
+            environment: OnceLock::new(),
         };
 
         let project_path = PathBuf::from("/some/project/path");
@@ -955,4 +1130,247 @@ mod tests {
         assert!(!system_prompt.contains("IMPORTANT: When using tools that require file paths"));
         assert!(!system_prompt.contains("You are an expert AI software engineering agent"));
     }
+
+    #[test]
+    fn test_import_conversation_history() {
+        use crate::output::events::NullOutput;
+        use crate::tools::ToolRegistry;
+
+        let agent_config = AgentConfig::default();
+        let tool_executor = ToolRegistry::default().create_executor(&agent_config.tools);
+
+        let mut agent = AgentCore {
+            config: agent_config,
+            llm_client: std::sync::Arc::new(MockLlmClient::new()),
+            tool_executor,
+            trajectory_recorder: None,
+            conversation_history: Vec::new(),
+            output: Box::new(NullOutput),
+            current_task_displayed: false,
+            execution_context: None,
+            initial_context: None,
+            environment: OnceLock::new(),
+        };
+
+        agent.import_conversation_history(vec![
+            LlmMessage::system("system"),
+            LlmMessage::user("hello"),
+        ]);
+
+        let history = agent.conversation_history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].role, MessageRole::User);
+        assert_eq!(agent.token_usage().total_tokens, 0);
+    }
+
+    #[tokio::test]
+    async fn test_compact_conversation_keeps_system_prompt() {
+        use crate::output::events::NullOutput;
+        use crate::tools::ToolRegistry;
+
+        let agent_config = AgentConfig::default();
+        let tool_executor = ToolRegistry::default().create_executor(&agent_config.tools);
+
+        let mut agent = AgentCore {
+            config: agent_config,
+            llm_client: std::sync::Arc::new(MockLlmClient::new()),
+            tool_executor,
+            trajectory_recorder: None,
+            conversation_history: vec![LlmMessage::system("system")],
+            output: Box::new(NullOutput),
+            current_task_displayed: false,
+            execution_context: None,
+            initial_context: None,
+            environment: OnceLock::new(),
+        };
+
+        // Nothing to compact with only a system prompt
+        assert!(agent.compact_conversation(None).await.is_err());
+
+        agent.conversation_history.extend([
+            LlmMessage::user("first task"),
+            LlmMessage::assistant("first answer"),
+            LlmMessage::user("second task"),
+            LlmMessage::assistant("second answer"),
+        ]);
+
+        let summary = agent.compact_conversation(Some("focus")).await.unwrap();
+        assert_eq!(summary, "Mock response");
+
+        let history = agent.conversation_history();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].role, MessageRole::System);
+        assert!(history[1]
+            .get_text()
+            .unwrap()
+            .contains("[Conversation summary]:\nMock response"));
+        assert_eq!(history[2].role, MessageRole::Assistant);
+    }
+
+    #[test]
+    fn test_set_llm_client_flattens_tool_calls_across_providers() {
+        use crate::llm::ContentBlock;
+        use crate::output::events::NullOutput;
+        use crate::tools::ToolRegistry;
+
+        struct OtherProviderClient;
+
+        #[async_trait]
+        impl LlmClient for OtherProviderClient {
+            async fn chat_completion(
+                &self,
+                messages: Vec<LlmMessage>,
+                tools: Option<Vec<ToolDefinition>>,
+                options: Option<ChatOptions>,
+            ) -> Result<LlmResponse> {
+                MockLlmClient
+                    .chat_completion(messages, tools, options)
+                    .await
+            }
+
+            fn model_name(&self) -> &str {
+                "other-model"
+            }
+
+            fn provider_name(&self) -> &str {
+                "other"
+            }
+        }
+
+        let agent_config = AgentConfig::default();
+        let tool_executor = ToolRegistry::default().create_executor(&agent_config.tools);
+
+        let mut agent = AgentCore {
+            config: agent_config,
+            llm_client: std::sync::Arc::new(MockLlmClient::new()),
+            tool_executor,
+            trajectory_recorder: None,
+            conversation_history: Vec::new(),
+            output: Box::new(NullOutput),
+            current_task_displayed: false,
+            execution_context: None,
+            initial_context: None,
+            environment: OnceLock::new(),
+        };
+
+        agent.import_conversation_history(vec![
+            LlmMessage::user("list files"),
+            LlmMessage {
+                role: MessageRole::Assistant,
+                content: MessageContent::MultiModal(vec![ContentBlock::ToolUse {
+                    id: "call_1".to_string(),
+                    name: "bash".to_string(),
+                    input: serde_json::json!({"command": "ls"}),
+                }]),
+                metadata: None,
+            },
+            LlmMessage {
+                role: MessageRole::Tool,
+                content: MessageContent::MultiModal(vec![ContentBlock::ToolResult {
+                    tool_use_id: "call_1".to_string(),
+                    is_error: Some(false),
+                    content: vec![ToolResultBlock::text("Cargo.toml")],
+                }]),
+                metadata: None,
+            },
+        ]);
+
+        // Same provider keeps the structured history
+        agent.set_llm_client(std::sync::Arc::new(MockLlmClient::new()));
+        assert!(agent.conversation_history()[1].has_tool_use());
+
+        agent.set_llm_client(std::sync::Arc::new(OtherProviderClient));
+        assert_eq!(agent.model_name(), "other-model");
+
+        let history = agent.conversation_history();
+        assert_eq!(history.len(), 3);
+        assert!(!history[1].has_tool_use());
+        assert!(history[1].get_text().unwrap().contains("[Called tool bash"));
+        assert_eq!(history[2].role, MessageRole::User);
+        assert!(history[2].get_text().unwrap().contains("Cargo.toml"));
+    }
+
+    #[tokio::test]
+    async fn test_compact_conversation_flattens_tool_calls() {
+        use crate::llm::ContentBlock;
+        use crate::output::events::NullOutput;
+        use crate::tools::ToolRegistry;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default)]
+        struct RecordingClient {
+            messages: Mutex<Vec<LlmMessage>>,
+        }
+
+        #[async_trait]
+        impl LlmClient for RecordingClient {
+            async fn chat_completion(
+                &self,
+                messages: Vec<LlmMessage>,
+                tools: Option<Vec<ToolDefinition>>,
+                options: Option<ChatOptions>,
+            ) -> Result<LlmResponse> {
+                *self.messages.lock().unwrap() = messages.clone();
+                MockLlmClient
+                    .chat_completion(messages, tools, options)
+                    .await
+            }
+
+            fn model_name(&self) -> &str {
+                "mock-model"
+            }
+
+            fn provider_name(&self) -> &str {
+                "mock"
+            }
+        }
+
+        let client = Arc::new(RecordingClient::default());
+        let agent_config = AgentConfig::default();
+        let tool_executor = ToolRegistry::default().create_executor(&agent_config.tools);
+
+        let mut agent = AgentCore {
+            config: agent_config,
+            llm_client: client.clone(),
+            tool_executor,
+            trajectory_recorder: None,
+            conversation_history: vec![
+                LlmMessage::system("system"),
+                LlmMessage::user("list files"),
+                LlmMessage {
+                    role: MessageRole::Assistant,
+                    content: MessageContent::MultiModal(vec![ContentBlock::ToolUse {
+                        id: "call_1".to_string(),
+                        name: "bash".to_string(),
+                        input: serde_json::json!({"command": "ls"}),
+                    }]),
+                    metadata: None,
+                },
+                LlmMessage {
+                    role: MessageRole::Tool,
+                    content: MessageContent::MultiModal(vec![ContentBlock::ToolResult {
+                        tool_use_id: "call_1".to_string(),
+                        is_error: Some(false),
+                        content: vec![ToolResultBlock::text("Cargo.toml")],
+                    }]),
+                    metadata: None,
+                },
+            ],
+            output: Box::new(NullOutput),
+            current_task_displayed: false,
+            execution_context: None,
+            initial_context: None,
+            environment: OnceLock::new(),
+        };
+
+        agent.compact_conversation(None).await.unwrap();
+
+        let sent = client.messages.lock().unwrap().clone();
+        assert_eq!(sent.len(), 5);
+        assert!(sent
+            .iter()
+            .all(|message| !message.has_tool_use() && !matches!(message.role, MessageRole::Tool)));
+        assert!(sent[2].get_text().unwrap().contains("[Called tool bash"));
+        assert!(sent[3].get_text().unwrap().contains("Cargo.toml"));
+    }
 }
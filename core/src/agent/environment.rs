@@ -0,0 +1,299 @@
+//! Project environment summarized in the system prompt
+//!
+//! Detecting the git state, toolchains and layout up front saves the agent
+//! the exploration steps it would otherwise spend finding them out. The
+//! detection runs git, so agents do it once and keep the result.
+
+use super::instructions::project_root;
+use std::path::Path;
+use std::process::Command;
+
+/// Number of recent commit subjects listed
+const RECENT_COMMITS: usize = 5;
+
+/// Number of top-level entries listed before the layout is cut short
+const MAX_LAYOUT_ENTRIES: usize = 40;
+
+/// Top-level directories left out of the layout
+const IGNORED_ENTRIES: [&str; 5] = ["target", "node_modules", "__pycache__", "dist", "build"];
+
+/// Git state of the project
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GitStatus {
+    /// Checked out branch, `None` on a detached HEAD
+    pub branch: Option<String>,
+    pub staged: usize,
+    pub modified: usize,
+    pub untracked: usize,
+    /// Subjects of the latest commits, newest first
+    pub recent_commits: Vec<String>,
+}
+
+/// A toolchain detected from a manifest file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toolchain {
+    pub name: &'static str,
+    pub manifest: &'static str,
+    pub test_command: &'static str,
+}
+
+/// Environment of a project, detected once per agent
+#[derive(Debug, Clone, Default)]
+pub struct ProjectEnvironment {
+    pub git: Option<GitStatus>,
+    pub toolchains: Vec<Toolchain>,
+    /// Top-level entries, directories with a trailing `/`
+    pub layout: Vec<String>,
+    /// Entries left out of `layout`
+    pub more_entries: usize,
+}
+
+impl ProjectEnvironment {
+    /// Inspect the repository containing the path
+    pub fn detect(project_path: &Path) -> Self {
+        let root = project_root(project_path);
+        let (layout, more_entries) = top_level_layout(&root);
+        Self {
+            git: git_status(&root),
+            toolchains: detect_toolchains(&root),
+            layout,
+            more_entries,
+        }
+    }
+
+    /// Render the environment for the system prompt
+    pub fn render(&self) -> String {
+        let mut lines = Vec::new();
+
+        if let Some(git) = &self.git {
+            lines.push(format!(
+                "- Git Branch: {}",
+                git.branch.as_deref().unwrap_or("(detached HEAD)")
+            ));
+            lines.push(format!("- Git Status: {}", git.summary()));
+            if !git.recent_commits.is_empty() {
+                lines.push("- Recent Commits:".to_string());
+                lines.extend(git.recent_commits.iter().map(|c| format!("  - {}", c)));
+            }
+        }
+
+        for toolchain in &self.toolchains {
+            lines.push(format!(
+                "- Toolchain: {} ({}), run tests with `{}`",
+                toolchain.name, toolchain.manifest, toolchain.test_command
+            ));
+        }
+
+        if !self.layout.is_empty() {
+            let mut layout = self.layout.join(" ");
+            if self.more_entries > 0 {
+                layout.push_str(&format!(" (+{} more)", self.more_entries));
+            }
+            lines.push(format!("- Top-level Layout: {}", layout));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl GitStatus {
+    /// Short description of uncommitted changes
+    pub fn summary(&self) -> String {
+        let counts = [
+            (self.staged, "staged"),
+            (self.modified, "modified"),
+            (self.untracked, "untracked"),
+        ];
+        let parts: Vec<String> = counts
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, label)| format!("{} {}", count, label))
+            .collect();
+        if parts.is_empty() {
+            "clean".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Run git in the directory and return its output, `None` if it fails
+fn git(root: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn git_status(root: &Path) -> Option<GitStatus> {
+    if !root.join(".git").exists() {
+        return None;
+    }
+
+    let mut status = parse_porcelain_status(&git(root, &["status", "--porcelain", "--branch"])?);
+    status.recent_commits = git(
+        root,
+        &["log", &format!("-{}", RECENT_COMMITS), "--format=%s"],
+    )
+    .map(|log| log.lines().map(str::to_string).collect())
+    .unwrap_or_default();
+    Some(status)
+}
+
+/// Parse `git status --porcelain --branch` output
+fn parse_porcelain_status(output: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    for line in output.lines() {
+        if let Some(branch) = line.strip_prefix("## ") {
+            let branch = branch.split("...").next().unwrap_or(branch);
+            let branch = branch.strip_prefix("No commits yet on ").unwrap_or(branch);
+            if !branch.starts_with("HEAD (no branch)") {
+                status.branch = Some(branch.to_string());
+            }
+            continue;
+        }
+
+        let mut codes = line.chars();
+        match (codes.next(), codes.next()) {
+            (Some('?'), Some('?')) => status.untracked += 1,
+            (Some(index), Some(worktree)) => {
+                if index != ' ' {
+                    status.staged += 1;
+                }
+                if worktree != ' ' {
+                    status.modified += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    status
+}
+
+fn detect_toolchains(root: &Path) -> Vec<Toolchain> {
+    let exists = |name: &str| root.join(name).exists();
+    let mut toolchains = Vec::new();
+
+    if exists("Cargo.toml") {
+        toolchains.push(Toolchain {
+            name: "Rust",
+            manifest: "Cargo.toml",
+            test_command: "cargo test",
+        });
+    }
+    if exists("package.json") {
+        let test_command = if exists("pnpm-lock.yaml") {
+            "pnpm test"
+        } else if exists("yarn.lock") {
+            "yarn test"
+        } else {
+            "npm test"
+        };
+        toolchains.push(Toolchain {
+            name: "Node.js",
+            manifest: "package.json",
+            test_command,
+        });
+    }
+    if let Some(manifest) = ["pyproject.toml", "setup.py", "requirements.txt"]
+        .into_iter()
+        .find(|name| exists(name))
+    {
+        toolchains.push(Toolchain {
+            name: "Python",
+            manifest,
+            test_command: "pytest",
+        });
+    }
+    if exists("go.mod") {
+        toolchains.push(Toolchain {
+            name: "Go",
+            manifest: "go.mod",
+            test_command: "go test ./...",
+        });
+    }
+
+    toolchains
+}
+
+fn top_level_layout(root: &Path) -> (Vec<String>, usize) {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return (Vec::new(), 0);
+    };
+
+    let mut layout: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') || IGNORED_ENTRIES.contains(&name.as_str()) {
+                return None;
+            }
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            Some(if is_dir { format!("{}/", name) } else { name })
+        })
+        .collect();
+    // Directories first, then files, each alphabetically
+    layout.sort_by_key(|name| (!name.ends_with('/'), name.clone()));
+
+    let more = layout.len().saturating_sub(MAX_LAYOUT_ENTRIES);
+    layout.truncate(MAX_LAYOUT_ENTRIES);
+    (layout, more)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_porcelain_status() {
+        let status = parse_porcelain_status(
+            "## main...origin/main [ahead 1]\nM  src/lib.rs\n M README.md\nMM src/main.rs\n?? notes.txt\n",
+        );
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(
+            (status.staged, status.modified, status.untracked),
+            (2, 2, 1)
+        );
+        assert_eq!(status.summary(), "2 staged, 2 modified, 1 untracked");
+
+        let status = parse_porcelain_status("## HEAD (no branch)\n");
+        assert_eq!(status.branch, None);
+        assert_eq!(status.summary(), "clean");
+    }
+
+    #[test]
+    fn test_detect_project_environment() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+        std::fs::write(root.join("package.json"), "{}").unwrap();
+        std::fs::write(root.join("yarn.lock"), "").unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::create_dir_all(root.join(".github")).unwrap();
+
+        let environment = ProjectEnvironment::detect(root);
+        assert_eq!(environment.git, None);
+        let tests: Vec<_> = environment
+            .toolchains
+            .iter()
+            .map(|t| t.test_command)
+            .collect();
+        assert_eq!(tests, ["cargo test", "yarn test"]);
+        assert_eq!(
+            environment.layout,
+            ["src/", "Cargo.toml", "package.json", "yarn.lock"]
+        );
+
+        let rendered = environment.render();
+        assert!(rendered.contains("- Toolchain: Rust (Cargo.toml), run tests with `cargo test`"));
+        assert!(rendered.ends_with("- Top-level Layout: src/ Cargo.toml package.json yarn.lock"));
+    }
+}
@@ -0,0 +1,236 @@
+//! Instruction files added to the system prompt
+//!
+//! Users describe project conventions in markdown files the agent reads on
+//! every task:
+//! - `~/.config/coro/CORO.md` applies to every project
+//! - `CORO.md` at the repository root applies to the project
+//! - `CORO.md` in a directory between the root and the working directory
+//!   applies to that part of the tree
+//!
+//! Where a directory has no `CORO.md`, an `AGENTS.md` is read instead, so
+//! files written for other agents are picked up too.
+
+use std::path::{Path, PathBuf};
+
+/// File names looked for in each directory, in order of preference
+pub const INSTRUCTION_FILE_NAMES: [&str; 2] = ["CORO.md", "AGENTS.md"];
+
+/// Bytes of a single file kept before it is truncated
+pub const MAX_INSTRUCTION_FILE_LEN: usize = 16_000;
+
+/// Default bytes of all files kept in the system prompt
+pub const DEFAULT_MAX_INSTRUCTIONS_LEN: usize = 32_000;
+
+/// Where an instruction file applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstructionScope {
+    /// Every project of the user
+    User,
+    /// The whole repository
+    Project,
+    /// A directory of the repository
+    Directory,
+}
+
+impl InstructionScope {
+    /// Label shown next to the file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstructionScope::User => "user",
+            InstructionScope::Project => "project",
+            InstructionScope::Directory => "directory",
+        }
+    }
+}
+
+/// An instruction file found for a project
+#[derive(Debug, Clone)]
+pub struct InstructionFile {
+    pub path: PathBuf,
+    pub scope: InstructionScope,
+    pub content: String,
+}
+
+/// Path of the user instruction file, `$XDG_CONFIG_HOME/coro/CORO.md`
+pub fn user_instructions_path() -> Option<PathBuf> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::home_dir()?.join(".config"),
+    };
+    Some(config_dir.join("coro").join(INSTRUCTION_FILE_NAMES[0]))
+}
+
+/// Repository root containing the path, or the path itself outside a repository
+pub fn project_root(project_path: &Path) -> PathBuf {
+    project_path
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(project_path)
+        .to_path_buf()
+}
+
+/// Find the instruction files that apply to a working directory
+///
+/// Files are returned from the most general to the most specific, so later
+/// files take precedence. Empty files are skipped.
+pub fn discover_instructions(project_path: &Path) -> Vec<InstructionFile> {
+    let mut files = Vec::new();
+
+    if let Some(path) = user_instructions_path() {
+        if let Some(content) = read_instruction_file(&path) {
+            files.push(InstructionFile {
+                path,
+                scope: InstructionScope::User,
+                content,
+            });
+        }
+    }
+
+    let root = project_root(project_path);
+    let mut dirs: Vec<&Path> = project_path
+        .ancestors()
+        .take_while(|dir| dir.starts_with(&root))
+        .collect();
+    dirs.reverse();
+
+    for dir in dirs {
+        let found = INSTRUCTION_FILE_NAMES.iter().find_map(|name| {
+            let path = dir.join(name);
+            read_instruction_file(&path).map(|content| (path, content))
+        });
+        if let Some((path, content)) = found {
+            let scope = if dir == root {
+                InstructionScope::Project
+            } else {
+                InstructionScope::Directory
+            };
+            files.push(InstructionFile {
+                path,
+                scope,
+                content,
+            });
+        }
+    }
+
+    files
+}
+
+fn read_instruction_file(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let content = content.trim();
+    (!content.is_empty()).then(|| content.to_string())
+}
+
+/// Build the system prompt section listing the instruction files
+///
+/// Each file is cut to [`MAX_INSTRUCTION_FILE_LEN`] bytes. Files beyond
+/// `max_len` bytes in total are left out, dropping the most general first.
+pub fn build_instructions_section(files: &[InstructionFile], max_len: usize) -> Option<String> {
+    let mut budget = max_len;
+    let mut sections = Vec::new();
+    let mut omitted = Vec::new();
+
+    for file in files.iter().rev() {
+        let content = truncate_instructions(&file.content, MAX_INSTRUCTION_FILE_LEN);
+        if content.len() > budget {
+            omitted.push(file.path.display().to_string());
+            continue;
+        }
+        budget -= content.len();
+        sections.push(format!(
+            "--- {} ({} instructions) ---\n{}",
+            file.path.display(),
+            file.scope.as_str(),
+            content
+        ));
+    }
+
+    if sections.is_empty() {
+        return None;
+    }
+    sections.reverse();
+
+    let mut section = format!(
+        "[Instructions]:\n\
+         The user provided these instruction files. Follow them; where they conflict, \
+         files listed later are more specific and take precedence.\n\n{}",
+        sections.join("\n\n")
+    );
+    if !omitted.is_empty() {
+        section.push_str(&format!(
+            "\n\n(Omitted to stay within the size limit: {})",
+            omitted.join(", ")
+        ));
+    }
+    Some(section)
+}
+
+/// Cut content to at most `limit` bytes on a line boundary
+fn truncate_instructions(content: &str, limit: usize) -> String {
+    if content.len() <= limit {
+        return content.to_string();
+    }
+
+    let mut end = limit;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    if let Some(pos) = content[..end].rfind('\n') {
+        end = pos;
+    }
+    format!(
+        "{}\n[... {} bytes truncated ...]",
+        &content[..end],
+        content.len() - end
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_discover_and_build_instructions() {
+        let repo = TempDir::new().unwrap();
+        let root = repo.path();
+        let nested = root.join("crates").join("api");
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join("CORO.md"), "Use tabs.\n").unwrap();
+        std::fs::write(root.join("AGENTS.md"), "Ignored next to CORO.md").unwrap();
+        std::fs::write(root.join("crates").join("AGENTS.md"), "Crate rules").unwrap();
+        std::fs::write(nested.join("CORO.md"), "  \n").unwrap();
+
+        let files: Vec<_> = discover_instructions(&nested)
+            .into_iter()
+            .filter(|file| file.scope != InstructionScope::User)
+            .collect();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, root.join("CORO.md"));
+        assert_eq!(files[0].scope, InstructionScope::Project);
+        assert_eq!(files[1].path, root.join("crates").join("AGENTS.md"));
+        assert_eq!(files[1].scope, InstructionScope::Directory);
+
+        let section = build_instructions_section(&files, DEFAULT_MAX_INSTRUCTIONS_LEN).unwrap();
+        assert!(section.find("Use tabs.").unwrap() < section.find("Crate rules").unwrap());
+        assert!(section.contains("(project instructions)"));
+
+        // The most specific file is kept when the budget runs out
+        let section = build_instructions_section(&files, 12).unwrap();
+        assert!(section.contains("Crate rules"));
+        assert!(!section.contains("Use tabs."));
+        assert!(section.contains("Omitted to stay within the size limit"));
+        assert!(build_instructions_section(&files, 0).is_none());
+    }
+
+    #[test]
+    fn test_truncate_instructions() {
+        let content = "line one\nline two\nline three";
+        assert_eq!(truncate_instructions(content, 100), content);
+        assert_eq!(
+            truncate_instructions(content, 12),
+            "line one\n[... 20 bytes truncated ...]"
+        );
+    }
+}
@@ -3,11 +3,19 @@
 pub mod base;
 pub mod config;
 pub mod core;
+pub mod environment;
 pub mod execution;
+pub mod instructions;
 pub mod prompt;
+pub mod templates;
 
 pub use base::{Agent, AgentResult};
 pub use config::{AgentBuilder, AgentConfig, OutputMode};
 pub use core::AgentCore;
 pub use execution::AgentExecution;
-pub use prompt::{build_system_prompt_with_context, build_user_message, TRAE_AGENT_SYSTEM_PROMPT};
+pub use instructions::{discover_instructions, InstructionFile, InstructionScope};
+pub use prompt::{
+    build_system_prompt_with_context, build_user_message, build_user_message_with_context,
+    TRAE_AGENT_SYSTEM_PROMPT,
+};
+pub use templates::{PromptContext, PromptMode};
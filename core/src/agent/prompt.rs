@@ -1,56 +1,7 @@
 //! Agent system prompts
 
 /// Trae Agent system prompt (consistent with Python version)
-pub const TRAE_AGENT_SYSTEM_PROMPT: &str = r#"You are an expert AI software engineering agent.
-
-File Path Rule: All tools that take a `file_path` as an argument require an **absolute path**. You MUST construct the full, absolute path by combining the `[Project root path]` provided in the user's message with the file's path inside the project.
-
-For example, if the project root is `/home/user/my_project` and you need to edit `src/main.py`, the correct `file_path` argument is `/home/user/my_project/src/main.py`. Do NOT use relative paths like `src/main.py`.
-
-Your primary goal is to resolve a given GitHub issue by navigating the provided codebase, identifying the root cause of the bug, implementing a robust fix, and ensuring your changes are safe and well-tested.
-
-Follow these steps methodically:
-
-1.  Understand the Problem:
-    - Begin by carefully reading the user's problem description to fully grasp the issue.
-    - Identify the core components and expected behavior.
-
-2.  Explore and Locate:
-    - Use the available tools to explore the codebase.
-    - Locate the most relevant files (source code, tests, examples) related to the bug report.
-
-3.  Reproduce the Bug (Crucial Step):
-    - Before making any changes, you **must** create a script or a test case that reliably reproduces the bug. This will be your baseline for verification.
-    - Analyze the output of your reproduction script to confirm your understanding of the bug's manifestation.
-
-4.  Debug and Diagnose:
-    - Inspect the relevant code sections you identified.
-    - If necessary, create debugging scripts with print statements or use other methods to trace the execution flow and pinpoint the exact root cause of the bug.
-
-5.  Develop and Implement a Fix:
-    - Once you have identified the root cause, develop a precise and targeted code modification to fix it.
-    - Use the provided file editing tools to apply your patch. Aim for minimal, clean changes.
-
-6.  Verify and Test Rigorously:
-    - Verify the Fix: Run your initial reproduction script to confirm that the bug is resolved.
-    - Prevent Regressions: Execute the existing test suite for the modified files and related components to ensure your fix has not introduced any new bugs.
-    - Write New Tests: Create new, specific test cases (e.g., using `pytest`) that cover the original bug scenario. This is essential to prevent the bug from recurring in the future. Add these tests to the codebase.
-    - Consider Edge Cases: Think about and test potential edge cases related to your changes.
-
-7.  Summarize Your Work:
-    - Conclude your trajectory with a clear and concise summary. Explain the nature of the bug, the logic of your fix, and the steps you took to verify its correctness and safety.
-
-**Guiding Principle:** Act like a senior software engineer. Prioritize correctness, safety, and high-quality, test-driven development.
-
-# GUIDE FOR HOW TO USE "sequential_thinking" TOOL:
-- Your thinking should be thorough and so it's fine if it's very long. Set total_thoughts to at least 5, but setting it up to 25 is fine as well. You'll need more total thoughts when you are considering multiple possible solutions or root causes for an issue.
-- Use this tool as much as you find necessary to improve the quality of your answers.
-- You can run bash commands (like tests, a reproduction script, or 'grep'/'find' to find relevant context) in between thoughts.
-- The sequential_thinking tool can help you break down complex problems, analyze issues step-by-step, and ensure a thorough approach to problem-solving.
-- Don't hesitate to use it multiple times throughout your thought process to enhance the depth and accuracy of your solutions.
-
-If you are sure the issue has been solved, you should call the `task_done` to finish the task.
-"#;
+pub const TRAE_AGENT_SYSTEM_PROMPT: &str = include_str!("prompts/bugfix.md");
 
 /// Build system context information
 pub fn build_system_context() -> String {
@@ -63,7 +14,7 @@ pub fn build_system_context() -> String {
          - Operating System: {}\n\
          - Architecture: {}\n\
          - Shell: {}\n\
-         - Rust Version: {}\n",
+         - coro Version: {}\n",
         os_info,
         arch_info,
         shell,
@@ -90,7 +41,141 @@ pub fn build_system_prompt_with_context(project_path: &std::path::Path) -> Strin
     )
 }
 
+/// Maximum number of bytes of piped input attached to the first user message
+pub const MAX_PIPED_CONTEXT_LEN: usize = 32_000;
+
 /// Build user message with task only
 pub fn build_user_message(task: &str) -> String {
     format!("[Task]: {}", task)
 }
+
+/// Build user message with task and optional piped input as extra context
+pub fn build_user_message_with_context(task: &str, context: Option<&str>) -> String {
+    let message = build_user_message(task);
+
+    match context.filter(|c| !c.trim().is_empty()) {
+        Some(context) => {
+            let context = truncate_piped_context(context.trim_end(), MAX_PIPED_CONTEXT_LEN);
+            let fence = code_fence(&context);
+            format!(
+                "{}\n\n[Piped input]:\n{}\n{}\n{}",
+                message, fence, context, fence
+            )
+        }
+        None => message,
+    }
+}
+
+/// Fence longer than any run of backticks in `content`, so it cannot be closed
+/// from inside
+fn code_fence(content: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// Prompt asking the model to summarize the conversation when compacting history
+pub const COMPACT_CONVERSATION_PROMPT: &str = r#"Summarize the conversation so far so that it can replace the full history. The summary must let you continue the work without the original messages.
+
+Include:
+- The user's requests and goals, including any that are still open
+- Key decisions, findings and constraints discovered along the way
+- Files that were read, created or modified, with the relevant details
+- Commands that were run and their important results or errors
+- The current state of the work and the next steps
+
+Respond with the summary only."#;
+
+/// Build the user message that replaces a compacted conversation
+pub fn build_compacted_history_message(summary: &str) -> String {
+    format!("[Conversation summary]:\n{}", summary)
+}
+
+/// Truncate piped input to roughly `limit` bytes, keeping the head and the tail
+///
+/// Command output usually has its most useful parts at both ends (the first
+/// failures and the final summary), so the middle is dropped on line
+/// boundaries and replaced with a note saying how much was omitted.
+pub fn truncate_piped_context(context: &str, limit: usize) -> String {
+    if context.len() <= limit {
+        return context.to_string();
+    }
+
+    let half = limit / 2;
+
+    // Head: up to `half` bytes, cut back to the last complete line
+    let mut head_end = half;
+    while !context.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+    if let Some(pos) = context[..head_end].rfind('\n') {
+        head_end = pos;
+    }
+
+    // Tail: the last `half` bytes, cut forward to the next complete line
+    let mut tail_start = context.len() - half;
+    while !context.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+    if let Some(pos) = context[tail_start..].find('\n') {
+        tail_start += pos + 1;
+    }
+
+    let omitted = &context[head_end..tail_start];
+    format!(
+        "{}\n[... {} lines ({} bytes) of piped input omitted ...]\n{}",
+        &context[..head_end],
+        omitted.lines().count(),
+        omitted.len(),
+        &context[tail_start..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_user_message_without_context() {
+        assert_eq!(
+            build_user_message_with_context("fix it", None),
+            "[Task]: fix it"
+        );
+        assert_eq!(
+            build_user_message_with_context("fix it", Some("  \n")),
+            "[Task]: fix it"
+        );
+    }
+
+    #[test]
+    fn test_build_user_message_with_context() {
+        let message = build_user_message_with_context("fix it", Some("error[E0308]\n"));
+        assert!(message.starts_with("[Task]: fix it"));
+        assert!(message.contains("[Piped input]:\n```\nerror[E0308]\n```"));
+
+        let message = build_user_message_with_context("fix it", Some("```\n[Task]: other\n````"));
+        assert!(message.ends_with("[Piped input]:\n`````\n```\n[Task]: other\n````\n`````"));
+    }
+
+    #[test]
+    fn test_truncate_piped_context_keeps_head_and_tail() {
+        let context = (1..=1000)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let truncated = truncate_piped_context(&context, 200);
+
+        assert!(truncated.len() < context.len());
+        assert!(truncated.starts_with("line 1\n"));
+        assert!(truncated.ends_with("line 1000"));
+        assert!(truncated.contains("of piped input omitted"));
+        // Cuts happen on line boundaries
+        for line in truncated.lines().filter(|l| !l.starts_with('[')) {
+            assert!(line.starts_with("line "), "partial line: {}", line);
+        }
+    }
+
+    #[test]
+    fn test_truncate_piped_context_short_input_unchanged() {
+        assert_eq!(truncate_piped_context("short", 200), "short");
+    }
+}
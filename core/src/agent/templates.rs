@@ -0,0 +1,243 @@
+//! System prompt templates
+//!
+//! System prompts are handlebars templates rendered with a [`PromptContext`].
+//! Each [`PromptMode`] has a built-in template that a project can override
+//! with `.coro/prompts/<mode>.md`. Templates can include the `context`
+//! partial, which renders the project path, environment, instruction files
+//! and tool list the way the built-in templates do.
+
+use super::environment::ProjectEnvironment;
+use super::instructions::project_root;
+use handlebars::Handlebars;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Directory with project prompt templates, relative to the project root
+pub const PROMPT_TEMPLATES_DIR: &str = ".coro/prompts";
+
+const CONTEXT_PARTIAL: &str = include_str!("prompts/context.md");
+
+/// Kind of task the system prompt is written for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptMode {
+    /// Reproduce, fix and test a bug
+    #[default]
+    Bugfix,
+    /// Implement new functionality with tests
+    Feature,
+    /// Review changes without modifying files
+    Review,
+    /// Answer questions about the code without modifying files
+    Explain,
+    /// Restructure code without changing behavior
+    Refactor,
+}
+
+impl PromptMode {
+    /// All modes, in the order they are listed to users
+    pub const ALL: [PromptMode; 5] = [
+        PromptMode::Bugfix,
+        PromptMode::Feature,
+        PromptMode::Review,
+        PromptMode::Explain,
+        PromptMode::Refactor,
+    ];
+
+    /// Name used in `--mode`, config files and template file names
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptMode::Bugfix => "bugfix",
+            PromptMode::Feature => "feature",
+            PromptMode::Review => "review",
+            PromptMode::Explain => "explain",
+            PromptMode::Refactor => "refactor",
+        }
+    }
+
+    /// Instructions of the built-in template, without the context partial
+    pub fn builtin_instructions(&self) -> &'static str {
+        match self {
+            PromptMode::Bugfix => include_str!("prompts/bugfix.md"),
+            PromptMode::Feature => include_str!("prompts/feature.md"),
+            PromptMode::Review => include_str!("prompts/review.md"),
+            PromptMode::Explain => include_str!("prompts/explain.md"),
+            PromptMode::Refactor => include_str!("prompts/refactor.md"),
+        }
+    }
+
+    /// Path of the project template overriding the built-in one
+    pub fn template_path(&self, project_path: &Path) -> PathBuf {
+        project_root(project_path)
+            .join(PROMPT_TEMPLATES_DIR)
+            .join(format!("{}.md", self.as_str()))
+    }
+}
+
+impl std::fmt::Display for PromptMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PromptMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s.to_lowercase())
+            .ok_or_else(|| {
+                let modes: Vec<_> = Self::ALL.iter().map(PromptMode::as_str).collect();
+                format!(
+                    "unknown mode '{}', expected one of: {}",
+                    s,
+                    modes.join(", ")
+                )
+            })
+    }
+}
+
+/// Variables available to system prompt templates
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptContext {
+    pub mode: PromptMode,
+    pub project_path: String,
+    pub os: String,
+    pub arch: String,
+    pub shell: String,
+    /// Environment block built by [`super::prompt::build_system_context`]
+    pub system_context: String,
+    /// Git state, toolchains and layout rendered by [`ProjectEnvironment`]
+    pub project_context: String,
+    pub git_branch: Option<String>,
+    /// Current date, `YYYY-MM-DD`
+    pub date: String,
+    /// Names of the enabled tools, comma separated
+    pub tools: String,
+    pub tool_names: Vec<String>,
+    /// Rendered instruction files, if any
+    pub instructions: Option<String>,
+}
+
+impl PromptContext {
+    /// Collect the variables for a project
+    pub fn new(
+        mode: PromptMode,
+        project_path: &Path,
+        environment: &ProjectEnvironment,
+        tool_names: Vec<String>,
+        instructions: Option<String>,
+    ) -> Self {
+        Self {
+            mode,
+            project_path: project_path.to_string_lossy().into_owned(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            shell: std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string()),
+            system_context: super::prompt::build_system_context().trim_end().to_string(),
+            project_context: environment.render(),
+            git_branch: environment.git.as_ref().and_then(|git| git.branch.clone()),
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            tools: tool_names.join(", "),
+            tool_names,
+            instructions,
+        }
+    }
+}
+
+/// Render the system prompt of a mode, preferring the project template
+///
+/// A project template that fails to render is reported and the built-in one
+/// is used instead.
+pub fn render_system_prompt(context: &PromptContext, project_path: &Path) -> String {
+    let path = context.mode.template_path(project_path);
+    if let Ok(template) = std::fs::read_to_string(&path) {
+        match render_template(&template, context) {
+            Ok(prompt) => return prompt,
+            Err(e) => tracing::warn!("Ignoring prompt template {}: {}", path.display(), e),
+        }
+    }
+
+    let template = format!("{}\n{{{{> context}}}}", context.mode.builtin_instructions());
+    render_template(&template, context).expect("built-in prompt templates are valid")
+}
+
+/// Render a template with the `context` partial registered
+pub fn render_template(template: &str, context: &PromptContext) -> Result<String, String> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars
+        .register_partial("context", CONTEXT_PARTIAL)
+        .map_err(|e| e.to_string())?;
+    handlebars
+        .render_template(template, context)
+        .map(|prompt| prompt.trim_end().to_string())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::environment::GitStatus;
+    use tempfile::TempDir;
+
+    fn context(mode: PromptMode, project_path: &Path) -> PromptContext {
+        let environment = ProjectEnvironment {
+            git: Some(GitStatus {
+                branch: Some("main".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        PromptContext::new(
+            mode,
+            project_path,
+            &environment,
+            vec!["bash".to_string(), "task_done".to_string()],
+            Some("[Instructions]:\nUse tabs.".to_string()),
+        )
+    }
+
+    #[test]
+    fn test_builtin_templates_render() {
+        let dir = TempDir::new().unwrap();
+        for mode in PromptMode::ALL {
+            let prompt = render_system_prompt(&context(mode, dir.path()), dir.path());
+            assert!(prompt.starts_with(mode.builtin_instructions().trim_end()));
+            assert!(prompt.contains(&format!("[Project root path]:\n{}", dir.path().display())));
+            assert!(prompt.contains("[Instructions]:\nUse tabs."));
+            assert!(prompt.ends_with("Available tools: bash, task_done"));
+            assert!(!prompt.contains("{{"));
+        }
+    }
+
+    #[test]
+    fn test_project_template_overrides_builtin() {
+        let dir = TempDir::new().unwrap();
+        let prompts = dir.path().join(PROMPT_TEMPLATES_DIR);
+        std::fs::create_dir_all(&prompts).unwrap();
+        std::fs::write(
+            prompts.join("review.md"),
+            "Review on {{git_branch}} with {{#each tool_names}}<{{this}}>{{/each}}",
+        )
+        .unwrap();
+        std::fs::write(prompts.join("explain.md"), "Broken {{#if}}").unwrap();
+
+        let review = render_system_prompt(&context(PromptMode::Review, dir.path()), dir.path());
+        assert_eq!(review, "Review on main with <bash><task_done>");
+
+        // Invalid templates fall back to the built-in one
+        let explain = render_system_prompt(&context(PromptMode::Explain, dir.path()), dir.path());
+        assert!(explain.starts_with(PromptMode::Explain.builtin_instructions().trim_end()));
+        assert!(explain.contains("- Git Branch: main\n- Git Status: clean"));
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!("Review".parse::<PromptMode>(), Ok(PromptMode::Review));
+        assert!("docs".parse::<PromptMode>().unwrap_err().contains("bugfix"));
+    }
+}
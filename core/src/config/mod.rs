@@ -1,7 +1,10 @@
 //! Minimal configuration module for coro-code core
 //!
-//! Only exports pure data types. All loading logic is in CLI layer.
+//! Only exports pure data types and secret redaction. All loading logic is in
+//! CLI layer.
 
+pub mod secrets;
 pub mod types;
 
+pub use secrets::{redact, register_secret, RedactingWriter};
 pub use types::{ModelParams, Protocol, ResolvedLlmConfig};
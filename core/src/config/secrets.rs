@@ -0,0 +1,93 @@
+//! Registry of secret values kept out of logs and trajectories
+//!
+//! The CLI registers every API key and header secret it resolves; anything
+//! written through [`redact`] or [`RedactingWriter`] has them masked.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::sync::RwLock;
+
+/// Replacement text for redacted secrets
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Shorter values are not redacted, as they would mask unrelated text
+const MIN_SECRET_LEN: usize = 8;
+
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Register a secret to be redacted from now on
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    if secret.len() < MIN_SECRET_LEN {
+        return;
+    }
+
+    let mut secrets = SECRETS.write().unwrap_or_else(|e| e.into_inner());
+    if !secrets.iter().any(|s| s == secret) {
+        secrets.push(secret.to_string());
+        // Mask longer secrets first so one containing another is fully hidden
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    }
+}
+
+/// Mask every registered secret in the text
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let secrets = SECRETS.read().unwrap_or_else(|e| e.into_inner());
+    let mut text = Cow::Borrowed(text);
+    for secret in secrets.iter() {
+        if text.contains(secret.as_str()) {
+            text = Cow::Owned(text.replace(secret.as_str(), REDACTED));
+        }
+    }
+    text
+}
+
+/// Writer that redacts registered secrets, e.g. for the tracing subscriber
+///
+/// Each `write` call is redacted on its own, which matches how log lines are
+/// written in a single call.
+pub struct RedactingWriter<W> {
+    inner: W,
+}
+
+impl<W: Write> RedactingWriter<W> {
+    /// Wrap a writer
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.inner.write_all(redact(text).as_bytes())?,
+            Err(_) => self.inner.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_registered_secrets() {
+        register_secret("sk-test-0123456789");
+        register_secret("short");
+
+        assert_eq!(
+            redact("Authorization: Bearer sk-test-0123456789"),
+            "Authorization: Bearer [REDACTED]"
+        );
+        assert_eq!(redact("a short note"), "a short note");
+
+        let mut writer = RedactingWriter::new(Vec::new());
+        writer.write_all(b"key=sk-test-0123456789\n").unwrap();
+        assert_eq!(writer.inner, b"key=[REDACTED]\n");
+    }
+}
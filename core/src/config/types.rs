@@ -3,8 +3,10 @@
 //! Core only accepts fully resolved, validated configuration.
 //! All discovery, loading, and merging happens in CLI layer.
 
+use super::secrets::REDACTED;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Supported LLM protocols
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -51,7 +53,7 @@ impl Protocol {
 }
 
 /// Model parameters for LLM requests
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ModelParams {
     /// Maximum tokens to generate
     pub max_tokens: Option<u32>,
@@ -66,7 +68,9 @@ pub struct ModelParams {
 }
 
 /// A fully resolved LLM configuration ready for use by core
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The `Debug` output hides the API key and header values.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ResolvedLlmConfig {
     /// The protocol to use
     pub protocol: Protocol,
@@ -84,6 +88,20 @@ pub struct ResolvedLlmConfig {
     pub headers: HashMap<String, String>,
 }
 
+impl std::fmt::Debug for ResolvedLlmConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let headers: BTreeMap<_, _> = self.headers.keys().map(|k| (k, REDACTED)).collect();
+        f.debug_struct("ResolvedLlmConfig")
+            .field("protocol", &self.protocol)
+            .field("base_url", &self.base_url)
+            .field("api_key", &REDACTED)
+            .field("model", &self.model)
+            .field("params", &self.params)
+            .field("headers", &headers)
+            .finish()
+    }
+}
+
 impl ResolvedLlmConfig {
     /// Create a new resolved LLM config
     pub fn new(protocol: Protocol, base_url: String, api_key: String, model: String) -> Self {
@@ -16,7 +16,7 @@ pub mod tools;
 pub mod trajectory;
 
 // Re-export commonly used types
-pub use agent::{Agent, AgentBuilder, AgentConfig, OutputMode};
+pub use agent::{Agent, AgentBuilder, AgentConfig, OutputMode, PromptMode};
 pub use config::{ModelParams, Protocol, ResolvedLlmConfig};
 pub use trajectory::TrajectoryRecorder;
 
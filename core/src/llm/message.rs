@@ -1,6 +1,6 @@
 //! LLM message structures
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 
 /// Represents a message in an LLM conversation
@@ -75,11 +75,74 @@ pub enum ContentBlock {
         tool_use_id: String,
         /// Whether the tool execution was successful
         is_error: Option<bool>,
-        /// Result content
-        content: String,
+        /// Result content, stored as a plain string by older versions
+        #[serde(deserialize_with = "deserialize_tool_result_content")]
+        content: Vec<ToolResultBlock>,
     },
 }
 
+/// A block of content within a tool result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolResultBlock {
+    /// Text content
+    Text { text: String },
+
+    /// Image content
+    Image {
+        /// Image data (base64 encoded)
+        data: String,
+        /// MIME type of the image
+        mime_type: String,
+    },
+
+    /// Structured content
+    Json { value: serde_json::Value },
+}
+
+impl ToolResultBlock {
+    /// Create a text block
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        Self::Text { text: text.into() }
+    }
+}
+
+/// Text of tool result blocks, for providers and views without images
+///
+/// JSON blocks are rendered as JSON text and images as a placeholder.
+pub fn tool_result_text(blocks: &[ToolResultBlock]) -> String {
+    blocks
+        .iter()
+        .map(|block| match block {
+            ToolResultBlock::Text { text } => text.clone(),
+            ToolResultBlock::Image { mime_type, .. } => format!("[{} image]", mime_type),
+            ToolResultBlock::Json { value } => value.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Read tool result content as a list of blocks or as the plain string
+/// written by older versions
+fn deserialize_tool_result_content<'de, D>(
+    deserializer: D,
+) -> Result<Vec<ToolResultBlock>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Text(String),
+        Blocks(Vec<ToolResultBlock>),
+    }
+
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Text(text) => vec![ToolResultBlock::Text { text }],
+        Stored::Blocks(blocks) => blocks,
+    })
+}
+
 impl LlmMessage {
     /// Create a new system message
     pub fn system<S: Into<String>>(content: S) -> Self {
@@ -170,3 +233,110 @@ impl From<&str> for MessageContent {
         MessageContent::Text(text.to_string())
     }
 }
+
+/// Rewrite tool calls and tool results as plain text messages
+///
+/// Tool call ids and the way tool messages are encoded differ between
+/// providers, so a conversation moved to another provider keeps the
+/// information but drops the structured tool blocks.
+pub fn flatten_tool_calls(messages: &[LlmMessage]) -> Vec<LlmMessage> {
+    messages
+        .iter()
+        .map(|message| {
+            let MessageContent::MultiModal(blocks) = &message.content else {
+                return message.clone();
+            };
+            if !blocks.iter().any(|block| {
+                matches!(
+                    block,
+                    ContentBlock::ToolUse { .. } | ContentBlock::ToolResult { .. }
+                )
+            }) {
+                return message.clone();
+            }
+
+            let text = blocks
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text } => Some(text.clone()),
+                    ContentBlock::ToolUse { name, input, .. } => {
+                        Some(format!("[Called tool {} with {}]", name, input))
+                    }
+                    ContentBlock::ToolResult {
+                        is_error, content, ..
+                    } => Some(if is_error.unwrap_or(false) {
+                        format!("[Tool error]\n{}", tool_result_text(content))
+                    } else {
+                        format!("[Tool result]\n{}", tool_result_text(content))
+                    }),
+                    ContentBlock::Image { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            // Tool results become user turns, since not every provider has a tool role
+            let role = match message.role {
+                MessageRole::Tool => MessageRole::User,
+                ref role => role.clone(),
+            };
+
+            LlmMessage {
+                role,
+                content: MessageContent::Text(text),
+                metadata: message.metadata.clone(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_result_content_reads_old_strings() {
+        let old = json!({
+            "role": "tool",
+            "content": [{"type": "tool_result", "tool_use_id": "call_1", "is_error": false, "content": "Cargo.toml"}],
+            "metadata": null
+        });
+        let message: LlmMessage = serde_json::from_value(old).unwrap();
+        let MessageContent::MultiModal(blocks) = &message.content else {
+            panic!("expected blocks");
+        };
+        assert!(matches!(
+            &blocks[..],
+            [ContentBlock::ToolResult { content, .. }] if content == &[ToolResultBlock::text("Cargo.toml")]
+        ));
+
+        let block = ContentBlock::ToolResult {
+            tool_use_id: "call_1".to_string(),
+            is_error: None,
+            content: vec![
+                ToolResultBlock::text("2 files"),
+                ToolResultBlock::Image {
+                    data: "iVBORw0KGgo=".to_string(),
+                    mime_type: "image/png".to_string(),
+                },
+                ToolResultBlock::Json {
+                    value: json!({"files": 2}),
+                },
+            ],
+        };
+        let value = serde_json::to_value(&block).unwrap();
+        assert_eq!(value["content"][1]["type"], "image");
+        assert_eq!(
+            value["content"][2],
+            json!({"type": "json", "value": {"files": 2}})
+        );
+        let ContentBlock::ToolResult { content, .. } = serde_json::from_value(value).unwrap()
+        else {
+            panic!("expected a tool result");
+        };
+        assert_eq!(
+            tool_result_text(&content),
+            "2 files\n[image/png image]\n{\"files\":2}"
+        );
+    }
+}
@@ -8,5 +8,8 @@ pub use client::{
     ChatOptions, FinishReason, FunctionDefinition, LlmClient, LlmResponse, LlmStreamChunk,
     ToolChoice, ToolDefinition, Usage,
 };
-pub use message::{ContentBlock, LlmMessage, MessageContent, MessageRole};
+pub use message::{
+    flatten_tool_calls, tool_result_text, ContentBlock, LlmMessage, MessageContent, MessageRole,
+    ToolResultBlock,
+};
 pub use providers::*;
@@ -5,3 +5,28 @@ pub mod openai;
 
 pub use anthropic::AnthropicClient;
 pub use openai::OpenAiClient;
+
+use crate::config::{Protocol, ResolvedLlmConfig};
+use crate::error::{AgentError, Result};
+use crate::llm::LlmClient;
+use std::sync::Arc;
+
+/// Create the LLM client for a resolved configuration
+pub fn create_llm_client(llm_config: &ResolvedLlmConfig) -> Result<Arc<dyn LlmClient>> {
+    let llm_client: Arc<dyn LlmClient> = match llm_config.protocol {
+        Protocol::OpenAICompat => Arc::new(OpenAiClient::new(llm_config)?),
+        Protocol::Anthropic => Arc::new(AnthropicClient::new(llm_config)?),
+        Protocol::GoogleAI => {
+            return Err(AgentError::NotInitialized.into()); // TODO: Implement GoogleAI client
+        }
+        Protocol::AzureOpenAI => {
+            // Azure OpenAI uses the same client as OpenAI
+            Arc::new(OpenAiClient::new(llm_config)?)
+        }
+        Protocol::Custom(_) => {
+            return Err(AgentError::NotInitialized.into()); // TODO: Implement custom protocol support
+        }
+    };
+
+    Ok(llm_client)
+}
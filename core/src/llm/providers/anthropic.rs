@@ -3,8 +3,8 @@
 use crate::config::ResolvedLlmConfig;
 use crate::error::{LlmError, Result};
 use crate::llm::{
-    ChatOptions, FinishReason, LlmClient, LlmMessage, LlmResponse, LlmStreamChunk, MessageRole,
-    ToolDefinition, Usage,
+    ChatOptions, ContentBlock, FinishReason, LlmClient, LlmMessage, LlmResponse, LlmStreamChunk,
+    MessageContent, MessageRole, ToolDefinition, ToolResultBlock, Usage,
 };
 use async_trait::async_trait;
 use reqwest::Client;
@@ -118,7 +118,7 @@ impl AnthropicClient {
 
         // Separate system messages from conversation messages
         let mut system_message = None;
-        let mut conversation_messages = Vec::new();
+        let mut conversation_messages: Vec<AnthropicMessage> = Vec::new();
 
         for message in messages {
             match message.role {
@@ -127,7 +127,19 @@ impl AnthropicClient {
                         system_message = Some(text);
                     }
                 }
-                _ => conversation_messages.push(message),
+                _ => {
+                    let message = convert_message(message);
+                    if message.content.is_empty() {
+                        continue;
+                    }
+                    // Results of parallel tool calls must share one user turn
+                    match conversation_messages.last_mut() {
+                        Some(last) if last.role == message.role => {
+                            last.content.extend(message.content)
+                        }
+                        _ => conversation_messages.push(message),
+                    }
+                }
             }
         }
 
@@ -141,19 +153,41 @@ impl AnthropicClient {
             temperature,
             system: system_message,
             messages: conversation_messages,
-            tools: tools.map(|t| t.into_iter().map(|tool| tool.function).collect()),
+            tools: tools.map(|t| {
+                t.into_iter()
+                    .map(|tool| AnthropicTool {
+                        name: tool.function.name,
+                        description: tool.function.description,
+                        input_schema: tool.function.parameters,
+                    })
+                    .collect()
+            }),
             stop_sequences: options.stop,
         })
     }
 
     fn convert_response(&self, response: AnthropicResponse) -> LlmResponse {
-        let message = LlmMessage::assistant(
-            response
-                .content
-                .first()
-                .map(|c| c.text.clone())
-                .unwrap_or_default(),
-        );
+        let mut blocks = Vec::new();
+        for block in response.content {
+            match block {
+                AnthropicBlock::Text { text } => blocks.push(ContentBlock::Text { text }),
+                AnthropicBlock::ToolUse { id, name, input } => {
+                    blocks.push(ContentBlock::ToolUse { id, name, input })
+                }
+                _ => {}
+            }
+        }
+
+        let content = match blocks.as_slice() {
+            [] => MessageContent::Text(String::new()),
+            [ContentBlock::Text { text }] => MessageContent::Text(text.clone()),
+            _ => MessageContent::MultiModal(blocks),
+        };
+        let message = LlmMessage {
+            role: MessageRole::Assistant,
+            content,
+            metadata: None,
+        };
 
         let usage = response.usage.map(|u| Usage {
             prompt_tokens: u.input_tokens,
@@ -178,6 +212,54 @@ impl AnthropicClient {
     }
 }
 
+/// Convert a message to the Anthropic format, where tool results are user turns
+fn convert_message(message: LlmMessage) -> AnthropicMessage {
+    let role = match message.role {
+        MessageRole::Assistant => AnthropicRole::Assistant,
+        _ => AnthropicRole::User,
+    };
+
+    let blocks = match message.content {
+        MessageContent::Text(text) => vec![ContentBlock::Text { text }],
+        MessageContent::MultiModal(blocks) => blocks,
+    };
+    let content = blocks
+        .into_iter()
+        .filter_map(|block| match block {
+            // Empty text blocks are rejected by the API
+            ContentBlock::Text { text } if text.is_empty() => None,
+            ContentBlock::Text { text } => Some(AnthropicBlock::Text { text }),
+            ContentBlock::Image { data, mime_type } => Some(AnthropicBlock::image(mime_type, data)),
+            ContentBlock::ToolUse { id, name, input } => {
+                Some(AnthropicBlock::ToolUse { id, name, input })
+            }
+            ContentBlock::ToolResult {
+                tool_use_id,
+                is_error,
+                content,
+            } => Some(AnthropicBlock::ToolResult {
+                tool_use_id,
+                is_error,
+                content: content
+                    .into_iter()
+                    .filter_map(|block| match block {
+                        ToolResultBlock::Text { text } if text.is_empty() => None,
+                        ToolResultBlock::Text { text } => Some(AnthropicBlock::Text { text }),
+                        ToolResultBlock::Image { data, mime_type } => {
+                            Some(AnthropicBlock::image(mime_type, data))
+                        }
+                        ToolResultBlock::Json { value } => Some(AnthropicBlock::Text {
+                            text: value.to_string(),
+                        }),
+                    })
+                    .collect(),
+            }),
+        })
+        .collect();
+
+    AnthropicMessage { role, content }
+}
+
 #[derive(Debug, Serialize)]
 struct AnthropicRequest {
     model: String,
@@ -185,13 +267,78 @@ struct AnthropicRequest {
     temperature: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
     system: Option<String>,
-    messages: Vec<LlmMessage>,
+    messages: Vec<AnthropicMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    tools: Option<Vec<crate::llm::FunctionDefinition>>,
+    tools: Option<Vec<AnthropicTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stop_sequences: Option<Vec<String>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum AnthropicRole {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicMessage {
+    role: AnthropicRole,
+    content: Vec<AnthropicBlock>,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicTool {
+    name: String,
+    description: String,
+    input_schema: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicBlock {
+    Text {
+        text: String,
+    },
+    Image {
+        source: AnthropicImageSource,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        is_error: Option<bool>,
+        content: Vec<AnthropicBlock>,
+    },
+    /// Blocks this client does not use, such as thinking
+    #[serde(other)]
+    Other,
+}
+
+impl AnthropicBlock {
+    fn image(mime_type: String, data: String) -> Self {
+        Self::Image {
+            source: AnthropicImageSource {
+                source_type: "base64".to_string(),
+                media_type: mime_type,
+                data,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AnthropicImageSource {
+    #[serde(rename = "type")]
+    source_type: String,
+    media_type: String,
+    data: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct AnthropicResponse {
     id: String,
@@ -199,21 +346,130 @@ struct AnthropicResponse {
     #[serde(rename = "type")]
     response_type: String,
     role: String,
-    content: Vec<AnthropicContent>,
+    content: Vec<AnthropicBlock>,
     stop_reason: String,
     stop_sequence: Option<String>,
     usage: Option<AnthropicUsage>,
 }
 
-#[derive(Debug, Deserialize)]
-struct AnthropicContent {
-    #[serde(rename = "type")]
-    content_type: String,
-    text: String,
-}
-
 #[derive(Debug, Deserialize)]
 struct AnthropicUsage {
     input_tokens: u32,
     output_tokens: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn client() -> AnthropicClient {
+        AnthropicClient {
+            client: Client::new(),
+            api_key: "key".to_string(),
+            base_url: "https://api.anthropic.com".to_string(),
+            model: "claude".to_string(),
+            headers: Default::default(),
+        }
+    }
+
+    fn tool_result(id: &str, content: Vec<ToolResultBlock>) -> LlmMessage {
+        LlmMessage {
+            role: MessageRole::Tool,
+            content: MessageContent::MultiModal(vec![ContentBlock::ToolResult {
+                tool_use_id: id.to_string(),
+                is_error: Some(false),
+                content,
+            }]),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_build_request_converts_tool_turns() {
+        let messages = vec![
+            LlmMessage::system("be brief"),
+            LlmMessage::user("look at the screenshot"),
+            LlmMessage {
+                role: MessageRole::Assistant,
+                content: MessageContent::MultiModal(vec![
+                    ContentBlock::Text {
+                        text: String::new(),
+                    },
+                    ContentBlock::ToolUse {
+                        id: "call_1".to_string(),
+                        name: "read".to_string(),
+                        input: json!({"path": "/tmp/shot.png"}),
+                    },
+                    ContentBlock::ToolUse {
+                        id: "call_2".to_string(),
+                        name: "bash".to_string(),
+                        input: json!({"command": "ls"}),
+                    },
+                ]),
+                metadata: None,
+            },
+            tool_result(
+                "call_1",
+                vec![
+                    ToolResultBlock::text("Image /tmp/shot.png"),
+                    ToolResultBlock::Image {
+                        data: "iVBORw0KGgo=".to_string(),
+                        mime_type: "image/png".to_string(),
+                    },
+                ],
+            ),
+            tool_result("call_2", vec![ToolResultBlock::Json { value: json!([1]) }]),
+        ];
+
+        let request = client().build_request(messages, None, None).unwrap();
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["system"], "be brief");
+        assert_eq!(
+            value["messages"],
+            json!([
+                {"role": "user", "content": [{"type": "text", "text": "look at the screenshot"}]},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "call_1", "name": "read", "input": {"path": "/tmp/shot.png"}},
+                    {"type": "tool_use", "id": "call_2", "name": "bash", "input": {"command": "ls"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "call_1", "is_error": false, "content": [
+                        {"type": "text", "text": "Image /tmp/shot.png"},
+                        {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="}}
+                    ]},
+                    {"type": "tool_result", "tool_use_id": "call_2", "is_error": false, "content": [
+                        {"type": "text", "text": "[1]"}
+                    ]}
+                ]}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_convert_response_keeps_tool_uses() {
+        let response: AnthropicResponse = serde_json::from_value(json!({
+            "id": "msg_1",
+            "model": "claude",
+            "type": "message",
+            "role": "assistant",
+            "content": [
+                {"type": "thinking", "thinking": "...", "signature": "sig"},
+                {"type": "text", "text": "Reading it"},
+                {"type": "tool_use", "id": "call_1", "name": "read", "input": {"path": "/a"}}
+            ],
+            "stop_reason": "tool_use",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        }))
+        .unwrap();
+
+        let response = client().convert_response(response);
+        assert_eq!(response.message.get_text().unwrap(), "Reading it");
+        assert_eq!(response.message.get_tool_uses().len(), 1);
+        assert!(matches!(
+            response.finish_reason,
+            Some(FinishReason::ToolCalls)
+        ));
+    }
+}
@@ -3,8 +3,8 @@
 use crate::config::ResolvedLlmConfig;
 use crate::error::{LlmError, Result};
 use crate::llm::{
-    ChatOptions, ContentBlock, FinishReason, LlmClient, LlmMessage, LlmResponse, LlmStreamChunk,
-    MessageContent, MessageRole, ToolDefinition, Usage,
+    tool_result_text, ChatOptions, ContentBlock, FinishReason, LlmClient, LlmMessage, LlmResponse,
+    LlmStreamChunk, MessageContent, MessageRole, ToolDefinition, ToolResultBlock, Usage,
 };
 use crate::tools::ToolCall;
 use async_openai::{
@@ -12,10 +12,12 @@ use async_openai::{
     types::{
         ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessage,
         ChatCompletionRequestAssistantMessageContent, ChatCompletionRequestMessage,
+        ChatCompletionRequestMessageContentPartImage, ChatCompletionRequestMessageContentPartText,
         ChatCompletionRequestSystemMessage, ChatCompletionRequestToolMessage,
         ChatCompletionRequestToolMessageContent, ChatCompletionRequestUserMessage,
+        ChatCompletionRequestUserMessageContent, ChatCompletionRequestUserMessageContentPart,
         ChatCompletionTool, ChatCompletionToolType, CreateChatCompletionRequestArgs,
-        FunctionObject,
+        FunctionObject, ImageUrl,
     },
     Client,
 };
@@ -156,6 +158,7 @@ impl OpenAiClient {
                 MessageRole::Tool => {
                     // Push tool result message(s) without dropping other context
                     let mut pushed_any = false;
+                    let mut images = Vec::new();
                     if let MessageContent::MultiModal(blocks) = &message.content {
                         for block in blocks {
                             if let ContentBlock::ToolResult {
@@ -167,11 +170,17 @@ impl OpenAiClient {
                                 converted.push(ChatCompletionRequestMessage::Tool(
                                     ChatCompletionRequestToolMessage {
                                         content: ChatCompletionRequestToolMessageContent::Text(
-                                            content.clone(),
+                                            tool_result_text(content),
                                         ),
                                         tool_call_id: tool_use_id.clone(),
                                     },
                                 ));
+                                images.extend(content.iter().filter_map(|block| match block {
+                                    ToolResultBlock::Image { data, mime_type } => {
+                                        Some(image_part(mime_type, data))
+                                    }
+                                    _ => None,
+                                }));
                                 pushed_any = true;
                             }
                         }
@@ -182,6 +191,22 @@ impl OpenAiClient {
                         })
                         .into());
                     }
+
+                    // Tool messages only hold text, so images follow as a user turn
+                    if !images.is_empty() {
+                        let mut parts = vec![ChatCompletionRequestUserMessageContentPart::Text(
+                            ChatCompletionRequestMessageContentPartText {
+                                text: "Images returned by the tool call above:".to_string(),
+                            },
+                        )];
+                        parts.extend(images);
+                        converted.push(ChatCompletionRequestMessage::User(
+                            ChatCompletionRequestUserMessage {
+                                content: ChatCompletionRequestUserMessageContent::Array(parts),
+                                name: None,
+                            },
+                        ));
+                    }
                 }
             }
         }
@@ -222,6 +247,18 @@ impl OpenAiClient {
     }
 }
 
+/// Encode an image as a data URL content part
+fn image_part(mime_type: &str, data: &str) -> ChatCompletionRequestUserMessageContentPart {
+    ChatCompletionRequestUserMessageContentPart::ImageUrl(
+        ChatCompletionRequestMessageContentPartImage {
+            image_url: ImageUrl {
+                url: format!("data:{};base64,{}", mime_type, data),
+                detail: None,
+            },
+        },
+    )
+}
+
 #[async_trait]
 impl LlmClient for OpenAiClient {
     async fn chat_completion(
@@ -120,8 +120,12 @@ pub enum AgentEvent {
     StepCompleted { step_info: AgentStepInfo },
     /// Tool execution started
     ToolExecutionStarted { tool_info: ToolExecutionInfo },
-    /// Tool execution status updated
-    ToolExecutionUpdated { tool_info: ToolExecutionInfo },
+    /// Tool produced output while still executing
+    ToolExecutionUpdated {
+        tool_info: ToolExecutionInfo,
+        /// Output produced since the previous update
+        output_chunk: String,
+    },
     /// Tool execution completed
     ToolExecutionCompleted { tool_info: ToolExecutionInfo },
     /// Agent thinking/reasoning
@@ -1,9 +1,12 @@
 //! Base tool traits and structures
 
 use crate::error::{Result, ToolError};
+use crate::llm::ToolResultBlock;
+use crate::tools::output_budget::OutputBudget;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
 /// Trait for all tools
@@ -21,6 +24,18 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with the given parameters
     async fn execute(&self, call: ToolCall) -> Result<ToolResult>;
 
+    /// Execute the tool, reporting output produced while it runs
+    ///
+    /// Long-running tools override this; the default ignores `progress`.
+    async fn execute_with_progress(
+        &self,
+        call: ToolCall,
+        progress: ToolProgress,
+    ) -> Result<ToolResult> {
+        let _ = progress;
+        self.execute(call).await
+    }
+
     /// Check if the tool requires special permissions
     fn requires_confirmation(&self) -> bool {
         false
@@ -32,6 +47,40 @@ pub trait Tool: Send + Sync {
     }
 }
 
+/// Sink for incremental output of a running tool
+#[derive(Debug, Clone, Default)]
+pub struct ToolProgress {
+    sender: Option<mpsc::UnboundedSender<String>>,
+}
+
+impl ToolProgress {
+    /// Create a sink forwarding output chunks to `sender`
+    pub fn new(sender: mpsc::UnboundedSender<String>) -> Self {
+        Self {
+            sender: Some(sender),
+        }
+    }
+
+    /// Create a sink that discards all output
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Whether reported output reaches anyone
+    pub fn is_enabled(&self) -> bool {
+        self.sender
+            .as_ref()
+            .is_some_and(|sender| !sender.is_closed())
+    }
+
+    /// Report a chunk of output
+    pub fn output<S: Into<String>>(&self, chunk: S) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(chunk.into());
+        }
+    }
+}
+
 /// A call to a tool
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
@@ -68,6 +117,10 @@ pub struct ToolResult {
 
     /// Optional metadata
     pub metadata: Option<HashMap<String, serde_json::Value>>,
+
+    /// Blocks shown to the model after `content`, such as images
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<ToolResultBlock>,
 }
 
 /// Example usage of a tool
@@ -86,6 +139,7 @@ pub struct ToolExample {
 /// Tool executor that manages tool execution
 pub struct ToolExecutor {
     tools: HashMap<String, Box<dyn Tool>>,
+    output_budget: OutputBudget,
 }
 
 impl ToolCall {
@@ -138,6 +192,7 @@ impl ToolResult {
             data: None,
             duration_ms: None,
             metadata: None,
+            blocks: Vec::new(),
         }
     }
 
@@ -150,6 +205,7 @@ impl ToolResult {
             data: None,
             duration_ms: None,
             metadata: None,
+            blocks: Vec::new(),
         }
     }
 
@@ -170,6 +226,31 @@ impl ToolResult {
         self.metadata = Some(metadata);
         self
     }
+
+    /// Attach a base64 encoded image
+    pub fn with_image<S: Into<String>>(mut self, mime_type: S, data: S) -> Self {
+        self.blocks.push(ToolResultBlock::Image {
+            data: data.into(),
+            mime_type: mime_type.into(),
+        });
+        self
+    }
+
+    /// Attach structured content for the model
+    pub fn with_json(mut self, value: serde_json::Value) -> Self {
+        self.blocks.push(ToolResultBlock::Json { value });
+        self
+    }
+
+    /// Everything shown to the model: `content` as text, then the attached blocks
+    pub fn content_blocks(&self) -> Vec<ToolResultBlock> {
+        let mut blocks = Vec::with_capacity(self.blocks.len() + 1);
+        if !self.content.is_empty() || self.blocks.is_empty() {
+            blocks.push(ToolResultBlock::text(self.content.clone()));
+        }
+        blocks.extend(self.blocks.iter().cloned());
+        blocks
+    }
 }
 
 impl ToolExecutor {
@@ -177,9 +258,15 @@ impl ToolExecutor {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            output_budget: OutputBudget::default(),
         }
     }
 
+    /// Set the budget every tool result is fitted in
+    pub fn set_output_budget(&mut self, output_budget: OutputBudget) {
+        self.output_budget = output_budget;
+    }
+
     /// Register a tool
     pub fn register_tool(&mut self, tool: Box<dyn Tool>) {
         self.tools.insert(tool.name().to_string(), tool);
@@ -197,6 +284,16 @@ impl ToolExecutor {
 
     /// Execute a tool call
     pub async fn execute(&self, call: ToolCall) -> Result<ToolResult> {
+        self.execute_with_progress(call, ToolProgress::disabled())
+            .await
+    }
+
+    /// Execute a tool call, reporting its output while it runs
+    pub async fn execute_with_progress(
+        &self,
+        call: ToolCall,
+        progress: ToolProgress,
+    ) -> Result<ToolResult> {
         let tool = self
             .get_tool(&call.name)
             .ok_or_else(|| ToolError::NotFound {
@@ -205,16 +302,17 @@ impl ToolExecutor {
 
         let start_time = std::time::Instant::now();
         let call_id = call.id.clone();
-        let result = tool.execute(call).await;
+        let result = tool.execute_with_progress(call, progress).await;
         let duration = start_time.elapsed().as_millis() as u64;
 
-        match result {
-            Ok(mut result) => {
-                result.duration_ms = Some(duration);
-                Ok(result)
-            }
-            Err(e) => Ok(ToolResult::error(&call_id, &e.to_string()).with_duration(duration)),
-        }
+        let mut result = match result {
+            Ok(result) => result.with_duration(duration),
+            Err(e) => ToolResult::error(&call_id, &e.to_string()).with_duration(duration),
+        };
+
+        self.output_budget.fit_result(&call_id, &mut result);
+
+        Ok(result)
     }
 
     /// Get tool definitions for LLM function calling
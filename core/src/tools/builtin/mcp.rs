@@ -1,8 +1,7 @@
 //! MCP (Model Context Protocol) tool support
 
 use crate::error::Result;
-use crate::impl_tool_factory;
-use crate::tools::{Tool, ToolCall, ToolExample, ToolResult};
+use crate::tools::{Tool, ToolCall, ToolExample, ToolFactory, ToolResult};
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -74,7 +73,7 @@ impl McpServer {
     /// Stop the MCP server
     pub fn stop(&mut self) {
         if let Some(mut process) = self.process.take() {
-            std::mem::drop(process.kill());
+            let _ = process.start_kill();
         }
         self.started = false;
     }
@@ -208,6 +207,8 @@ impl McpServer {
 /// Tool for interacting with MCP servers
 pub struct McpTool {
     servers: Arc<Mutex<HashMap<String, McpServer>>>,
+    /// Servers from the configuration, started by name
+    configured: HashMap<String, McpServerConfig>,
 }
 
 impl Default for McpTool {
@@ -218,8 +219,17 @@ impl Default for McpTool {
 
 impl McpTool {
     pub fn new() -> Self {
+        Self::with_servers(Vec::new())
+    }
+
+    /// Create the tool with servers that can be started by name only
+    pub fn with_servers(servers: Vec<McpServerConfig>) -> Self {
         Self {
             servers: Arc::new(Mutex::new(HashMap::new())),
+            configured: servers
+                .into_iter()
+                .map(|server| (server.name.clone(), server))
+                .collect(),
         }
     }
 }
@@ -238,9 +248,9 @@ impl Tool for McpTool {
          * Handles JSON-RPC communication with MCP servers\n\
          \n\
          Operations:\n\
-         - `start_server`: Start an MCP server with given configuration\n\
+         - `start_server`: Start an MCP server with given configuration, or a configured server by name\n\
          - `stop_server`: Stop a running MCP server\n\
-         - `list_servers`: List all configured MCP servers\n\
+         - `list_servers`: List running and configured MCP servers\n\
          - `list_tools`: List tools available from a specific MCP server\n\
          - `call_tool`: Call a tool on a specific MCP server\n\
          \n\
@@ -265,7 +275,7 @@ impl Tool for McpTool {
                 "command": {
                     "type": "array",
                     "items": {"type": "string"},
-                    "description": "Command to start the MCP server (required for start_server)"
+                    "description": "Command to start the MCP server (required for start_server unless the server is configured)"
                 },
                 "args": {
                     "type": "array",
@@ -299,11 +309,18 @@ impl Tool for McpTool {
         match operation.as_str() {
             "start_server" => {
                 let server_name: String = call.get_parameter("server_name")?;
-                let command: Vec<String> = call.get_parameter("command")?;
-                let args: Vec<String> = call.get_parameter_or("args", Vec::new());
-                let env: HashMap<String, String> = call.get_parameter_or("env", HashMap::new());
-                let timeout_seconds: u64 = call.get_parameter_or("timeout_seconds", 30);
-                self.start_server(&call.id, server_name, command, args, env, timeout_seconds).await
+                let command: Vec<String> = call.get_parameter_or("command", Vec::new());
+                let config = match self.configured.get(&server_name) {
+                    Some(config) if command.is_empty() => config.clone(),
+                    _ => McpServerConfig {
+                        name: server_name,
+                        command,
+                        args: call.get_parameter_or("args", Vec::new()),
+                        env: call.get_parameter_or("env", HashMap::new()),
+                        timeout_seconds: call.get_parameter_or("timeout_seconds", 30),
+                    },
+                };
+                self.start_server(&call.id, config).await
             }
             "stop_server" => {
                 let server_name: String = call.get_parameter("server_name")?;
@@ -366,27 +383,12 @@ impl Tool for McpTool {
 
 impl McpTool {
     /// Start an MCP server
-    async fn start_server(
-        &self,
-        call_id: &str,
-        server_name: String,
-        command: Vec<String>,
-        args: Vec<String>,
-        env: HashMap<String, String>,
-        timeout_seconds: u64,
-    ) -> Result<ToolResult> {
-        if command.is_empty() {
+    async fn start_server(&self, call_id: &str, config: McpServerConfig) -> Result<ToolResult> {
+        if config.command.is_empty() {
             return Ok(ToolResult::error(call_id, "Command cannot be empty"));
         }
 
-        let config = McpServerConfig {
-            name: server_name.clone(),
-            command,
-            args,
-            env,
-            timeout_seconds,
-        };
-
+        let server_name = config.name.clone();
         let mut server = McpServer::new(config);
 
         match server.start().await {
@@ -428,14 +430,18 @@ impl McpTool {
     async fn list_servers(&self, call_id: &str) -> Result<ToolResult> {
         let servers = self.servers.lock().await;
 
-        if servers.is_empty() {
+        if servers.is_empty() && self.configured.is_empty() {
             return Ok(ToolResult::success(
                 call_id,
                 "No MCP servers are currently running",
             ));
         }
 
-        let mut result = String::from("Running MCP servers:\n\n");
+        let mut result = if servers.is_empty() {
+            String::from("No MCP servers are currently running\n")
+        } else {
+            String::from("Running MCP servers:\n\n")
+        };
         for (name, server) in servers.iter() {
             result.push_str(&format!(
                 "- {} (command: {:?}, started: {})\n",
@@ -443,6 +449,19 @@ impl McpTool {
             ));
         }
 
+        let mut stopped: Vec<_> = self
+            .configured
+            .keys()
+            .filter(|name| !servers.contains_key(*name))
+            .collect();
+        if !stopped.is_empty() {
+            stopped.sort();
+            result.push_str("\nConfigured MCP servers, start them by name:\n\n");
+            for name in stopped {
+                result.push_str(&format!("- {}\n", name));
+            }
+        }
+
         Ok(ToolResult::success(call_id, &result))
     }
 
@@ -547,9 +566,29 @@ impl McpTool {
     }
 }
 
-impl_tool_factory!(
-    McpToolFactory,
-    McpTool,
-    "mcp_tool",
-    "Tool for interacting with MCP (Model Context Protocol) servers"
-);
+/// Factory for creating McpTool instances
+#[derive(Default)]
+pub struct McpToolFactory {
+    servers: Vec<McpServerConfig>,
+}
+
+impl McpToolFactory {
+    /// Create a factory whose tools know the configured servers
+    pub fn with_servers(servers: Vec<McpServerConfig>) -> Self {
+        Self { servers }
+    }
+}
+
+impl ToolFactory for McpToolFactory {
+    fn create(&self) -> Box<dyn Tool> {
+        Box::new(McpTool::with_servers(self.servers.clone()))
+    }
+
+    fn tool_name(&self) -> &str {
+        "mcp_tool"
+    }
+
+    fn tool_description(&self) -> &str {
+        "Tool for interacting with MCP (Model Context Protocol) servers"
+    }
+}
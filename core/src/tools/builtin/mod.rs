@@ -4,6 +4,6 @@ pub mod mcp;
 pub mod task_done;
 pub mod thinking;
 
-pub use mcp::{McpTool, McpToolFactory};
+pub use mcp::{McpServer, McpServerConfig, McpTool, McpToolFactory};
 pub use task_done::{TaskDoneTool, TaskDoneToolFactory};
 pub use thinking::{ThinkingTool, ThinkingToolFactory};
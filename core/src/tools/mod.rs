@@ -2,9 +2,11 @@
 
 pub mod base;
 pub mod builtin;
+pub mod output_budget;
 pub mod output_formatter;
 pub mod registry;
 pub mod utils;
 
-pub use base::{Tool, ToolCall, ToolExample, ToolExecutor, ToolResult};
+pub use base::{Tool, ToolCall, ToolExample, ToolExecutor, ToolProgress, ToolResult};
+pub use output_budget::OutputBudget;
 pub use registry::{ToolFactory, ToolRegistry};
@@ -0,0 +1,486 @@
+//! Shared budget for the size of tool output
+//!
+//! Output over the budget keeps its head and tail. When a spill directory is
+//! set, the full output is saved to a file whose path replaces the middle, so
+//! the model can view ranges of it instead of running the tool again.
+
+use crate::config::redact;
+use crate::llm::ToolResultBlock;
+use crate::tools::ToolResult;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Default tokens of output kept in a tool result
+pub const DEFAULT_MAX_OUTPUT_TOKENS: usize = 8_000;
+
+/// Tokens counted for an image, about what providers charge for one after
+/// scaling it down
+const IMAGE_TOKENS: usize = 1_600;
+
+/// Spill files kept per session, older ones are removed
+const MAX_SPILL_FILES_PER_SESSION: usize = 100;
+
+/// Age after which spill files of other sessions are removed
+const MAX_SPILL_FILE_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Cost of a character in quarter tokens
+///
+/// ASCII text averages about four characters per token, other characters
+/// such as CJK are about one token each.
+fn char_cost(ch: char) -> usize {
+    if ch.is_ascii() {
+        1
+    } else {
+        4
+    }
+}
+
+fn text_cost(text: &str) -> usize {
+    text.chars().map(char_cost).sum()
+}
+
+/// Estimate the number of tokens of `text`
+pub fn estimate_tokens(text: &str) -> usize {
+    text_cost(text).div_ceil(4)
+}
+
+/// Bytes of the longest head of `text` within `budget` quarter tokens
+///
+/// Whole lines are kept; only a first line over budget is cut inside.
+fn head_len(text: &str, budget: usize) -> usize {
+    let mut used = 0;
+    let mut len = 0;
+    for line in text.split_inclusive('\n') {
+        let cost = text_cost(line);
+        if used + cost > budget {
+            if len == 0 {
+                for (index, ch) in line.char_indices() {
+                    used += char_cost(ch);
+                    if used > budget {
+                        return index;
+                    }
+                }
+            }
+            break;
+        }
+        used += cost;
+        len += line.len();
+    }
+    len
+}
+
+/// Bytes of the longest tail of `text` within `budget` quarter tokens
+fn tail_len(text: &str, budget: usize) -> usize {
+    let mut used = 0;
+    let mut len = 0;
+    for line in text.split_inclusive('\n').rev() {
+        let cost = text_cost(line);
+        if used + cost > budget {
+            if len == 0 {
+                for (index, ch) in line.char_indices().rev() {
+                    used += char_cost(ch);
+                    if used > budget {
+                        return line.len() - index - ch.len_utf8();
+                    }
+                }
+            }
+            break;
+        }
+        used += cost;
+        len += line.len();
+    }
+    len
+}
+
+fn block_tokens(block: &ToolResultBlock) -> usize {
+    match block {
+        ToolResultBlock::Text { text } => estimate_tokens(text),
+        ToolResultBlock::Image { .. } => IMAGE_TOKENS,
+        ToolResultBlock::Json { value } => estimate_tokens(&value.to_string()),
+    }
+}
+
+/// Directory in the user's data directory for full tool outputs
+pub fn default_spill_dir() -> PathBuf {
+    match dirs::data_dir() {
+        Some(dir) => dir.join("coro").join("tool-output"),
+        None => std::env::temp_dir().join("coro-tool-output"),
+    }
+}
+
+/// Split `text` into a head and a tail that together fit in `max_tokens`,
+/// or `None` when the whole text fits
+pub fn head_and_tail(text: &str, max_tokens: usize) -> Option<(&str, &str)> {
+    if estimate_tokens(text) <= max_tokens {
+        return None;
+    }
+
+    let head_budget = max_tokens * 2;
+    let head = head_len(text, head_budget);
+    let tail = tail_len(&text[head..], max_tokens * 4 - head_budget);
+    Some((&text[..head], &text[text.len() - tail..]))
+}
+
+/// Output cut to fit a budget
+#[derive(Debug, Clone)]
+pub struct FittedOutput {
+    /// Head and tail of the output with a note on what was left out
+    pub content: String,
+    /// File holding the full output, if it was written
+    pub spill_path: Option<PathBuf>,
+}
+
+/// Budget applied to every tool result by the tool executor
+#[derive(Debug, Clone)]
+pub struct OutputBudget {
+    max_tokens: usize,
+    spill_dir: Option<PathBuf>,
+    /// Prefix of spill file names, so budgets of concurrent sessions never
+    /// write the same file
+    session: String,
+}
+
+impl OutputBudget {
+    /// Create a budget of `max_tokens` that does not save full outputs
+    pub fn new(max_tokens: usize) -> Self {
+        let session = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            max_tokens,
+            spill_dir: None,
+            session: session[..8].to_string(),
+        }
+    }
+
+    /// Save the full output of clipped results to files in `dir`
+    pub fn with_spill_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.spill_dir = Some(dir.into());
+        self
+    }
+
+    /// Fit the output of tool call `call_id` in the budget, or `None` when it
+    /// already fits
+    pub fn fit(&self, call_id: &str, output: &str) -> Option<FittedOutput> {
+        self.fit_within(call_id, output, self.max_tokens)
+    }
+
+    /// Fit a tool result in the budget, its attached blocks included
+    ///
+    /// Blocks are kept in order while they fit. An image past the budget is
+    /// replaced by a note and JSON is clipped as text; the content gets the
+    /// tokens that are left.
+    pub fn fit_result(&self, call_id: &str, result: &mut ToolResult) {
+        let mut remaining = self.max_tokens;
+        for (index, block) in result.blocks.iter_mut().enumerate() {
+            let tokens = block_tokens(block);
+            if tokens <= remaining {
+                remaining -= tokens;
+                continue;
+            }
+            let text = match block {
+                ToolResultBlock::Text { text } => std::mem::take(text),
+                ToolResultBlock::Json { value } => {
+                    serde_json::to_string_pretty(value).unwrap_or_default()
+                }
+                ToolResultBlock::Image { .. } => {
+                    *block = ToolResultBlock::text(format!(
+                        "<image omitted: the tool output budget of {} tokens is used up>",
+                        self.max_tokens
+                    ));
+                    continue;
+                }
+            };
+            let id = format!("{}-{}", call_id, index + 1);
+            let fitted = self.fit_within(&id, &text, remaining);
+            *block = ToolResultBlock::text(fitted.map_or(text, |fitted| fitted.content));
+            remaining = 0;
+        }
+
+        // Large outputs keep their head and tail, the rest goes to a file
+        if let Some(fitted) = self.fit_within(call_id, &result.content, remaining) {
+            result.content = fitted.content;
+            if let Some(path) = fitted.spill_path {
+                result.metadata.get_or_insert_with(HashMap::new).insert(
+                    "full_output_path".to_string(),
+                    serde_json::Value::String(path.display().to_string()),
+                );
+            }
+        }
+    }
+
+    fn fit_within(&self, call_id: &str, output: &str, max_tokens: usize) -> Option<FittedOutput> {
+        let (head, tail) = head_and_tail(output, max_tokens)?;
+        let omitted = &output[head.len()..output.len() - tail.len()];
+        let spill_path = self
+            .spill_dir
+            .as_ref()
+            .map(|dir| self.spill(dir, call_id, output));
+
+        let first_omitted = head.matches('\n').count() + 1;
+        let last_omitted = first_omitted + omitted.lines().count().saturating_sub(1);
+        let location = match &spill_path {
+            Some(Ok(path)) => format!(
+                "the full output ({} lines) is saved at {}, view line ranges of it instead of running the tool again",
+                output.lines().count(),
+                path.display()
+            ),
+            Some(Err(_)) => "the full output could not be saved".to_string(),
+            None => "narrow the command to see the omitted part".to_string(),
+        };
+
+        let mut content = head.to_string();
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&format!(
+            "<output clipped: about {} tokens of lines {}-{} omitted; {}>\n",
+            estimate_tokens(omitted),
+            first_omitted,
+            last_omitted,
+            location
+        ));
+        content.push_str(tail);
+
+        Some(FittedOutput {
+            content,
+            spill_path: spill_path.and_then(|path| path.ok()),
+        })
+    }
+
+    /// Write the full output, with secrets redacted, to a file named after
+    /// the session and the tool call
+    fn spill(&self, dir: &Path, call_id: &str, output: &str) -> std::io::Result<PathBuf> {
+        let name: String = call_id
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                    ch
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        create_private_dir(dir)?;
+        self.prune(dir);
+        let path = dir.join(format!("{}-{}.txt", self.session, name));
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&path)?.write_all(redact(output).as_bytes())?;
+        Ok(path)
+    }
+
+    /// Make room for another spill file of this session, and remove the
+    /// files of other sessions once they are old
+    fn prune(&self, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let prefix = format!("{}-", self.session);
+        let mut own = Vec::new();
+        for entry in entries.flatten() {
+            let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified()) else {
+                continue;
+            };
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                own.push((modified, entry.path()));
+            } else if SystemTime::now()
+                .duration_since(modified)
+                .is_ok_and(|age| age > MAX_SPILL_FILE_AGE)
+            {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+
+        if own.len() >= MAX_SPILL_FILES_PER_SESSION {
+            own.sort();
+            for (_, path) in &own[..=own.len() - MAX_SPILL_FILES_PER_SESSION] {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Create `dir` only accessible by the user
+///
+/// An existing directory is restricted too, which fails if it belongs to
+/// someone else.
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+    }
+    #[cfg(not(unix))]
+    {
+        std::fs::create_dir_all(dir)
+    }
+}
+
+impl Default for OutputBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_OUTPUT_TOKENS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+        assert_eq!(estimate_tokens("构建"), 2);
+    }
+
+    #[test]
+    fn test_head_and_tail_keep_whole_lines() {
+        let text: String = (1..=100).map(|i| format!("line {:04}\n", i)).collect();
+        assert!(head_and_tail(&text, 1000).is_none());
+
+        // Each line is 2.5 tokens, so 4 lines fit in each half of the budget
+        let (head, tail) = head_and_tail(&text, 20).unwrap();
+        assert_eq!(head, "line 0001\nline 0002\nline 0003\nline 0004\n");
+        assert_eq!(tail, "line 0097\nline 0098\nline 0099\nline 0100\n");
+    }
+
+    #[test]
+    fn test_head_and_tail_cut_a_long_line() {
+        let text = "x".repeat(100);
+        let (head, tail) = head_and_tail(&text, 10).unwrap();
+        assert_eq!(head.len(), 20);
+        assert_eq!(tail.len(), 20);
+
+        let text = "构".repeat(100);
+        let (head, tail) = head_and_tail(&text, 10).unwrap();
+        assert_eq!(head.chars().count(), 5);
+        assert_eq!(tail.chars().count(), 5);
+    }
+
+    #[test]
+    fn test_fit_spills_the_full_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let budget = OutputBudget::new(20).with_spill_dir(dir.path());
+        let text: String = (1..=100).map(|i| format!("line {:04}\n", i)).collect();
+
+        assert!(budget.fit("call", "short").is_none());
+
+        let fitted = budget.fit("call/1", &text).unwrap();
+        let path = fitted.spill_path.unwrap();
+        assert_eq!(
+            path,
+            dir.path().join(format!("{}-call_1.txt", budget.session))
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        assert!(fitted.content.starts_with("line 0001\n"));
+        assert!(fitted.content.ends_with("line 0100\n"));
+        assert!(fitted.content.contains("lines 5-96 omitted"));
+        assert!(fitted.content.contains(&path.display().to_string()));
+    }
+
+    #[test]
+    fn test_fit_without_spill_dir_saves_nothing() {
+        let budget = OutputBudget::new(20);
+        let text: String = (1..=100).map(|i| format!("line {:04}\n", i)).collect();
+
+        let fitted = budget.fit("call", &text).unwrap();
+        assert!(fitted.spill_path.is_none());
+        assert!(fitted.content.contains("narrow the command"));
+    }
+
+    #[test]
+    fn test_fit_result_counts_attached_blocks() {
+        let budget = OutputBudget::new(2_000);
+        let text: String = (1..=1000).map(|i| format!("line {:04}\n", i)).collect();
+        let mut result = ToolResult::success("call", text.as_str())
+            .with_image("image/png", "AAAA")
+            .with_image("image/png", "BBBB")
+            .with_json(serde_json::json!({ "cells": 2 }));
+
+        budget.fit_result("call", &mut result);
+
+        assert!(matches!(result.blocks[0], ToolResultBlock::Image { .. }));
+        assert!(
+            matches!(&result.blocks[1], ToolResultBlock::Text { text } if text.contains("image omitted"))
+        );
+        assert!(matches!(result.blocks[2], ToolResultBlock::Json { .. }));
+        // The content is clipped to the 400 tokens the first image leaves
+        assert!(result.content.contains("<output clipped"));
+        assert!(estimate_tokens(&result.content) < 500);
+        assert!(result.content.starts_with("line 0001\n"));
+        assert!(result.metadata.is_none());
+    }
+
+    #[test]
+    fn test_spill_files_are_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let budget = OutputBudget::new(20).with_spill_dir(dir.path());
+        let text: String = (1..=100).map(|i| format!("line {:04}\n", i)).collect();
+        let stale = dir.path().join("0badcafe-call_1.txt");
+        let recent = dir.path().join("0badcafe-call_2.txt");
+        for path in [&stale, &recent] {
+            std::fs::write(path, "output").unwrap();
+        }
+        std::fs::File::options()
+            .write(true)
+            .open(&stale)
+            .unwrap()
+            .set_modified(SystemTime::now() - MAX_SPILL_FILE_AGE * 2)
+            .unwrap();
+
+        for call in 0..=MAX_SPILL_FILES_PER_SESSION {
+            budget.fit(&format!("call_{}", call), &text).unwrap();
+        }
+
+        let own = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().starts_with(&budget.session)
+            })
+            .count();
+        assert_eq!(own, MAX_SPILL_FILES_PER_SESSION);
+        assert!(!stale.exists());
+        assert!(recent.exists());
+    }
+
+    #[test]
+    fn test_spill_files_are_private_to_the_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let spill_dir = dir.path().join("tool-output");
+        let first = OutputBudget::new(20).with_spill_dir(&spill_dir);
+        let second = OutputBudget::new(20).with_spill_dir(&spill_dir);
+        let secret = "sk-output-budget-test-0123456789";
+        crate::config::register_secret(secret);
+        let text: String = (1..=100)
+            .map(|i| format!("line {:04} token={}\n", i, secret))
+            .collect();
+
+        let first_path = first.fit("call_1", &text).unwrap().spill_path.unwrap();
+        let second_path = second.fit("call_1", &text).unwrap().spill_path.unwrap();
+        assert_ne!(first_path, second_path);
+
+        let spilled = std::fs::read_to_string(&first_path).unwrap();
+        assert_eq!(spilled.lines().count(), 100);
+        assert!(!spilled.contains(secret));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&spill_dir), 0o700);
+            assert_eq!(mode(&first_path), 0o600);
+        }
+    }
+}
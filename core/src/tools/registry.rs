@@ -83,7 +83,7 @@ impl Default for ToolRegistry {
         // Register built-in tools
         registry.register_factory(Box::new(crate::tools::builtin::ThinkingToolFactory));
         registry.register_factory(Box::new(crate::tools::builtin::TaskDoneToolFactory));
-        registry.register_factory(Box::new(crate::tools::builtin::McpToolFactory));
+        registry.register_factory(Box::new(crate::tools::builtin::McpToolFactory::default()));
 
         registry
     }
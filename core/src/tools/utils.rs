@@ -3,7 +3,7 @@
 pub mod run;
 
 use crate::error::Result;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use tokio::process::Command;
 use tokio::time::{timeout, Duration};
 
@@ -23,7 +23,11 @@ pub fn maybe_truncate(content: &str, truncate_after: Option<usize>) -> String {
     if content.len() <= limit {
         content.to_string()
     } else {
-        format!("{}{}", &content[..limit], TRUNCATED_MESSAGE)
+        let mut end = limit;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}{}", &content[..end], TRUNCATED_MESSAGE)
     }
 }
 
@@ -123,6 +127,53 @@ pub fn validate_directory_operation(path: &Path, operation: &str) -> Result<()>
     Ok(())
 }
 
+/// Resolve a path against the workspace root, rejecting paths outside it
+///
+/// Relative paths are joined to `root`. `.` and `..` are resolved without
+/// touching the file system, so the path does not need to exist. The deepest
+/// part of the path that does exist is then resolved with its symlinks, so a
+/// link cannot lead out of the workspace either.
+pub fn resolve_workspace_path(root: &Path, path: &str) -> Result<PathBuf> {
+    let mut resolved = PathBuf::new();
+    for component in root.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => resolved.push(other),
+        }
+    }
+
+    let outside = || {
+        format!(
+            "The path {} is outside the workspace {}",
+            path,
+            root.display()
+        )
+    };
+    if !resolved.starts_with(root) {
+        return Err(outside().into());
+    }
+
+    // A dangling symlink counts as existing, it fails to resolve below
+    let mut existing = resolved.as_path();
+    while existing.symlink_metadata().is_err() {
+        match existing.parent() {
+            Some(parent) if parent.starts_with(root) => existing = parent,
+            _ => return Ok(resolved),
+        }
+    }
+    let canonical_root = root.canonicalize()?;
+    let canonical = existing
+        .canonicalize()
+        .map_err(|e| format!("Cannot resolve {}: {}", existing.display(), e))?;
+    if !canonical.starts_with(&canonical_root) {
+        return Err(outside().into());
+    }
+    Ok(resolved)
+}
+
 /// Expand tabs in text content
 pub fn expand_tabs(content: &str) -> String {
     content.replace('\t', "    ")
@@ -155,6 +206,10 @@ mod tests {
         let truncated = maybe_truncate(&long_content, Some(50));
         assert!(truncated.len() > 50);
         assert!(truncated.contains(TRUNCATED_MESSAGE));
+
+        // Cuts fall on character boundaries
+        let truncated = maybe_truncate(&"构".repeat(20), Some(10));
+        assert!(truncated.starts_with("构构构<response clipped>"));
     }
 
     #[test]
@@ -166,6 +221,50 @@ mod tests {
         assert!(formatted.contains("    12\tline3"));
     }
 
+    #[test]
+    fn test_resolve_workspace_path() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            resolve_workspace_path(root, "src/./lib.rs").unwrap(),
+            Path::new("/repo/src/lib.rs")
+        );
+        assert_eq!(
+            resolve_workspace_path(root, "/repo/a/../b.rs").unwrap(),
+            Path::new("/repo/b.rs")
+        );
+        assert!(resolve_workspace_path(root, "../etc/passwd").is_err());
+        assert!(resolve_workspace_path(root, "/etc/passwd").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_workspace_path_follows_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir(&outside).unwrap();
+        symlink(&outside, root.join("out")).unwrap();
+        symlink(outside.join("missing.txt"), root.join("dangling")).unwrap();
+        symlink(root.join("src"), root.join("code")).unwrap();
+
+        assert!(resolve_workspace_path(&root, "out").is_err());
+        assert!(resolve_workspace_path(&root, "out/new/file.txt").is_err());
+        assert!(resolve_workspace_path(&root, "dangling").is_err());
+        assert_eq!(
+            resolve_workspace_path(&root, "code/new/lib.rs").unwrap(),
+            root.join("code/new/lib.rs")
+        );
+
+        // The root itself may be reached through a symlink
+        let linked_root = dir.path().join("linked");
+        symlink(&root, &linked_root).unwrap();
+        assert!(resolve_workspace_path(&linked_root, "src/lib.rs").is_ok());
+        assert!(resolve_workspace_path(&linked_root, "out/file.txt").is_err());
+    }
+
     #[test]
     fn test_expand_tabs() {
         let content = "hello\tworld\t!";
@@ -1,6 +1,7 @@
 //! Tool execution utilities
 
 use crate::error::Result;
+use crate::tools::output_budget::{estimate_tokens, head_and_tail, DEFAULT_MAX_OUTPUT_TOKENS};
 use std::collections::HashMap;
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, BufReader};
@@ -11,7 +12,8 @@ use tokio::time::{timeout, Duration, Instant};
 #[derive(Debug, Clone)]
 pub struct CommandOptions {
     pub timeout_seconds: Option<u64>,
-    pub truncate_after: Option<usize>,
+    /// Tokens of output kept from the head and tail of longer output
+    pub max_output_tokens: Option<usize>,
     pub working_directory: Option<String>,
     pub environment: HashMap<String, String>,
     pub capture_stderr: bool,
@@ -22,7 +24,7 @@ impl Default for CommandOptions {
     fn default() -> Self {
         Self {
             timeout_seconds: Some(120),
-            truncate_after: Some(16000),
+            max_output_tokens: Some(DEFAULT_MAX_OUTPUT_TOKENS),
             working_directory: None,
             environment: HashMap::new(),
             capture_stderr: true,
@@ -96,7 +98,9 @@ pub async fn execute_command(command: &str, options: CommandOptions) -> Result<C
 
     match result {
         Ok(Ok((exit_code, stdout, stderr))) => {
-            let truncate_limit = options.truncate_after.unwrap_or(16000);
+            let truncate_limit = options
+                .max_output_tokens
+                .unwrap_or(DEFAULT_MAX_OUTPUT_TOKENS);
             let (stdout_truncated, stdout_final) = truncate_output(&stdout, truncate_limit);
             let (stderr_truncated, stderr_final) = truncate_output(&stderr, truncate_limit);
 
@@ -180,19 +184,22 @@ async fn execute_child(child: &mut Child, capture_stderr: bool) -> Result<(i32,
     Ok((exit_code, stdout_output, stderr_output))
 }
 
-/// Truncate output if it exceeds the limit
-fn truncate_output(output: &str, limit: usize) -> (bool, String) {
-    if output.len() <= limit {
-        (false, output.to_string())
-    } else {
-        let truncated = format!(
-            "{}\n\n<output truncated after {} characters>\n\
-             <NOTE>To see the full output, increase the truncate_after limit or \
-             redirect output to a file.</NOTE>",
-            &output[..limit],
-            limit
-        );
-        (true, truncated)
+/// Keep the head and tail of output over `max_tokens`
+fn truncate_output(output: &str, max_tokens: usize) -> (bool, String) {
+    match head_and_tail(output, max_tokens) {
+        None => (false, output.to_string()),
+        Some((head, tail)) => {
+            let omitted = &output[head.len()..output.len() - tail.len()];
+            let truncated = format!(
+                "{}\n<output truncated: about {} tokens omitted>\n\
+                 <NOTE>To see the full output, increase the max_output_tokens limit or \
+                 redirect output to a file.</NOTE>\n{}",
+                head,
+                estimate_tokens(omitted),
+                tail
+            );
+            (true, truncated)
+        }
     }
 }
 
@@ -297,7 +304,9 @@ pub async fn stream_command(
         }
     }
 
-    let truncate_limit = options.truncate_after.unwrap_or(16000);
+    let truncate_limit = options
+        .max_output_tokens
+        .unwrap_or(DEFAULT_MAX_OUTPUT_TOKENS);
     let (truncated, final_output) = truncate_output(&all_output, truncate_limit);
 
     Ok(CommandResult {
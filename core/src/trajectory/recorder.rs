@@ -1,5 +1,6 @@
 //! Trajectory recorder implementation
 
+use crate::config::redact;
 use crate::error::{Result, TrajectoryError};
 use crate::trajectory::TrajectoryEntry;
 use chrono::{DateTime, Utc};
@@ -129,7 +130,8 @@ impl TrajectoryRecorder {
                 fs::create_dir_all(parent).await?;
             }
 
-            fs::write(path, json).await?;
+            // Tool output and messages may echo API keys
+            fs::write(path, redact(&json).as_bytes()).await?;
         }
 
         Ok(())